@@ -1,9 +1,12 @@
-use crate::{types::*, Error, Script};
+use crate::{base58, bech32, types::*, Error, Script};
 use bitcoin_hashes::{hash160::Hash as Hash160, Hash};
 use codec::{Decode, Encode};
 use sha2::{Digest, Sha256};
 use sp_core::{H160, H256};
-use sp_std::vec::Vec;
+use sp_std::{vec, vec::Vec};
+
+#[cfg(feature = "std")]
+use codec::alloc::string::String;
 
 use secp256k1::{constants::PUBLIC_KEY_SIZE, Error as Secp256k1Error, PublicKey as Secp256k1PublicKey};
 
@@ -29,8 +32,56 @@ pub enum Address {
     // output: OP_0 {sha256(redeem_script)}
     // witness: [redeem_script_sig ...] {redeem_script}
     P2WSHv0(H256),
+    // input: <>
+    // output: OP_1 {tweaked_x_only_pubkey}
+    // witness: {signature} or [script_input ...] {script} {control_block}
+    P2TRv1(H256),
+}
+
+/// Bitcoin network, used to select the address prefixes when converting
+/// an `Address` to or from its human-readable representation.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Regtest,
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        Network::Mainnet
+    }
 }
 
+impl Network {
+    // https://github.com/bitcoin/bitcoin/blob/5ba5becbb5d8c794efe579caeea7eea64f895a13/src/chainparams.cpp#L136-L137
+    fn p2pkh_prefix(self) -> u8 {
+        match self {
+            Network::Mainnet => 0x00,
+            Network::Testnet | Network::Regtest => 0x6f,
+        }
+    }
+
+    fn p2sh_prefix(self) -> u8 {
+        match self {
+            Network::Mainnet => 0x05,
+            Network::Testnet | Network::Regtest => 0xc4,
+        }
+    }
+
+    fn bech32_hrp(self) -> &'static [u8] {
+        match self {
+            Network::Mainnet => b"bc",
+            Network::Testnet => b"tb",
+            Network::Regtest => b"bcrt",
+        }
+    }
+}
+
+/// Maximum witness version, encoded as OP_1 .. OP_16 in the script
+const MAX_WITNESS_VERSION: u8 = 16;
+
 impl Address {
     pub fn from_script_pub_key(script: &Script) -> Result<Self, Error> {
         const OP_DUP: u8 = OpCode::OpDup as u8;
@@ -39,6 +90,7 @@ impl Address {
         const OP_CHECK_SIG: u8 = OpCode::OpCheckSig as u8;
         const OP_EQUAL: u8 = OpCode::OpEqual as u8;
         const OP_0: u8 = OpCode::Op0 as u8;
+        const OP_1: u8 = OpCode::Op1 as u8;
 
         match script.as_bytes() {
             &[OP_DUP, OP_HASH_160, HASH160_SIZE_HEX, ref addr @ .., OP_EQUAL_VERIFY, OP_CHECK_SIG]
@@ -55,6 +107,9 @@ impl Address {
             &[OP_0, HASH160_SIZE_HEX, ref addr @ ..] if addr.len() == HASH160_SIZE_HEX as usize => {
                 Ok(Self::P2WPKHv0(H160::from_slice(addr)))
             }
+            &[OP_1, HASH256_SIZE_HEX, ref addr @ ..] if addr.len() == HASH256_SIZE_HEX as usize => {
+                Ok(Self::P2TRv1(H256::from_slice(addr)))
+            }
             _ => Err(Error::InvalidBtcAddress),
         }
     }
@@ -93,6 +148,113 @@ impl Address {
                 script.append(script_hash);
                 script
             }
+            Self::P2TRv1(output_key) => {
+                let mut script = Script::new();
+                script.append(OpCode::Op1);
+                script.append(HASH256_SIZE_HEX);
+                script.append(output_key);
+                script
+            }
+        }
+    }
+
+    /// Encodes the address as Base58Check (P2PKH, P2SH) or Bech32(m) (witness
+    /// programs), using the prefixes of the given network.
+    pub fn encode_str(&self, network: Network) -> Vec<u8> {
+        match self {
+            Self::P2PKH(hash) => Self::encode_base58(network.p2pkh_prefix(), hash.as_bytes()),
+            Self::P2SH(hash) => Self::encode_base58(network.p2sh_prefix(), hash.as_bytes()),
+            Self::P2WPKHv0(hash) => Self::encode_bech32(network, 0, hash.as_bytes()),
+            Self::P2WSHv0(hash) => Self::encode_bech32(network, 0, hash.as_bytes()),
+            Self::P2TRv1(output_key) => Self::encode_bech32(network, 1, output_key.as_bytes()),
+        }
+    }
+
+    /// Returns the human-readable representation of the address on the given network
+    #[cfg(feature = "std")]
+    pub fn to_string(&self, network: Network) -> String {
+        // the alphabets of both encodings are a subset of ASCII
+        String::from_utf8(self.encode_str(network)).expect("encoded address is valid ascii; qed")
+    }
+
+    /// Parses a Base58Check or Bech32(m) encoded address, validating its checksum
+    /// and that it belongs to the given network.
+    pub fn from_str(encoded: &str, network: Network) -> Result<Self, Error> {
+        let hrp = network.bech32_hrp();
+        let is_bech32 = encoded.len() > hrp.len()
+            && encoded.as_bytes()[..hrp.len()].eq_ignore_ascii_case(hrp)
+            && encoded.as_bytes()[hrp.len()] == b'1';
+        if is_bech32 {
+            Self::decode_bech32(encoded.as_bytes(), network)
+        } else {
+            Self::decode_base58(encoded.as_bytes(), network)
+        }
+    }
+
+    fn encode_base58(prefix: u8, hash: &[u8]) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(hash.len() + 1);
+        payload.push(prefix);
+        payload.extend_from_slice(hash);
+        base58::encode_check(&payload)
+    }
+
+    fn decode_base58(encoded: &[u8], network: Network) -> Result<Self, Error> {
+        // 1 byte prefix, 20 bytes hash and 4 bytes checksum encode to at most 35 characters
+        if encoded.len() > 35 {
+            return Err(Error::InvalidAddressEncoding);
+        }
+        let payload = base58::decode_check(encoded)?;
+        match payload.split_first() {
+            Some((&prefix, hash)) if hash.len() == HASH160_SIZE_HEX as usize => {
+                if prefix == network.p2pkh_prefix() {
+                    Ok(Self::P2PKH(H160::from_slice(hash)))
+                } else if prefix == network.p2sh_prefix() {
+                    Ok(Self::P2SH(H160::from_slice(hash)))
+                } else {
+                    Err(Error::InvalidBtcAddress)
+                }
+            }
+            _ => Err(Error::InvalidBtcAddress),
+        }
+    }
+
+    fn encode_bech32(network: Network, version: u8, program: &[u8]) -> Vec<u8> {
+        let mut data = vec![version];
+        // converting from 8 to 5 bits with padding cannot fail
+        data.extend(bech32::convert_bits(program, 8, 5, true).unwrap_or_default());
+        let variant = if version == 0 {
+            bech32::Variant::Bech32
+        } else {
+            bech32::Variant::Bech32m
+        };
+        bech32::encode(network.bech32_hrp(), &data, variant)
+    }
+
+    // https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki#segwit-address-format
+    fn decode_bech32(encoded: &[u8], network: Network) -> Result<Self, Error> {
+        let (hrp, data, variant) = bech32::decode(encoded)?;
+        if hrp != network.bech32_hrp() {
+            return Err(Error::InvalidBtcAddress);
+        }
+        let (&version, program) = data.split_first().ok_or(Error::InvalidBtcAddress)?;
+        if version > MAX_WITNESS_VERSION {
+            return Err(Error::InvalidBtcAddress);
+        }
+        let expected_variant = if version == 0 {
+            bech32::Variant::Bech32
+        } else {
+            bech32::Variant::Bech32m
+        };
+        if variant != expected_variant {
+            return Err(Error::InvalidAddressChecksum);
+        }
+        let program = bech32::convert_bits(program, 5, 8, false)?;
+        match (version, program.len()) {
+            (0, 20) => Ok(Self::P2WPKHv0(H160::from_slice(&program))),
+            (0, 32) => Ok(Self::P2WSHv0(H256::from_slice(&program))),
+            (1, 32) => Ok(Self::P2TRv1(H256::from_slice(&program))),
+            // other witness versions and program sizes are not supported
+            _ => Err(Error::InvalidBtcAddress),
         }
     }
 
@@ -275,6 +437,7 @@ mod tests {
     use super::*;
     use frame_support::assert_err;
     use secp256k1::{rand::rngs::OsRng, Secp256k1, SecretKey as Secp256k1SecretKey};
+    use sp_std::str::FromStr;
 
     #[test]
     fn test_public_key_to_hash() {
@@ -292,6 +455,139 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_p2tr_script_round_trip() {
+        let script =
+            Script::from(hex::decode("5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c").unwrap());
+        let address = Address::from_script_pub_key(&script).unwrap();
+
+        assert_eq!(
+            address,
+            Address::P2TRv1(H256::from_slice(
+                &hex::decode("a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c").unwrap()
+            ))
+        );
+        assert!(address.to_script_pub_key().is_p2tr_v1());
+        assert_eq!(address.to_script_pub_key(), script);
+    }
+
+    #[test]
+    fn test_p2tr_encoding_round_trip() {
+        let address = Address::P2TRv1(H256::random());
+        assert_eq!(Address::decode(&mut &address.encode()[..]).unwrap(), address);
+    }
+
+    #[test]
+    fn test_unknown_witness_version_fails() {
+        // OP_2 {32 bytes} is reserved for future witness versions
+        let mut bytes = vec![OpCode::Op2 as u8, HASH256_SIZE_HEX];
+        bytes.extend_from_slice(&[1; 32]);
+        assert_err!(
+            Address::from_script_pub_key(&Script::from(bytes)),
+            Error::InvalidBtcAddress
+        );
+    }
+
+    #[test]
+    fn test_address_string_round_trip() {
+        let cases = vec![
+            (
+                Network::Mainnet,
+                "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+                Address::P2PKH(H160::from_str("62e907b15cbf27d5425399ebf6f0fb50ebb88f18").unwrap()),
+            ),
+            (
+                Network::Mainnet,
+                "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+                Address::P2SH(H160::from_str("b472a266d0bd89c13706a4132ccfb16f7c3b9fcb").unwrap()),
+            ),
+            (
+                Network::Testnet,
+                "mptPe2c24DSixd22a69yFSREHZvycyXdqp",
+                Address::P2PKH(H160::from_str("66c7060feb882664ae62ffad0051fe843e318e85").unwrap()),
+            ),
+            (
+                Network::Regtest,
+                "2N2cfLFwWQYqCUTsPekU4U6Z6nSqA38RXcv",
+                Address::P2SH(H160::from_str("66c7060feb882664ae62ffad0051fe843e318e85").unwrap()),
+            ),
+            (
+                Network::Mainnet,
+                "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+                Address::P2WPKHv0(H160::from_str("751e76e8199196d454941c45d1b3a323f1433bd6").unwrap()),
+            ),
+            (
+                Network::Testnet,
+                "tb1qp0we5epypgj4acd2c4au58045ruud2pd6heuee",
+                Address::P2WPKHv0(H160::from_str("0bdd9a64240a255ee1aac57bca1df5a0f9c6a82d").unwrap()),
+            ),
+            (
+                Network::Regtest,
+                "bcrt1qp0we5epypgj4acd2c4au58045ruud2pdc7q3ws",
+                Address::P2WPKHv0(H160::from_str("0bdd9a64240a255ee1aac57bca1df5a0f9c6a82d").unwrap()),
+            ),
+            (
+                Network::Mainnet,
+                "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3",
+                Address::P2WSHv0(
+                    H256::from_str("1863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262").unwrap(),
+                ),
+            ),
+            (
+                Network::Mainnet,
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+                Address::P2TRv1(
+                    H256::from_str("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798").unwrap(),
+                ),
+            ),
+        ];
+
+        for (network, encoded, address) in cases {
+            assert_eq!(Address::from_str(encoded, network).unwrap(), address);
+            assert_eq!(address.to_string(network), encoded);
+        }
+    }
+
+    #[test]
+    fn test_address_from_str_uppercase_bech32() {
+        assert_eq!(
+            Address::from_str("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4", Network::Mainnet).unwrap(),
+            Address::P2WPKHv0(H160::from_str("751e76e8199196d454941c45d1b3a323f1433bd6").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_address_from_str_wrong_network_fails() {
+        assert_err!(
+            Address::from_str("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa", Network::Testnet),
+            Error::InvalidBtcAddress
+        );
+        assert_err!(
+            Address::from_str("tb1qp0we5epypgj4acd2c4au58045ruud2pd6heuee", Network::Mainnet),
+            Error::InvalidAddressEncoding
+        );
+    }
+
+    #[test]
+    fn test_address_from_str_invalid_checksum_fails() {
+        assert_err!(
+            Address::from_str("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb", Network::Mainnet),
+            Error::InvalidAddressChecksum
+        );
+        assert_err!(
+            Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5", Network::Mainnet),
+            Error::InvalidAddressChecksum
+        );
+        // taproot address with a bech32 instead of a bech32m checksum
+        assert_err!(
+            Address::from_str(
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+                Network::Mainnet
+            ),
+            Error::InvalidAddressChecksum
+        );
+    }
+
     #[test]
     fn test_check_secret_key_constraints() {
         assert_err!(
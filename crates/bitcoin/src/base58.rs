@@ -0,0 +1,136 @@
+//! Base58 and Base58Check encoding as used by legacy (P2PKH and P2SH) Bitcoin addresses.
+//! <https://en.bitcoin.it/wiki/Base58Check_encoding>
+
+use crate::{utils::sha256d, Error};
+use sp_std::{prelude::*, vec};
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Number of checksum bytes appended to the payload
+const CHECKSUM_SIZE: usize = 4;
+
+/// Encodes the given bytes using the Bitcoin Base58 alphabet
+///
+/// # Arguments
+///
+/// * `data` - the bytes to encode
+pub fn encode(data: &[u8]) -> Vec<u8> {
+    // every leading zero byte is encoded as the first character of the alphabet
+    let zeros = data.iter().take_while(|&&byte| byte == 0).count();
+
+    // little-endian base58 digits, log(256) / log(58) ~ 1.38
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in &data[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut result = vec![ALPHABET[0]; zeros];
+    result.extend(digits.iter().rev().map(|&digit| ALPHABET[digit as usize]));
+    result
+}
+
+/// Decodes a Base58 encoded string into the raw bytes
+///
+/// # Arguments
+///
+/// * `encoded` - the Base58 characters
+pub fn decode(encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    let zeros = encoded.iter().take_while(|&&c| c == ALPHABET[0]).count();
+
+    // little-endian base256 digits
+    let mut bytes: Vec<u8> = Vec::with_capacity(encoded.len());
+    for &c in &encoded[zeros..] {
+        let mut carry = ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .ok_or(Error::InvalidAddressEncoding)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut result = vec![0; zeros];
+    result.extend(bytes.iter().rev());
+    Ok(result)
+}
+
+/// Appends the first four bytes of the double sha256 of `data` and
+/// encodes the result using Base58
+///
+/// # Arguments
+///
+/// * `data` - the versioned payload to encode
+pub fn encode_check(data: &[u8]) -> Vec<u8> {
+    let mut payload = data.to_vec();
+    payload.extend_from_slice(&sha256d(data)[..CHECKSUM_SIZE]);
+    encode(&payload)
+}
+
+/// Decodes a Base58Check string and validates the checksum. Returns the
+/// payload without the checksum.
+///
+/// # Arguments
+///
+/// * `encoded` - the Base58Check characters
+pub fn decode_check(encoded: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut payload = decode(encoded)?;
+    if payload.len() < CHECKSUM_SIZE {
+        return Err(Error::InvalidAddressEncoding);
+    }
+    let checksum = payload.split_off(payload.len() - CHECKSUM_SIZE);
+    if checksum[..] != sha256d(&payload)[..CHECKSUM_SIZE] {
+        return Err(Error::InvalidAddressChecksum);
+    }
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        assert_eq!(encode(b"hello world"), b"StV1DL6CwTryKyV".to_vec());
+        assert_eq!(decode(b"StV1DL6CwTryKyV").unwrap(), b"hello world".to_vec());
+        assert_eq!(encode(&[0, 0, 1]), b"112".to_vec());
+        assert_eq!(decode(b"112").unwrap(), vec![0, 0, 1]);
+        assert_eq!(encode(&[]), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_decode_invalid_character_fails() {
+        assert_eq!(decode(b"0OIl"), Err(Error::InvalidAddressEncoding));
+    }
+
+    #[test]
+    fn test_encode_decode_check() {
+        // genesis coinbase address
+        let payload = hex::decode("0062e907b15cbf27d5425399ebf6f0fb50ebb88f18").unwrap();
+        let encoded = encode_check(&payload);
+        assert_eq!(encoded, b"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".to_vec());
+        assert_eq!(decode_check(&encoded).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_decode_check_invalid_checksum_fails() {
+        assert_eq!(
+            decode_check(b"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb"),
+            Err(Error::InvalidAddressChecksum)
+        );
+    }
+}
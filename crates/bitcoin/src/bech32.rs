@@ -0,0 +1,210 @@
+//! Bech32 and Bech32m encoding as used by segregated witness addresses.
+//! <https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki>
+//! <https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki>
+
+use crate::Error;
+use sp_std::{prelude::*, vec};
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+const SEPARATOR: u8 = b'1';
+
+const CHECKSUM_SIZE: usize = 6;
+
+/// Maximum length of a bech32 string
+const MAX_LENGTH: usize = 90;
+
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+/// Checksum variant, bech32 is used for witness version 0 and bech32m for all later versions
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => BECH32_CONST,
+            Variant::Bech32m => BECH32M_CONST,
+        }
+    }
+
+    fn from_constant(constant: u32) -> Option<Self> {
+        match constant {
+            BECH32_CONST => Some(Variant::Bech32),
+            BECH32M_CONST => Some(Variant::Bech32m),
+            _ => None,
+        }
+    }
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+    let mut checksum: u32 = 1;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x01ff_ffff) << 5) ^ (*value as u32);
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn hrp_expand(hrp: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(hrp.len() * 2 + 1);
+    result.extend(hrp.iter().map(|c| c >> 5));
+    result.push(0);
+    result.extend(hrp.iter().map(|c| c & 0x1f));
+    result
+}
+
+/// Encodes the human-readable part and the 5-bit `data` values, appending the checksum
+///
+/// # Arguments
+///
+/// * `hrp` - lowercase human-readable part (e.g. `bc`)
+/// * `data` - values in the range 0..32
+/// * `variant` - checksum variant
+pub fn encode(hrp: &[u8], data: &[u8], variant: Variant) -> Vec<u8> {
+    let mut values = hrp_expand(hrp);
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0; CHECKSUM_SIZE]);
+    let checksum = polymod(&values) ^ variant.constant();
+
+    let mut result = hrp.to_vec();
+    result.push(SEPARATOR);
+    result.extend(data.iter().map(|&value| CHARSET[value as usize]));
+    result.extend((0..CHECKSUM_SIZE).map(|i| CHARSET[((checksum >> (5 * (5 - i))) & 0x1f) as usize]));
+    result
+}
+
+/// Decodes a bech32 or bech32m string and validates the checksum. Returns the
+/// lowercase human-readable part, the 5-bit data values and the checksum variant.
+///
+/// # Arguments
+///
+/// * `encoded` - the bech32 characters, either all lowercase or all uppercase
+pub fn decode(encoded: &[u8]) -> Result<(Vec<u8>, Vec<u8>, Variant), Error> {
+    if encoded.len() > MAX_LENGTH || encoded.iter().any(|&c| !(33..=126).contains(&c)) {
+        return Err(Error::InvalidAddressEncoding);
+    }
+    // mixed case is not allowed
+    if encoded.iter().any(u8::is_ascii_lowercase) && encoded.iter().any(u8::is_ascii_uppercase) {
+        return Err(Error::InvalidAddressEncoding);
+    }
+    let encoded = encoded.to_ascii_lowercase();
+
+    let separator = encoded
+        .iter()
+        .rposition(|&c| c == SEPARATOR)
+        .ok_or(Error::InvalidAddressEncoding)?;
+    if separator == 0 || separator + CHECKSUM_SIZE + 1 > encoded.len() {
+        return Err(Error::InvalidAddressEncoding);
+    }
+
+    let hrp = encoded[..separator].to_vec();
+    let mut data = Vec::with_capacity(encoded.len() - separator - 1);
+    for c in &encoded[separator + 1..] {
+        let value = CHARSET
+            .iter()
+            .position(|a| a == c)
+            .ok_or(Error::InvalidAddressEncoding)?;
+        data.push(value as u8);
+    }
+
+    let mut values = hrp_expand(&hrp);
+    values.extend_from_slice(&data);
+    let variant = Variant::from_constant(polymod(&values)).ok_or(Error::InvalidAddressChecksum)?;
+
+    data.truncate(data.len() - CHECKSUM_SIZE);
+    Ok((hrp, data, variant))
+}
+
+/// Regroups the bits of `data` from `from`-bit to `to`-bit values
+///
+/// # Arguments
+///
+/// * `data` - values of at most `from` bits each
+/// * `from` - number of bits per input value
+/// * `to` - number of bits per output value
+/// * `pad` - whether to pad the remaining bits with zeros, if false any padding must be zero
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, Error> {
+    let mut accumulator: u32 = 0;
+    let mut bits: u32 = 0;
+    let max_value: u32 = (1 << to) - 1;
+    let mut result = vec![];
+    for &value in data {
+        if (value as u32) >> from != 0 {
+            return Err(Error::InvalidAddressEncoding);
+        }
+        accumulator = (accumulator << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((accumulator >> bits) & max_value) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            result.push(((accumulator << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || ((accumulator << (to - bits)) & max_value) != 0 {
+        return Err(Error::InvalidAddressEncoding);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_valid_checksums() {
+        // https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki#test-vectors
+        assert_eq!(decode(b"A12UEL5L").unwrap(), (b"a".to_vec(), vec![], Variant::Bech32));
+        assert_eq!(
+            decode(b"abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw").unwrap().0,
+            b"abcdef".to_vec()
+        );
+        // https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki#test-vectors-for-bech32m
+        assert_eq!(decode(b"A1LQFN3A").unwrap(), (b"a".to_vec(), vec![], Variant::Bech32m));
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let data = convert_bits(&[0xde, 0xad, 0xbe, 0xef], 8, 5, true).unwrap();
+        for variant in [Variant::Bech32, Variant::Bech32m].iter() {
+            let encoded = encode(b"bc", &data, *variant);
+            assert_eq!(decode(&encoded).unwrap(), (b"bc".to_vec(), data.clone(), *variant));
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid_fails() {
+        // no separator
+        assert_eq!(decode(b"pzry9x0s0muk"), Err(Error::InvalidAddressEncoding));
+        // empty hrp
+        assert_eq!(decode(b"1pzry9x0s0muk"), Err(Error::InvalidAddressEncoding));
+        // invalid data character
+        assert_eq!(decode(b"x1b4n0q5v"), Err(Error::InvalidAddressEncoding));
+        // mixed case
+        assert_eq!(decode(b"A12uEL5L"), Err(Error::InvalidAddressEncoding));
+        // invalid checksum
+        assert_eq!(decode(b"a12uel5m"), Err(Error::InvalidAddressChecksum));
+    }
+
+    #[test]
+    fn test_convert_bits() {
+        let data = vec![0x75, 0x1e, 0x76, 0xe8];
+        let converted = convert_bits(&data, 8, 5, true).unwrap();
+        assert_eq!(convert_bits(&converted, 5, 8, false).unwrap(), data);
+        // non-zero padding
+        assert_eq!(convert_bits(&[0x1f], 5, 8, false), Err(Error::InvalidAddressEncoding));
+    }
+}
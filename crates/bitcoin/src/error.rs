@@ -15,6 +15,11 @@ pub enum Error {
     InvalidBtcHash,
     InvalidScript,
     InvalidBtcAddress,
+    InvalidAddressEncoding,
+    InvalidAddressChecksum,
+    InvalidSigHashType,
+    InvalidInputIndex,
+    ScriptVerificationFailed,
     ArithmeticOverflow,
     ArithmeticUnderflow,
 }
@@ -80,10 +80,10 @@ impl Formattable for CompactUint {
         let mut formatter = Formatter::new();
         if self.value < 0xfd {
             formatter.format(self.value as u8);
-        } else if self.value < u16::max_value() as u64 {
+        } else if self.value <= u16::max_value() as u64 {
             formatter.format(0xfd_u8);
             formatter.format(self.value as u16);
-        } else if self.value < u32::max_value() as u64 {
+        } else if self.value <= u32::max_value() as u64 {
             formatter.format(0xfe_u8);
             formatter.format(self.value as u32);
         } else {
@@ -124,10 +124,13 @@ impl Formattable<bool> for TransactionInput {
         };
         formatter.format(&previous_hash);
         formatter.format(previous_index);
-        formatter.format(CompactUint::from_usize(self.script.len()));
-        if let TransactionInputSource::Coinbase(Some(height)) = self.source {
-            formatter.format(Script::height(height).as_bytes());
-        }
+        // the coinbase height is parsed separately, but it is part of the script
+        let height_script = match self.source {
+            TransactionInputSource::Coinbase(Some(height)) => Script::height(height),
+            _ => Script::new(),
+        };
+        formatter.format(CompactUint::from_usize(height_script.len() + self.script.len()));
+        formatter.output(height_script.as_bytes());
         formatter.output(&self.script); // we already formatted the length
         formatter.format(self.sequence);
         formatter.result()
@@ -385,6 +388,16 @@ mod tests {
         assert_eq!(formatted, input_bytes);
     }
 
+    #[test]
+    fn test_format_coinbase_transaction_input() {
+        let raw_input = parser::tests::sample_coinbase_transaction_input();
+        let input_bytes = hex::decode(&raw_input).unwrap();
+        let mut parser = parser::BytesParser::new(&input_bytes);
+        let input: TransactionInput = parser.parse_with(2).unwrap();
+        let formatted = input.format();
+        assert_eq!(formatted, input_bytes);
+    }
+
     #[test]
     fn test_format_transaction_output() {
         let raw_output = parser::tests::sample_transaction_output();
@@ -420,6 +433,109 @@ mod tests {
         assert_eq!(computed_txid, expected_txid);
     }
 
+    /// Returns the raw transactions and their txids from the regtest corpus in `testdata`
+    fn testdata_transactions() -> Vec<(Vec<u8>, H256Le)> {
+        let blocks: serde_json::Value = serde_json::from_str(include_str!("../../../testdata/test.json")).unwrap();
+        blocks
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|block| block["block"]["tx"].as_array().unwrap().iter())
+            .map(|tx| {
+                let tx = &tx["verboseTransaction"];
+                (
+                    hex::decode(tx["hex"].as_str().unwrap()).unwrap(),
+                    H256Le::from_hex_be(tx["txid"].as_str().unwrap()),
+                )
+            })
+            .collect()
+    }
+
+    fn assert_round_trip(transaction: &Transaction) {
+        let formatted = transaction.format();
+        let reparsed = parser::parse_transaction(&formatted).unwrap();
+        assert_eq!(&reparsed, transaction);
+        assert_eq!(reparsed.format(), formatted);
+    }
+
+    #[test]
+    fn test_format_testdata_transactions() {
+        let transactions = testdata_transactions();
+        assert!(!transactions.is_empty());
+        for (tx_bytes, expected_txid) in transactions {
+            let transaction = parser::parse_transaction(&tx_bytes).unwrap();
+            assert_eq!(transaction.format(), tx_bytes);
+            assert_eq!(transaction.tx_id(), expected_txid);
+            assert_round_trip(&transaction);
+        }
+    }
+
+    #[test]
+    fn test_format_testdata_transactions_with_modified_fields() {
+        let lock_times = [
+            LockTime::BlockHeight(0),
+            LockTime::BlockHeight(1),
+            LockTime::BlockHeight(499_999_999),
+            LockTime::Time(500_000_000),
+            LockTime::Time(u32::max_value()),
+        ];
+        let sequences = [0, 1, 0xffff_fffd, 0xffff_fffe, u32::max_value()];
+
+        for (tx_bytes, _) in testdata_transactions() {
+            let transaction = parser::parse_transaction(&tx_bytes).unwrap();
+            for lock_at in lock_times.iter() {
+                for sequence in sequences.iter() {
+                    let mut modified = transaction.clone();
+                    modified.lock_at = lock_at.clone();
+                    for input in modified.inputs.iter_mut() {
+                        input.sequence = *sequence;
+                    }
+                    assert_round_trip(&modified);
+
+                    // legacy serialization without witness data
+                    for input in modified.inputs.iter_mut() {
+                        input.witness.clear();
+                    }
+                    assert_round_trip(&modified);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_coinbase_heights() {
+        let heights = [
+            0,
+            1,
+            16,
+            17,
+            127,
+            128,
+            255,
+            256,
+            32_767,
+            32_768,
+            8_388_607,
+            8_388_608,
+            0x7fff_ffff,
+        ];
+        for height in heights.iter() {
+            let transaction = TransactionBuilder::new()
+                .with_version(2)
+                .add_input(
+                    TransactionInputBuilder::new()
+                        .with_source(TransactionInputSource::Coinbase(Some(*height)))
+                        .with_script(&[1, 2, 3])
+                        .with_sequence(u32::max_value())
+                        .add_witness(&[0; 32])
+                        .build(),
+                )
+                .add_output(TransactionOutput::op_return(0, &[1; 32]))
+                .build();
+            assert_round_trip(&transaction);
+        }
+    }
+
     #[test]
     fn test_format_block_header() {
         let hex_header = parser::tests::sample_block_header();
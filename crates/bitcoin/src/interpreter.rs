@@ -0,0 +1,477 @@
+//! Minimal script interpreter which verifies the input signatures of the standard
+//! P2PK, P2PKH, P2WPKH and P2SH-wrapped P2WPKH spend paths.
+//! <https://github.com/bitcoin/bitcoin/blob/7fcf53f7b4524572d1d0c9a5fdc388e87eb02416/src/script/interpreter.cpp>
+
+use crate::{
+    address::global::SECP256K1,
+    sighash::SigHashType,
+    types::{OpCode, Transaction, TransactionInputSource, TransactionOutput, Value, HASH160_SIZE_HEX},
+    Address, Error, Script,
+};
+use bitcoin_hashes::{hash160::Hash as Hash160, Hash};
+use secp256k1::{Message, PublicKey as Secp256k1PublicKey, Signature};
+use sp_core::H160;
+use sp_std::{prelude::*, vec};
+
+/// Maximum size of a script in bytes
+const MAX_SCRIPT_SIZE: usize = 10_000;
+
+/// Maximum size of a single stack element in bytes
+const MAX_SCRIPT_ELEMENT_SIZE: usize = 520;
+
+/// Maximum number of elements on the stack
+const MAX_STACK_SIZE: usize = 1000;
+
+const OP_0: u8 = OpCode::Op0 as u8;
+const OP_PUSHDATA1: u8 = OpCode::OpPushData1 as u8;
+const OP_PUSHDATA2: u8 = OpCode::OpPushData2 as u8;
+const OP_PUSHDATA4: u8 = OpCode::OpPushData4 as u8;
+const OP_1NEGATE: u8 = OpCode::Op1Negate as u8;
+const OP_1: u8 = OpCode::Op1 as u8;
+const OP_16: u8 = OpCode::Op16 as u8;
+const OP_VERIFY: u8 = OpCode::OpVerify as u8;
+const OP_DUP: u8 = OpCode::OpDup as u8;
+const OP_EQUAL: u8 = OpCode::OpEqual as u8;
+const OP_EQUAL_VERIFY: u8 = OpCode::OpEqualVerify as u8;
+const OP_HASH_160: u8 = OpCode::OpHash160 as u8;
+const OP_CHECK_SIG: u8 = OpCode::OpCheckSig as u8;
+const OP_CHECK_SIG_VERIFY: u8 = OpCode::OpCheckSigverify as u8;
+
+type Stack = Vec<Vec<u8>>;
+
+/// Determines how the signature hash is computed
+#[derive(Copy, Clone)]
+enum SigVersion {
+    Base,
+    /// Segwit v0 signatures also commit to the value of the spent output
+    WitnessV0(Value),
+}
+
+struct SignatureChecker<'a> {
+    transaction: &'a Transaction,
+    input_index: usize,
+    sig_version: SigVersion,
+}
+
+impl<'a> SignatureChecker<'a> {
+    /// Returns true if `signature` (DER encoded with the sighash type appended) is a valid
+    /// signature by `public_key` over this input, any failure is treated as an invalid signature
+    fn check_sig(&self, signature: &[u8], public_key: &[u8], script_code: &Script) -> bool {
+        let (sighash_type, der) = match signature.split_last() {
+            Some((sighash_type, der)) => (*sighash_type, der),
+            None => return false,
+        };
+        let sighash_type = match SigHashType::from_u8(sighash_type) {
+            Ok(sighash_type) => sighash_type,
+            Err(_) => return false,
+        };
+        let hash = match self.sig_version {
+            SigVersion::Base => self
+                .transaction
+                .signature_hash(self.input_index, script_code, sighash_type),
+            SigVersion::WitnessV0(value) => {
+                match self
+                    .transaction
+                    .segwit_signature_hash(self.input_index, script_code, value, sighash_type)
+                {
+                    Ok(hash) => hash,
+                    Err(_) => return false,
+                }
+            }
+        };
+
+        match (
+            Signature::from_der(der),
+            Secp256k1PublicKey::from_slice(public_key),
+            Message::from_slice(hash.as_bytes()),
+        ) {
+            (Ok(mut signature), Ok(public_key), Ok(message)) => {
+                // low-S signatures are only required by policy
+                signature.normalize_s();
+                SECP256K1.verify(&message, &signature, &public_key).is_ok()
+            }
+            _ => false,
+        }
+    }
+}
+
+enum Instruction<'a> {
+    Push(&'a [u8]),
+    Op(u8),
+}
+
+/// Reads the instruction at `position` and advances it to the next instruction
+fn next_instruction<'a>(script: &'a [u8], position: &mut usize) -> Result<Instruction<'a>, Error> {
+    let read = |start: usize, size: usize| -> Result<&'a [u8], Error> {
+        let end = start.checked_add(size).ok_or(Error::InvalidScript)?;
+        script.get(start..end).ok_or(Error::InvalidScript)
+    };
+
+    let opcode = *script.get(*position).ok_or(Error::EndOfFile)?;
+    let (header_size, data_size) = match opcode {
+        OP_0 => (1, 0),
+        0x01..=0x4b => (1, opcode as usize),
+        OP_PUSHDATA1 => (2, read(*position + 1, 1)?[0] as usize),
+        OP_PUSHDATA2 => {
+            let size = read(*position + 1, 2)?;
+            (3, u16::from_le_bytes([size[0], size[1]]) as usize)
+        }
+        OP_PUSHDATA4 => {
+            let size = read(*position + 1, 4)?;
+            (5, u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as usize)
+        }
+        _ => {
+            *position += 1;
+            return Ok(Instruction::Op(opcode));
+        }
+    };
+
+    if data_size > MAX_SCRIPT_ELEMENT_SIZE {
+        return Err(Error::InvalidScript);
+    }
+    let data = read(*position + header_size, data_size)?;
+    *position += header_size + data_size;
+    Ok(Instruction::Push(data))
+}
+
+/// Interprets the bytes as a boolean, negative zero is false
+fn cast_to_bool(bytes: &[u8]) -> bool {
+    match bytes.split_last() {
+        Some((&last, rest)) => rest.iter().any(|&byte| byte != 0) || (last != 0 && last != 0x80),
+        None => false,
+    }
+}
+
+fn from_bool(value: bool) -> Vec<u8> {
+    if value {
+        vec![1]
+    } else {
+        vec![]
+    }
+}
+
+fn pop(stack: &mut Stack) -> Result<Vec<u8>, Error> {
+    stack.pop().ok_or(Error::ScriptVerificationFailed)
+}
+
+/// Parses a script which only contains pushes (e.g. a standard script_sig) into a stack
+fn parse_push_only(script: &[u8]) -> Result<Stack, Error> {
+    let mut stack = Stack::new();
+    let mut position = 0;
+    while position < script.len() {
+        match next_instruction(script, &mut position)? {
+            Instruction::Push(data) => stack.push(data.to_vec()),
+            Instruction::Op(_) => return Err(Error::InvalidScript),
+        }
+    }
+    Ok(stack)
+}
+
+/// Executes the script on the given stack, only the opcodes used by the
+/// supported spend paths are implemented
+fn execute(script: &Script, stack: &mut Stack, checker: &SignatureChecker) -> Result<(), Error> {
+    let bytes = script.as_bytes();
+    if bytes.len() > MAX_SCRIPT_SIZE {
+        return Err(Error::InvalidScript);
+    }
+
+    let mut position = 0;
+    while position < bytes.len() {
+        match next_instruction(bytes, &mut position)? {
+            Instruction::Push(data) => stack.push(data.to_vec()),
+            Instruction::Op(OP_1NEGATE) => stack.push(vec![0x81]),
+            Instruction::Op(opcode @ OP_1..=OP_16) => stack.push(vec![opcode - OP_1 + 1]),
+            Instruction::Op(OP_VERIFY) => {
+                if !cast_to_bool(&pop(stack)?) {
+                    return Err(Error::ScriptVerificationFailed);
+                }
+            }
+            Instruction::Op(OP_DUP) => {
+                let top = stack.last().cloned().ok_or(Error::ScriptVerificationFailed)?;
+                stack.push(top);
+            }
+            Instruction::Op(OP_HASH_160) => {
+                let top = pop(stack)?;
+                stack.push(Hash160::hash(&top).to_vec());
+            }
+            Instruction::Op(opcode @ OP_EQUAL) | Instruction::Op(opcode @ OP_EQUAL_VERIFY) => {
+                let equal = pop(stack)? == pop(stack)?;
+                if opcode == OP_EQUAL {
+                    stack.push(from_bool(equal));
+                } else if !equal {
+                    return Err(Error::ScriptVerificationFailed);
+                }
+            }
+            Instruction::Op(opcode @ OP_CHECK_SIG) | Instruction::Op(opcode @ OP_CHECK_SIG_VERIFY) => {
+                let public_key = pop(stack)?;
+                let signature = pop(stack)?;
+                let valid = checker.check_sig(&signature, &public_key, script);
+                if opcode == OP_CHECK_SIG {
+                    stack.push(from_bool(valid));
+                } else if !valid {
+                    return Err(Error::ScriptVerificationFailed);
+                }
+            }
+            Instruction::Op(_) => return Err(Error::UnsupportedInputFormat),
+        }
+
+        if stack.len() > MAX_STACK_SIZE {
+            return Err(Error::InvalidScript);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the key hash if the script is a version 0 witness key hash program
+fn witness_v0_key_hash(script: &[u8]) -> Option<H160> {
+    match script {
+        [OP_0, HASH160_SIZE_HEX, hash @ ..] if hash.len() == HASH160_SIZE_HEX as usize => Some(H160::from_slice(hash)),
+        _ => None,
+    }
+}
+
+fn verify_witness_v0_key_hash(
+    transaction: &Transaction,
+    input_index: usize,
+    hash: H160,
+    witness: &[Vec<u8>],
+    value: Value,
+) -> Result<(), Error> {
+    // witness: {signature} {pubkey}
+    if witness.len() != 2 || witness.iter().any(|item| item.len() > MAX_SCRIPT_ELEMENT_SIZE) {
+        return Err(Error::InvalidScript);
+    }
+
+    let checker = SignatureChecker {
+        transaction,
+        input_index,
+        sig_version: SigVersion::WitnessV0(value),
+    };
+    let mut stack = witness.to_vec();
+    execute(&Address::P2PKH(hash).to_script_pub_key(), &mut stack, &checker)?;
+
+    // the witness script must leave exactly one true element on the stack
+    match stack.as_slice() {
+        [top] if cast_to_bool(top) => Ok(()),
+        _ => Err(Error::ScriptVerificationFailed),
+    }
+}
+
+/// Verifies that the input at `input_index` is authorized to spend `spent_output`,
+/// i.e. that it contains a valid signature by the key that `spent_output` pays to.
+///
+/// # Arguments
+///
+/// * `transaction` - the spending transaction
+/// * `input_index` - index of the input to verify
+/// * `spent_output` - the output referenced by the input
+pub fn verify_input(
+    transaction: &Transaction,
+    input_index: usize,
+    spent_output: &TransactionOutput,
+) -> Result<(), Error> {
+    let input = transaction.inputs.get(input_index).ok_or(Error::InvalidInputIndex)?;
+    if let TransactionInputSource::Coinbase(_) = input.source {
+        return Err(Error::UnsupportedInputFormat);
+    }
+    let script_pub_key = &spent_output.script;
+    if script_pub_key.is_p2wsh_v0() || script_pub_key.is_p2tr_v1() {
+        return Err(Error::UnsupportedInputFormat);
+    }
+
+    if let Some(hash) = witness_v0_key_hash(script_pub_key.as_bytes()) {
+        // native witness programs must have an empty script_sig
+        if !input.script.is_empty() {
+            return Err(Error::InvalidScript);
+        }
+        return verify_witness_v0_key_hash(transaction, input_index, hash, &input.witness, spent_output.value);
+    }
+
+    let script_sig_stack = parse_push_only(&input.script)?;
+    let mut stack = script_sig_stack.clone();
+    let checker = SignatureChecker {
+        transaction,
+        input_index,
+        sig_version: SigVersion::Base,
+    };
+    execute(script_pub_key, &mut stack, &checker)?;
+    if !stack.last().map_or(false, |top| cast_to_bool(top)) {
+        return Err(Error::ScriptVerificationFailed);
+    }
+
+    if script_pub_key.is_p2sh() {
+        // the script_sig has already been proven to push the redeem script, which
+        // must be a witness program for P2SH-P2WPKH
+        let hash = match script_sig_stack.as_slice() {
+            [redeem_script] => witness_v0_key_hash(redeem_script),
+            _ => None,
+        }
+        .ok_or(Error::UnsupportedInputFormat)?;
+        return verify_witness_v0_key_hash(transaction, input_index, hash, &input.witness, spent_output.value);
+    }
+
+    if !input.witness.is_empty() {
+        return Err(Error::InvalidScript);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_transaction;
+
+    // all three outputs pay to the key 034f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871aa:
+    // P2WPKH (100000 sat), P2PKH (200000 sat) and P2SH-P2WPKH (300000 sat)
+    const FUNDING_TX: &str = "020000000101010101010101010101010101010101010101010101010101010101010101010000000000ffffffff03a086010000000000160014fc7250a211deddc70ee5a2738de5f07817351cef400d0300000000001976a914fc7250a211deddc70ee5a2738de5f07817351cef88ace09304000000000017a914ec8f3d9c2763a0997a465b968d99db47e82e69d28700000000";
+
+    // spends all outputs of `FUNDING_TX` with SIGHASH_ALL signatures
+    const SPENDING_TX: &str = "02000000000103b64479416da6f884b5d50c2c444eace67fd860b99c12bc9c39f2fa6e451c979e0000000000ffffffffb64479416da6f884b5d50c2c444eace67fd860b99c12bc9c39f2fa6e451c979e010000006a47304402207affb6cd0d39f8eac5958393a6e1ddbdfd86b3b29234c3bb0db9439d870a7479022031a78c5637803d5086fad8a2e85be253890f9b1a7594c8339465639c15c303770121034f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871aaffffffffb64479416da6f884b5d50c2c444eace67fd860b99c12bc9c39f2fa6e451c979e0200000017160014fc7250a211deddc70ee5a2738de5f07817351cefffffffff01b0000900000000001976a914222222222222222222222222222222222222222288ac02483045022100b7541aa516337d925aaaa73625daa0772c55f8cfcd742367cc144e7e0e28752302207c539785683a3038b0ee859e594185eb285ffec6507c5e2facd4344ceffff9240121034f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871aa0002483045022100c3b838ecebeccd9fda2721bf74b43f2f808523542c4bff87e73570eb1472083402206dd226911f9efda044ba5923832f7302448d63f27cf4013e018868258afa08350121034f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871aa00000000";
+
+    fn parse_hex_transaction(raw_tx: &str) -> Transaction {
+        parse_transaction(&hex::decode(raw_tx).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_verify_input_succeeds() {
+        let funding_tx = parse_hex_transaction(FUNDING_TX);
+        let spending_tx = parse_hex_transaction(SPENDING_TX);
+
+        for (index, spent_output) in funding_tx.outputs.iter().enumerate() {
+            assert_eq!(
+                spending_tx.inputs[index].source,
+                TransactionInputSource::FromOutput(funding_tx.tx_id(), index as u32)
+            );
+            assert_eq!(verify_input(&spending_tx, index, spent_output), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_verify_input_with_modified_transaction_fails() {
+        let funding_tx = parse_hex_transaction(FUNDING_TX);
+        let mut spending_tx = parse_hex_transaction(SPENDING_TX);
+        spending_tx.outputs[0].value -= 1;
+
+        for (index, spent_output) in funding_tx.outputs.iter().enumerate() {
+            assert_eq!(
+                verify_input(&spending_tx, index, spent_output),
+                Err(Error::ScriptVerificationFailed)
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_input_with_wrong_spent_output_fails() {
+        let funding_tx = parse_hex_transaction(FUNDING_TX);
+        let spending_tx = parse_hex_transaction(SPENDING_TX);
+
+        // segwit signatures commit to the spent value
+        let mut spent_output = funding_tx.outputs[0].clone();
+        spent_output.value += 1;
+        assert_eq!(
+            verify_input(&spending_tx, 0, &spent_output),
+            Err(Error::ScriptVerificationFailed)
+        );
+
+        // paying to a different key
+        let spent_output = TransactionOutput::payment(200000, &Address::P2PKH(H160::zero()));
+        assert_eq!(
+            verify_input(&spending_tx, 1, &spent_output),
+            Err(Error::ScriptVerificationFailed)
+        );
+
+        // native witness programs do not have a script_sig
+        assert_eq!(
+            verify_input(&spending_tx, 2, &funding_tx.outputs[0]),
+            Err(Error::InvalidScript)
+        );
+        // the witness is not valid for a P2PKH output
+        assert_eq!(
+            verify_input(&spending_tx, 0, &funding_tx.outputs[1]),
+            Err(Error::ScriptVerificationFailed)
+        );
+    }
+
+    #[test]
+    fn test_verify_input_with_swapped_witness_fails() {
+        let funding_tx = parse_hex_transaction(FUNDING_TX);
+        let mut spending_tx = parse_hex_transaction(SPENDING_TX);
+        let witness = spending_tx.inputs[0].witness.clone();
+        spending_tx.inputs[2].witness = witness;
+
+        assert_eq!(
+            verify_input(&spending_tx, 2, &funding_tx.outputs[2]),
+            Err(Error::ScriptVerificationFailed)
+        );
+    }
+
+    #[test]
+    fn test_verify_input_mainnet_p2pkh() {
+        let transaction = parse_hex_transaction("020000000210b8fbfb6e1a5d2d30677c4ce797b0520774a6a250c22192eacd63b2f8025970110000006b483045022100819b0bdc0568a549cb5230c4f5fc0561764dd95b2e191efe9ab154bb8a5a95820220021f3547cefe915a5bb2906a89bc7ec4e858077ec9b023b48f7929898207de91012102279da390217bff00f6dbae65c993c714e5cd6b7ea384ffb9d4a51f09f044fa30ffffffff43ac430a2b980dbd82911eed89ec70526ed33ac614137e310f2ca70fefaa8c29010000006a473044022069e74ad037fe7304f8545230a32eff39e8fc6133640ee4bc8eb1b9108d79cfa702206dee0ba9b9e0e329074d414bb92609a34e1ae3c7ef2d0658c29230f4b5e85a2b012103bb7b040b18c3ab6d6c4ea8f42e47cb8628ccbcad016804c327603d80951a5850ffffffff02b80581000000000017a914dfea03c60b988da73084af5c9c863d988ae99a18874c113b00000000001976a914c8b46a12370c76a1e382773a3d044fa17beea53288ac00000000");
+        let spent_outputs = [
+            "ab16df7821e2ea80942ea162be595ecf47080fdf",
+            "d4e0e1819458cc308433a533c20431509cadf894",
+        ];
+
+        for (index, hash) in spent_outputs.iter().enumerate() {
+            let address = Address::P2PKH(H160::from_slice(&hex::decode(hash).unwrap()));
+            let spent_output = TransactionOutput::payment(0, &address);
+            assert_eq!(verify_input(&transaction, index, &spent_output), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_verify_input_p2sh_p2wpkh() {
+        // https://github.com/bitcoin/bips/blob/master/bip-0143.mediawiki#p2sh-wrapped-p2wpkh
+        let transaction = parse_hex_transaction("01000000000101db6b1b20aa0fd7b23880be2ecbd4a98130974cf4748fb66092ac4d3ceb1a5477010000001716001479091972186c449eb1ded22b78e40d009bdf0089feffffff02b8b4eb0b000000001976a914a457b684d7f0d539a46a45bbc043f35b59d0d96388ac0008af2f000000001976a914fd270b1ee6abcaea97fea7ad0402e8bd8ad6d77c88ac02473044022047ac8e878352d3ebbde1c94ce3a10d057c24175747116f8288e5d794d12d482f0220217f36a485cae903c713331d877c1f64677e3622ad4010726870540656fe9dcb012103ad1d8e89212f0b92c74d23bb710c00662ad1470198ac48c43f7d6f93a2a2687392040000");
+        let address = Address::P2SH(H160::from_slice(
+            &hex::decode("4733f37cf4db86fbc2efed2500b4f4e49f312023").unwrap(),
+        ));
+        let spent_output = TransactionOutput::payment(1_000_000_000, &address);
+        assert_eq!(verify_input(&transaction, 0, &spent_output), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_input_unsupported_fails() {
+        let funding_tx = parse_hex_transaction(FUNDING_TX);
+        let spending_tx = parse_hex_transaction(SPENDING_TX);
+
+        assert_eq!(
+            verify_input(&spending_tx, 3, &funding_tx.outputs[0]),
+            Err(Error::InvalidInputIndex)
+        );
+
+        let spent_output = TransactionOutput::payment(0, &Address::P2WSHv0(Default::default()));
+        assert_eq!(
+            verify_input(&spending_tx, 0, &spent_output),
+            Err(Error::UnsupportedInputFormat)
+        );
+
+        let mut coinbase_tx = spending_tx.clone();
+        coinbase_tx.inputs[1].source = TransactionInputSource::Coinbase(None);
+        assert_eq!(
+            verify_input(&coinbase_tx, 1, &funding_tx.outputs[1]),
+            Err(Error::UnsupportedInputFormat)
+        );
+    }
+
+    #[test]
+    fn test_parse_push_only() {
+        assert_eq!(
+            parse_push_only(&[OP_0, 0x02, 0xaa, 0xbb, OP_PUSHDATA1, 0x01, 0xcc]),
+            Ok(vec![vec![], vec![0xaa, 0xbb], vec![0xcc]])
+        );
+        assert_eq!(parse_push_only(&[OP_DUP]), Err(Error::InvalidScript));
+        // truncated push
+        assert_eq!(parse_push_only(&[0x02, 0xaa]), Err(Error::InvalidScript));
+    }
+
+    #[test]
+    fn test_cast_to_bool() {
+        assert!(!cast_to_bool(&[]));
+        assert!(!cast_to_bool(&[0, 0]));
+        assert!(!cast_to_bool(&[0, 0x80]));
+        assert!(cast_to_bool(&[1]));
+        assert!(cast_to_bool(&[0x80, 0]));
+    }
+}
@@ -12,6 +12,9 @@
 //!
 //! - (De)serialization of block headers, transactions and merkle proofs.
 //! - Script (address) construction and parsing.
+//! - Legacy and BIP143 (segwit) signature hash computation.
+//! - Signature verification of standard (P2PKH, P2WPKH, P2SH-P2WPKH) inputs.
+//! - Base58Check and Bech32(m) address encoding.
 //! - Merkle proof construction and verification.
 //! - Elliptic curve multiplication over Secp256k1.
 
@@ -30,6 +33,9 @@ pub mod merkle;
 mod address;
 pub use address::*;
 
+pub mod base58;
+pub mod bech32;
+
 mod script;
 pub use script::Script;
 
@@ -38,4 +44,7 @@ pub mod types;
 pub mod formatter;
 pub mod parser;
 
+pub mod interpreter;
+pub mod sighash;
+
 pub mod utils;
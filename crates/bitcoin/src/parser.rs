@@ -387,15 +387,13 @@ fn parse_transaction_input(raw_input: &[u8], version: i32) -> Result<(Transactio
             // version 1 does not include height
             None
         } else {
-            // version 2 transactions include a height as the first 4 bytes, see
+            // version 2 transactions include a height as the first push of the script, see
             // https://github.com/bitcoin/bips/blob/master/bip-0034.mediawiki
-            let height_size: u64 = parser.parse::<CompactUint>()?.value;
-            script_size = script_size.checked_sub(height_size + 1).ok_or(Error::EndOfFile)?;
-
-            let mut buffer = [0u8; 4];
-            let bytes = parser.read(height_size as usize)?;
-            buffer[..3].copy_from_slice(bytes.get(0..3).ok_or(Error::EndOfFile)?);
-            Some(u32::from_le_bytes(buffer))
+            let (height, height_script_size) = parse_coinbase_height(&mut parser)?;
+            script_size = script_size
+                .checked_sub(height_script_size as u64)
+                .ok_or(Error::EndOfFile)?;
+            Some(height)
         };
 
         TransactionInputSource::Coinbase(height)
@@ -423,6 +421,25 @@ fn parse_transaction_input(raw_input: &[u8], version: i32) -> Result<(Transactio
     ))
 }
 
+/// Parses the BIP34 block height of a coinbase script, returning the height and the
+/// number of script bytes consumed. This is the inverse of `Script::height`.
+fn parse_coinbase_height(parser: &mut BytesParser) -> Result<(u32, usize), Error> {
+    let opcode: u8 = parser.parse()?;
+    if (OpCode::Op1 as u8..=OpCode::Op16 as u8).contains(&opcode) {
+        // heights 1 to 16 are pushed as a single opcode
+        return Ok(((opcode - OpCode::Op1 as u8 + 1) as u32, 1));
+    }
+
+    // otherwise the opcode is the number of bytes pushed
+    let height_size = opcode as usize;
+    if height_size > 4 {
+        return Err(Error::MalformedTransaction);
+    }
+    let mut buffer = [0u8; 4];
+    buffer[..height_size].copy_from_slice(&parser.read(height_size)?);
+    Ok((u32::from_le_bytes(buffer), height_size + 1))
+}
+
 fn parse_transaction_output(raw_output: &[u8]) -> Result<(TransactionOutput, usize), Error> {
     let mut parser = BytesParser::new(raw_output);
     let value: i64 = parser.parse()?;
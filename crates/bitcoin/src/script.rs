@@ -21,11 +21,31 @@ impl Script {
         Self::default()
     }
 
+    /// Encodes the block height as the first push of the coinbase script, see
+    /// <https://github.com/bitcoin/bips/blob/master/bip-0034.mediawiki>. Like Bitcoin Core,
+    /// heights 1 to 16 are pushed as `OP_1` to `OP_16`, all other heights as a minimally
+    /// encoded little-endian script number.
     pub(crate) fn height(height: u32) -> Script {
         let mut script = Script::new();
-        script.append(OpCode::Op3);
-        let bytes = height.to_le_bytes();
-        script.append(&bytes[0..=2]);
+        if (1..=16).contains(&height) {
+            script.append(OpCode::Op1 as u8 + (height - 1) as u8);
+            return script;
+        }
+
+        let mut bytes: Vec<u8> = height
+            .to_le_bytes()
+            .iter()
+            .rev()
+            .skip_while(|&&byte| byte == 0)
+            .copied()
+            .collect();
+        bytes.reverse();
+        // script numbers are signed, so a set high bit requires an extra byte
+        if bytes.last().map_or(false, |&byte| byte & 0x80 != 0) {
+            bytes.push(0);
+        }
+        script.append(bytes.len() as u8);
+        script.append(bytes.as_slice());
         script
     }
 
@@ -51,6 +71,13 @@ impl Script {
             && self.bytes[1] == HASH256_SIZE_HEX
     }
 
+    pub fn is_p2tr_v1(&self) -> bool {
+        // first byte is version
+        self.len() == P2TR_V1_SCRIPT_SIZE as usize
+            && self.bytes[0] == OpCode::Op1 as u8
+            && self.bytes[1] == HASH256_SIZE_HEX
+    }
+
     pub fn is_p2pkh(&self) -> bool {
         self.len() == P2PKH_SCRIPT_SIZE as usize
             && self.bytes[0] == OpCode::OpDup as u8
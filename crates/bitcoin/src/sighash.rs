@@ -0,0 +1,480 @@
+//! Computation of the message digest that is signed by the signature of a transaction input.
+//! <https://github.com/bitcoin/bitcoin/blob/7fcf53f7b4524572d1d0c9a5fdc388e87eb02416/src/script/interpreter.cpp#L1306>
+//! <https://github.com/bitcoin/bips/blob/master/bip-0143.mediawiki>
+
+use crate::{
+    formatter::Formattable,
+    types::{CompactUint, H256Le, LockTime, OpCode, Transaction, TransactionInputSource},
+    utils::sha256d_be,
+    Error, Script,
+};
+use sp_core::H256;
+use sp_std::{prelude::*, vec};
+
+/// Flag which signs only the input being spent, allowing others to add inputs
+const SIGHASH_ANYONECANPAY: u8 = 0x80;
+
+/// Mask to extract the base type, ignoring the flags
+const SIGHASH_BASE_MASK: u8 = 0x1f;
+
+/// Signature hash type, appended as the last byte of every signature
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SigHashType {
+    /// Sign all inputs and outputs
+    All = 0x01,
+    /// Sign all inputs but none of the outputs
+    None = 0x02,
+    /// Sign all inputs and only the output with the same index as the signed input
+    Single = 0x03,
+    AllPlusAnyoneCanPay = 0x81,
+    NonePlusAnyoneCanPay = 0x82,
+    SinglePlusAnyoneCanPay = 0x83,
+}
+
+impl SigHashType {
+    /// Parses a standard sighash type, e.g. the last byte of a signature
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - the encoded sighash type
+    pub fn from_u8(value: u8) -> Result<Self, Error> {
+        match value {
+            0x01 => Ok(SigHashType::All),
+            0x02 => Ok(SigHashType::None),
+            0x03 => Ok(SigHashType::Single),
+            0x81 => Ok(SigHashType::AllPlusAnyoneCanPay),
+            0x82 => Ok(SigHashType::NonePlusAnyoneCanPay),
+            0x83 => Ok(SigHashType::SinglePlusAnyoneCanPay),
+            _ => Err(Error::InvalidSigHashType),
+        }
+    }
+
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    fn base(self) -> SigHashType {
+        match self.as_u8() & SIGHASH_BASE_MASK {
+            0x02 => SigHashType::None,
+            0x03 => SigHashType::Single,
+            _ => SigHashType::All,
+        }
+    }
+
+    fn anyone_can_pay(self) -> bool {
+        self.as_u8() & SIGHASH_ANYONECANPAY != 0
+    }
+}
+
+/// Formats the outpoint (previous transaction hash and output index) spent by an input
+fn format_outpoint(source: &TransactionInputSource) -> Vec<u8> {
+    let (previous_hash, previous_index) = match *source {
+        TransactionInputSource::Coinbase(_) => (H256Le::zero(), u32::max_value()),
+        TransactionInputSource::FromOutput(hash, index) => (hash, index),
+    };
+    let mut bytes = previous_hash.format();
+    bytes.extend(previous_index.format());
+    bytes
+}
+
+fn format_lock_time(lock_at: &LockTime) -> Vec<u8> {
+    match *lock_at {
+        LockTime::BlockHeight(b) | LockTime::Time(b) => b.format(),
+    }
+}
+
+/// Removes all OP_CODESEPARATOR opcodes from the script, skipping over pushed data.
+/// A truncated push at the end of the script is kept as-is.
+fn remove_code_separators(script: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(script.len());
+    let mut position = 0;
+    while position < script.len() {
+        let opcode = script[position];
+        let (header_size, data_size) = match opcode {
+            0x01..=0x4b => (1, opcode as usize),
+            x if x == OpCode::OpPushData1 as u8 && position + 2 <= script.len() => (2, script[position + 1] as usize),
+            x if x == OpCode::OpPushData2 as u8 && position + 3 <= script.len() => (
+                3,
+                u16::from_le_bytes([script[position + 1], script[position + 2]]) as usize,
+            ),
+            x if x == OpCode::OpPushData4 as u8 && position + 5 <= script.len() => {
+                let mut size = [0u8; 4];
+                size.copy_from_slice(&script[position + 1..position + 5]);
+                (5, u32::from_le_bytes(size) as usize)
+            }
+            x if x == OpCode::OpPushData1 as u8 || x == OpCode::OpPushData2 as u8 || x == OpCode::OpPushData4 as u8 => {
+                (script.len() - position, 0)
+            }
+            _ => (1, 0),
+        };
+        let end = position
+            .saturating_add(header_size)
+            .saturating_add(data_size)
+            .min(script.len());
+        if opcode != OpCode::OpCodeSeparator as u8 {
+            result.extend_from_slice(&script[position..end]);
+        }
+        position = end;
+    }
+    result
+}
+
+impl Transaction {
+    /// Computes the legacy (pre-segwit) signature hash of the input at `input_index`,
+    /// as used by P2PKH and P2SH inputs.
+    ///
+    /// Like Bitcoin Core, signing a non-existent input or using `SIGHASH_SINGLE`
+    /// without a matching output results in the hash `1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `input_index` - index of the input being signed
+    /// * `script_code` - script of the spent output (the redeem script for P2SH)
+    /// * `sighash_type` - which parts of the transaction are signed
+    pub fn signature_hash(&self, input_index: usize, script_code: &Script, sighash_type: SigHashType) -> H256 {
+        let base = sighash_type.base();
+        if input_index >= self.inputs.len() || (base == SigHashType::Single && input_index >= self.outputs.len()) {
+            let mut one = [0u8; 32];
+            one[0] = 1;
+            return H256::from(one);
+        }
+
+        let mut bytes = self.version.format();
+
+        let inputs: Vec<usize> = if sighash_type.anyone_can_pay() {
+            vec![input_index]
+        } else {
+            (0..self.inputs.len()).collect()
+        };
+        bytes.extend(CompactUint::from_usize(inputs.len()).format());
+        for index in inputs {
+            let input = &self.inputs[index];
+            bytes.extend(format_outpoint(&input.source));
+            if index == input_index {
+                bytes.extend(remove_code_separators(script_code.as_bytes()).format());
+            } else {
+                bytes.extend(CompactUint::from_usize(0).format());
+            }
+            if index != input_index && base != SigHashType::All {
+                // let the other inputs update their sequence
+                bytes.extend(0u32.format());
+            } else {
+                bytes.extend(input.sequence.format());
+            }
+        }
+
+        match base {
+            SigHashType::None => bytes.extend(CompactUint::from_usize(0).format()),
+            SigHashType::Single => {
+                bytes.extend(CompactUint::from_usize(input_index + 1).format());
+                for _ in 0..input_index {
+                    // empty outputs have a value of -1 and an empty script
+                    bytes.extend((-1i64).format());
+                    bytes.extend(CompactUint::from_usize(0).format());
+                }
+                bytes.extend(self.outputs[input_index].format());
+            }
+            _ => bytes.extend(self.outputs.format()),
+        }
+
+        bytes.extend(format_lock_time(&self.lock_at));
+        bytes.extend((sighash_type.as_u8() as u32).format());
+        sha256d_be(&bytes)
+    }
+
+    /// Computes the BIP143 signature hash of the segwit (v0) input at `input_index`,
+    /// as used by P2WPKH, P2WSH and their P2SH-wrapped variants.
+    ///
+    /// # Arguments
+    ///
+    /// * `input_index` - index of the input being signed
+    /// * `script_code` - for P2WPKH `OP_DUP OP_HASH160 {pubkey_hash} OP_EQUALVERIFY OP_CHECKSIG`,
+    /// for P2WSH the witness script
+    /// * `value` - amount (in satoshis) of the spent output
+    /// * `sighash_type` - which parts of the transaction are signed
+    pub fn segwit_signature_hash(
+        &self,
+        input_index: usize,
+        script_code: &Script,
+        value: i64,
+        sighash_type: SigHashType,
+    ) -> Result<H256, Error> {
+        let input = self.inputs.get(input_index).ok_or(Error::InvalidInputIndex)?;
+        let base = sighash_type.base();
+
+        let hash_prevouts = if sighash_type.anyone_can_pay() {
+            H256::zero()
+        } else {
+            let prevouts: Vec<u8> = self
+                .inputs
+                .iter()
+                .flat_map(|input| format_outpoint(&input.source))
+                .collect();
+            sha256d_be(&prevouts)
+        };
+
+        let hash_sequence = if sighash_type.anyone_can_pay() || base != SigHashType::All {
+            H256::zero()
+        } else {
+            let sequences: Vec<u8> = self.inputs.iter().flat_map(|input| input.sequence.format()).collect();
+            sha256d_be(&sequences)
+        };
+
+        let hash_outputs = match base {
+            SigHashType::All => {
+                let outputs: Vec<u8> = self.outputs.iter().flat_map(|output| output.format()).collect();
+                sha256d_be(&outputs)
+            }
+            SigHashType::Single if input_index < self.outputs.len() => sha256d_be(&self.outputs[input_index].format()),
+            _ => H256::zero(),
+        };
+
+        let mut bytes = self.version.format();
+        bytes.extend(hash_prevouts.format());
+        bytes.extend(hash_sequence.format());
+        bytes.extend(format_outpoint(&input.source));
+        bytes.extend(script_code.format());
+        bytes.extend(value.format());
+        bytes.extend(input.sequence.format());
+        bytes.extend(hash_outputs.format());
+        bytes.extend(format_lock_time(&self.lock_at));
+        bytes.extend((sighash_type.as_u8() as u32).format());
+        Ok(sha256d_be(&bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::parse_transaction, Address, PublicKey};
+    use secp256k1::{Message, PublicKey as Secp256k1PublicKey, Secp256k1, SecretKey, Signature};
+    use sp_core::H160;
+
+    fn parse_hex_transaction(raw_tx: &str) -> Transaction {
+        parse_transaction(&hex::decode(raw_tx).unwrap()).unwrap()
+    }
+
+    fn p2pkh_script_code(public_key: &[u8]) -> Script {
+        let mut bytes = [0u8; 33];
+        bytes.copy_from_slice(public_key);
+        Address::P2PKH(PublicKey(bytes).to_hash()).to_script_pub_key()
+    }
+
+    fn bip143_p2wpkh_transaction() -> Transaction {
+        // https://github.com/bitcoin/bips/blob/master/bip-0143.mediawiki#native-p2wpkh
+        parse_hex_transaction(
+            "0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000",
+        )
+    }
+
+    fn bip143_p2wpkh_script_code() -> Script {
+        Address::P2PKH(H160::from_slice(
+            &hex::decode("1d0f172a0ecb48aee1be1f2687d2963ae33f71a1").unwrap(),
+        ))
+        .to_script_pub_key()
+    }
+
+    #[test]
+    fn test_sighash_type_from_u8() {
+        assert_eq!(SigHashType::from_u8(0x01), Ok(SigHashType::All));
+        assert_eq!(SigHashType::from_u8(0x83), Ok(SigHashType::SinglePlusAnyoneCanPay));
+        assert_eq!(SigHashType::from_u8(0x00), Err(Error::InvalidSigHashType));
+        assert_eq!(SigHashType::from_u8(0x84), Err(Error::InvalidSigHashType));
+    }
+
+    #[test]
+    fn test_legacy_signature_hash_verifies_mainnet_signatures() {
+        // both inputs are p2pkh, signed with SIGHASH_ALL
+        let transaction = parse_hex_transaction("020000000210b8fbfb6e1a5d2d30677c4ce797b0520774a6a250c22192eacd63b2f8025970110000006b483045022100819b0bdc0568a549cb5230c4f5fc0561764dd95b2e191efe9ab154bb8a5a95820220021f3547cefe915a5bb2906a89bc7ec4e858077ec9b023b48f7929898207de91012102279da390217bff00f6dbae65c993c714e5cd6b7ea384ffb9d4a51f09f044fa30ffffffff43ac430a2b980dbd82911eed89ec70526ed33ac614137e310f2ca70fefaa8c29010000006a473044022069e74ad037fe7304f8545230a32eff39e8fc6133640ee4bc8eb1b9108d79cfa702206dee0ba9b9e0e329074d414bb92609a34e1ae3c7ef2d0658c29230f4b5e85a2b012103bb7b040b18c3ab6d6c4ea8f42e47cb8628ccbcad016804c327603d80951a5850ffffffff02b80581000000000017a914dfea03c60b988da73084af5c9c863d988ae99a18874c113b00000000001976a914c8b46a12370c76a1e382773a3d044fa17beea53288ac00000000");
+        let secp = Secp256k1::new();
+
+        for (index, input) in transaction.inputs.iter().enumerate() {
+            // script_sig: {signature} {pubkey}
+            let signature_size = input.script[0] as usize;
+            let signature = &input.script[1..1 + signature_size];
+            let public_key = &input.script[2 + signature_size..];
+            let (sighash_type, der) = signature.split_last().unwrap();
+            assert_eq!(SigHashType::from_u8(*sighash_type), Ok(SigHashType::All));
+
+            let hash = transaction.signature_hash(index, &p2pkh_script_code(public_key), SigHashType::All);
+            let mut signature = Signature::from_der(der).unwrap();
+            signature.normalize_s();
+            assert!(secp
+                .verify(
+                    &Message::from_slice(hash.as_bytes()).unwrap(),
+                    &signature,
+                    &Secp256k1PublicKey::from_slice(public_key).unwrap()
+                )
+                .is_ok());
+        }
+
+        assert_eq!(
+            transaction.signature_hash(
+                0,
+                &p2pkh_script_code(&transaction.inputs[0].script[74..]),
+                SigHashType::All
+            ),
+            H256::from_slice(&hex::decode("caf62851203beae651e164de4928d84b40f6c7218f44ad22dab7743cacad3bc7").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_legacy_signature_hash_single_without_output_is_one() {
+        let mut transaction = bip143_p2wpkh_transaction();
+        transaction.outputs.truncate(1);
+        let mut one = [0u8; 32];
+        one[0] = 1;
+
+        let script_code = bip143_p2wpkh_script_code();
+        assert_eq!(
+            transaction.signature_hash(1, &script_code, SigHashType::Single),
+            H256::from(one)
+        );
+        assert_eq!(
+            transaction.signature_hash(2, &script_code, SigHashType::All),
+            H256::from(one)
+        );
+        assert_ne!(
+            transaction.signature_hash(0, &script_code, SigHashType::Single),
+            H256::from(one)
+        );
+    }
+
+    #[test]
+    fn test_legacy_signature_hash_ignores_unsigned_parts() {
+        let transaction = bip143_p2wpkh_transaction();
+        let script_code = bip143_p2wpkh_script_code();
+
+        let mut changed_outputs = transaction.clone();
+        changed_outputs.outputs[1].value += 1;
+        let mut changed_inputs = transaction.clone();
+        changed_inputs.inputs[0].sequence = 0;
+        changed_inputs.inputs[0].script = vec![1, 2, 3];
+
+        let hash = |tx: &Transaction, sighash_type| tx.signature_hash(1, &script_code, sighash_type);
+        assert_ne!(
+            hash(&transaction, SigHashType::All),
+            hash(&changed_outputs, SigHashType::All)
+        );
+        assert_eq!(
+            hash(&transaction, SigHashType::None),
+            hash(&changed_outputs, SigHashType::None)
+        );
+        assert_ne!(
+            hash(&transaction, SigHashType::Single),
+            hash(&changed_outputs, SigHashType::Single)
+        );
+        assert_ne!(
+            hash(&transaction, SigHashType::All),
+            hash(&changed_inputs, SigHashType::All)
+        );
+        assert_eq!(
+            hash(&transaction, SigHashType::None),
+            hash(&changed_inputs, SigHashType::None)
+        );
+        assert_eq!(
+            hash(&transaction, SigHashType::AllPlusAnyoneCanPay),
+            hash(&changed_inputs, SigHashType::AllPlusAnyoneCanPay)
+        );
+    }
+
+    #[test]
+    fn test_legacy_signature_hash_removes_code_separators() {
+        let transaction = bip143_p2wpkh_transaction();
+        let script_code = bip143_p2wpkh_script_code();
+        let mut with_separator = vec![OpCode::OpCodeSeparator as u8];
+        with_separator.extend_from_slice(script_code.as_bytes());
+
+        assert_eq!(
+            transaction.signature_hash(1, &Script::from(with_separator), SigHashType::All),
+            transaction.signature_hash(1, &script_code, SigHashType::All)
+        );
+        // pushed data is not an opcode
+        assert_eq!(remove_code_separators(&[0x01, 0xab, 0xab]), vec![0x01, 0xab]);
+        assert_eq!(remove_code_separators(&[0x4c, 0x05, 0xab]), vec![0x4c, 0x05, 0xab]);
+    }
+
+    #[test]
+    fn test_segwit_signature_hash_native_p2wpkh() {
+        // https://github.com/bitcoin/bips/blob/master/bip-0143.mediawiki#native-p2wpkh
+        let transaction = bip143_p2wpkh_transaction();
+        let hash = transaction
+            .segwit_signature_hash(1, &bip143_p2wpkh_script_code(), 600_000_000, SigHashType::All)
+            .unwrap();
+        assert_eq!(
+            hash,
+            H256::from_slice(&hex::decode("c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_segwit_signature_hash_p2sh_p2wpkh() {
+        // https://github.com/bitcoin/bips/blob/master/bip-0143.mediawiki#p2sh-wrapped-p2wpkh
+        let transaction = parse_hex_transaction("0100000001db6b1b20aa0fd7b23880be2ecbd4a98130974cf4748fb66092ac4d3ceb1a54770100000000feffffff02b8b4eb0b000000001976a914a457b684d7f0d539a46a45bbc043f35b59d0d96388ac0008af2f000000001976a914fd270b1ee6abcaea97fea7ad0402e8bd8ad6d77c88ac92040000");
+        let script_code = Address::P2PKH(H160::from_slice(
+            &hex::decode("79091972186c449eb1ded22b78e40d009bdf0089").unwrap(),
+        ))
+        .to_script_pub_key();
+        let hash = transaction
+            .segwit_signature_hash(0, &script_code, 1_000_000_000, SigHashType::All)
+            .unwrap();
+        assert_eq!(
+            hash,
+            H256::from_slice(&hex::decode("64f3b0f4dd2bb3aa1ce8566d220cc74dda9df97d8490cc81d89d735c92e59fb6").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_segwit_signature_hash_commits_to_value() {
+        let transaction = bip143_p2wpkh_transaction();
+        let script_code = bip143_p2wpkh_script_code();
+        let hash = |value, sighash_type| {
+            transaction
+                .segwit_signature_hash(1, &script_code, value, sighash_type)
+                .unwrap()
+        };
+        assert_ne!(hash(600_000_000, SigHashType::All), hash(600_000_001, SigHashType::All));
+        assert_ne!(
+            hash(600_000_000, SigHashType::All),
+            hash(600_000_000, SigHashType::None)
+        );
+        assert_ne!(
+            hash(600_000_000, SigHashType::Single),
+            hash(600_000_000, SigHashType::SinglePlusAnyoneCanPay)
+        );
+    }
+
+    #[test]
+    fn test_segwit_signature_hash_invalid_input_index_fails() {
+        let transaction = bip143_p2wpkh_transaction();
+        assert_eq!(
+            transaction.segwit_signature_hash(2, &bip143_p2wpkh_script_code(), 0, SigHashType::All),
+            Err(Error::InvalidInputIndex)
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify_p2wpkh_input() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+        let public_key = Secp256k1PublicKey::from_secret_key(&secp, &secret_key);
+
+        let transaction = bip143_p2wpkh_transaction();
+        let script_code = p2pkh_script_code(&public_key.serialize());
+        let hash = transaction
+            .segwit_signature_hash(0, &script_code, 100_000, SigHashType::All)
+            .unwrap();
+        let message = Message::from_slice(hash.as_bytes()).unwrap();
+        let signature = secp.sign(&message, &secret_key);
+
+        assert!(secp.verify(&message, &signature, &public_key).is_ok());
+        let other = Message::from_slice(
+            transaction
+                .segwit_signature_hash(0, &script_code, 100_001, SigHashType::All)
+                .unwrap()
+                .as_bytes(),
+        )
+        .unwrap();
+        assert!(secp.verify(&other, &signature, &public_key).is_err());
+    }
+}
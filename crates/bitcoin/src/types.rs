@@ -235,6 +235,7 @@ pub const P2PKH_SCRIPT_SIZE: u32 = 25;
 pub const P2SH_SCRIPT_SIZE: u32 = 23;
 pub const P2WPKH_V0_SCRIPT_SIZE: u32 = 22;
 pub const P2WSH_V0_SCRIPT_SIZE: u32 = 34;
+pub const P2TR_V1_SCRIPT_SIZE: u32 = 34;
 pub const HASH160_SIZE_HEX: u8 = 0x14;
 pub const HASH256_SIZE_HEX: u8 = 0x20;
 pub const MAX_OPRETURN_SIZE: usize = 83;
@@ -244,6 +245,7 @@ pub const MAX_OPRETURN_SIZE: usize = 83;
 /// Bitcoin Basic Block Headers
 // TODO: Figure out how to set a pointer to the ChainIndex mapping instead
 #[derive(Encode, Decode, Default, Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct BlockHeader {
     pub merkle_root: H256Le,
     pub target: U256,
@@ -261,6 +263,16 @@ impl BlockHeader {
         self.hash = new_hash;
         Ok(self.hash)
     }
+
+    /// Returns the expected number of hashes needed to find a block with this header's
+    /// target, i.e. 2^256 / (target + 1)
+    pub fn work(&self) -> U256 {
+        // 2^256 does not fit into a U256, but 2^256 / (target + 1) = ~target / (target + 1) + 1
+        match self.target.checked_add(U256::one()) {
+            Some(divisor) => (!self.target / divisor) + U256::one(),
+            None => U256::one(),
+        }
+    }
 }
 
 #[derive(PartialEq, Clone, Debug)]
@@ -517,14 +529,17 @@ fn generate_coinbase_transaction(
 
 /// Representation of a Bitcoin blockchain
 #[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct BlockChain {
     pub chain_id: u32,
     pub start_height: u32,
     pub max_height: u32,
+    /// cumulative work of all blocks up to and including the block at max_height
+    pub chainwork: U256,
 }
 
 /// Represents a bitcoin 32 bytes hash digest encoded in little-endian
-#[derive(Encode, Decode, Default, PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(Encode, Decode, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct H256Le {
     content: [u8; 32],
@@ -882,7 +897,12 @@ mod tests {
 
     #[test]
     fn test_script_height() {
-        assert_eq!(Script::height(100).len(), 4);
+        assert_eq!(Script::height(0).as_bytes(), &[0x00]);
+        assert_eq!(Script::height(1).as_bytes(), &[OpCode::Op1 as u8]);
+        assert_eq!(Script::height(16).as_bytes(), &[OpCode::Op16 as u8]);
+        assert_eq!(Script::height(100).as_bytes(), &[0x01, 0x64]);
+        assert_eq!(Script::height(203).as_bytes(), &[0x02, 0xcb, 0x00]);
+        assert_eq!(Script::height(328014).as_bytes(), &[0x03, 0x4e, 0x01, 0x05]);
     }
 
     #[test]
@@ -990,8 +1010,8 @@ mod tests {
             .unwrap();
         assert_eq!(block.header.version, 4);
         assert_eq!(block.header.merkle_root, block.transactions[0].tx_id());
-        // should be 3, might change if block is changed
-        assert_eq!(block.header.nonce, 3);
+        // should be 1, might change if block is changed
+        assert_eq!(block.header.nonce, 1);
         assert!(block.header.nonce > 0);
     }
 
@@ -1095,5 +1115,21 @@ mod tests {
         );
 
         assert_eq!(expected, actual);
+        assert_eq!(actual.format(), tx_bytes);
+    }
+
+    #[test]
+    fn test_block_header_work() {
+        let header_with_target = |target: U256| BlockHeader {
+            target,
+            ..Default::default()
+        };
+
+        // the genesis block target (nBits 0x1d00ffff)
+        let genesis_target = U256::from(0xffff) << 208;
+        assert_eq!(header_with_target(genesis_target).work(), U256::from(0x100010001u64));
+
+        assert_eq!(header_with_target(U256::one()).work(), U256::one() << 255);
+        assert_eq!(header_with_target(U256::max_value()).work(), U256::one());
     }
 }
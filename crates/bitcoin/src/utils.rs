@@ -2,7 +2,7 @@ use sha2::{Digest, Sha256};
 use sp_core::{H256, U256};
 use sp_std::{prelude::*, vec};
 
-use crate::types::H256Le;
+use crate::{formatter::TryFormattable, parser::Parsable, types::H256Le, Error};
 
 // the _SIZE constants describe the size in number of bytes of various parts of transactions.
 // Since bytes in the witnesses cost only 1/4th of the cost to transmit, the so called virtual
@@ -99,6 +99,13 @@ pub fn log256(value: &U256) -> u8 {
     result
 }
 
+/// Rounds a difficulty target down to the precision of its compact `nBits` encoding, as
+/// Bitcoin Core does before committing a computed target to a block header
+pub fn round_target(target: &U256) -> Result<U256, Error> {
+    let (rounded, _) = U256::parse(&target.try_format()?, 0)?;
+    Ok(rounded)
+}
+
 pub fn sha256d_be(bytes: &[u8]) -> H256 {
     H256::from_slice(&sha256d(bytes)[..])
 }
@@ -218,6 +225,18 @@ mod tests {
         assert_eq!(result, 24);
     }
 
+    #[test]
+    fn test_round_target() {
+        // 0x1d00ffff
+        let max_target = U256::from(0xffff) << 208;
+        assert_eq!(round_target(&max_target).unwrap(), max_target);
+        assert_eq!(round_target(&(max_target + 1)).unwrap(), max_target);
+        assert_eq!(
+            round_target(&((max_target << 1) - 1)).unwrap(),
+            U256::from(0x01fffd) << 208
+        );
+    }
+
     #[test]
     fn test_sha256d() {
         assert_eq!(
@@ -1,16 +1,42 @@
-//! Runtime API definition for the Refund Module.
+//! Runtime API definition for the BTC-Relay Module.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::Codec;
 use frame_support::dispatch::DispatchError;
+use sp_std::vec::Vec;
 
 sp_api::decl_runtime_apis! {
-    pub trait BtcRelayApi<H256Le> where
+    pub trait BtcRelayApi<H256Le, BlockChain, RichBlockHeader> where
         H256Le: Codec,
+        BlockChain: Codec,
+        RichBlockHeader: Codec,
     {
         /// Verify that the block with the given block hash is relayed, has sufficient
         /// confirmations and is part of the main chain
         fn verify_block_header_inclusion(block_hash: H256Le) -> Result<(), DispatchError>;
+
+        /// Get the hash of the best block
+        fn get_best_block() -> H256Le;
+
+        /// Get the height of the best block
+        fn get_best_block_height() -> u32;
+
+        /// Get the relayed block header with the given hash
+        fn get_block_header(block_hash: H256Le) -> Option<RichBlockHeader>;
+
+        /// Get the relayed main chain block header at the given height
+        fn get_block_header_at_height(block_height: u32) -> Option<RichBlockHeader>;
+
+        /// Get all forks, ordered by chainwork (descending), together with the hash of their tip
+        fn get_forks() -> Vec<(BlockChain, H256Le)>;
+
+        /// Verify that the transaction is included in the main chain with at least the
+        /// given number of confirmations
+        fn verify_transaction_inclusion(
+            tx_id: H256Le,
+            raw_merkle_proof: Vec<u8>,
+            confirmations: u32,
+        ) -> Result<(), DispatchError>;
     }
 }
@@ -12,13 +12,41 @@ pub use self::gen_client::Client as RefundClient;
 pub use module_btc_relay_rpc_runtime_api::BtcRelayApi as BtcRelayRuntimeApi;
 
 #[rpc]
-pub trait BtcRelayApi<BlockHash, H256Le> {
+pub trait BtcRelayApi<BlockHash, H256Le, BlockChain, RichBlockHeader> {
     #[rpc(name = "btcRelay_verifyBlockHeaderInclusion")]
     fn verify_block_header_inclusion(
         &self,
         block_hash: H256Le,
         at: Option<BlockHash>,
     ) -> JsonRpcResult<Result<(), DispatchError>>;
+
+    #[rpc(name = "btcRelay_getBestBlock")]
+    fn get_best_block(&self, at: Option<BlockHash>) -> JsonRpcResult<H256Le>;
+
+    #[rpc(name = "btcRelay_getBestBlockHeight")]
+    fn get_best_block_height(&self, at: Option<BlockHash>) -> JsonRpcResult<u32>;
+
+    #[rpc(name = "btcRelay_getBlockHeader")]
+    fn get_block_header(&self, block_hash: H256Le, at: Option<BlockHash>) -> JsonRpcResult<Option<RichBlockHeader>>;
+
+    #[rpc(name = "btcRelay_getBlockHeaderAtHeight")]
+    fn get_block_header_at_height(
+        &self,
+        block_height: u32,
+        at: Option<BlockHash>,
+    ) -> JsonRpcResult<Option<RichBlockHeader>>;
+
+    #[rpc(name = "btcRelay_getForks")]
+    fn get_forks(&self, at: Option<BlockHash>) -> JsonRpcResult<Vec<(BlockChain, H256Le)>>;
+
+    #[rpc(name = "btcRelay_verifyTransactionInclusion")]
+    fn verify_transaction_inclusion(
+        &self,
+        tx_id: H256Le,
+        raw_merkle_proof: Vec<u8>,
+        confirmations: u32,
+        at: Option<BlockHash>,
+    ) -> JsonRpcResult<Result<(), DispatchError>>;
 }
 
 /// A struct that implements the [`BtcRelayApi`].
@@ -49,12 +77,15 @@ impl From<Error> for i64 {
     }
 }
 
-impl<C, Block, H256Le> BtcRelayApi<<Block as BlockT>::Hash, H256Le> for BtcRelay<C, Block>
+impl<C, Block, H256Le, BlockChain, RichBlockHeader>
+    BtcRelayApi<<Block as BlockT>::Hash, H256Le, BlockChain, RichBlockHeader> for BtcRelay<C, Block>
 where
     Block: BlockT,
     C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
-    C::Api: BtcRelayRuntimeApi<Block, H256Le>,
+    C::Api: BtcRelayRuntimeApi<Block, H256Le, BlockChain, RichBlockHeader>,
     H256Le: Codec,
+    BlockChain: Codec,
+    RichBlockHeader: Codec,
 {
     fn verify_block_header_inclusion(
         &self,
@@ -71,4 +102,85 @@ where
                 data: Some(format!("{:?}", e).into()),
             })
     }
+
+    fn get_best_block(&self, at: Option<<Block as BlockT>::Hash>) -> JsonRpcResult<H256Le> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.get_best_block(&at).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(Error::RuntimeError.into()),
+            message: "Unable to fetch best block".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn get_best_block_height(&self, at: Option<<Block as BlockT>::Hash>) -> JsonRpcResult<u32> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.get_best_block_height(&at).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(Error::RuntimeError.into()),
+            message: "Unable to fetch best block height".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn get_block_header(
+        &self,
+        block_hash: H256Le,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> JsonRpcResult<Option<RichBlockHeader>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.get_block_header(&at, block_hash).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(Error::RuntimeError.into()),
+            message: "Unable to fetch block header".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn get_block_header_at_height(
+        &self,
+        block_height: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> JsonRpcResult<Option<RichBlockHeader>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.get_block_header_at_height(&at, block_height).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(Error::RuntimeError.into()),
+            message: "Unable to fetch block header".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn get_forks(&self, at: Option<<Block as BlockT>::Hash>) -> JsonRpcResult<Vec<(BlockChain, H256Le)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.get_forks(&at).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(Error::RuntimeError.into()),
+            message: "Unable to fetch forks".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn verify_transaction_inclusion(
+        &self,
+        tx_id: H256Le,
+        raw_merkle_proof: Vec<u8>,
+        confirmations: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> JsonRpcResult<Result<(), DispatchError>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.verify_transaction_inclusion(&at, tx_id, raw_merkle_proof, confirmations)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to verify transaction inclusion".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
 }
@@ -62,7 +62,7 @@ fn mine_block_with_one_tx<T: Config>(
         .with_previous_hash(prev_block_hash)
         .with_version(4)
         .with_coinbase(address, 50, 3)
-        .with_timestamp(1588813835)
+        .with_timestamp(1588814835)
         .add_transaction(transaction.clone())
         .mine(U256::from(2).pow(254.into()))
         .unwrap();
@@ -130,6 +130,39 @@ benchmarks! {
 
     }: _(RawOrigin::Signed(origin), raw_tx, value.into(), address, Some(H256::from_slice(&op_return)))
 
+    resolve_revoked_request {
+        let request = SettledRequest::Issue(H256::zero());
+        RevokedRequests::<T>::insert(request, H256Le::zero());
+
+    }: _(RawOrigin::Root, request)
+
+    set_confirmation_tiers {
+        let n in 0 .. MAX_CONFIRMATION_TIERS;
+        let tiers = (0..n).map(|i| ((i as Value + 1) * 100_000, i + 1)).collect::<Vec<_>>();
+
+    }: _(RawOrigin::Root, tiers)
+
+    import_block_header_snapshot {
+        let n in 1 .. T::ChainParams::DIFFICULTY_ADJUSTMENT_INTERVAL;
+
+        let address = BtcAddress::P2PKH(H160::from([0; 20]));
+        let mut prev_hash = H256Le::zero();
+        let raw_block_headers = (0..n)
+            .map(|_| {
+                let block = BlockBuilder::new()
+                    .with_previous_hash(prev_hash)
+                    .with_version(4)
+                    .with_coinbase(&address, 50, 3)
+                    .with_timestamp(1588813835)
+                    .mine(U256::from(2).pow(254.into()))
+                    .unwrap();
+                prev_hash = block.header.hash;
+                RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+    }: _(RawOrigin::Root, raw_block_headers, 0)
+
 }
 
 impl_benchmark_test_suite!(BtcRelay, crate::mock::ExtBuilder::build(), crate::mock::Test);
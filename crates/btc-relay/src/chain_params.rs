@@ -0,0 +1,101 @@
+//! Consensus parameters of the relayed chain.
+
+use crate::{
+    DIFFICULTY_ADJUSTMENT_INTERVAL, MAX_FUTURE_BLOCK_TIME, MEDIAN_TIME_SPAN, MIN_BLOCK_VERSION, TARGET_SPACING,
+    TARGET_TIMESPAN, TARGET_TIMESPAN_DIVISOR, UNROUNDED_MAX_TARGET,
+};
+use bitcoin::{
+    types::{BlockHeader, H256Le},
+    Network,
+};
+use sp_core::U256;
+
+/// Read access to the block headers stored in the relay.
+pub trait HeaderChain {
+    /// Returns the stored block header with the given hash, if any
+    fn block_header(&self, block_hash: H256Le) -> Option<BlockHeader>;
+
+    /// Returns the cumulative chainwork up to the stored block with the given hash, if any
+    fn chainwork(&self, block_hash: H256Le) -> Option<U256>;
+}
+
+/// Error returned by `ChainParams::retarget` if a block header it requires is not stored
+#[derive(Debug, PartialEq, Eq)]
+pub struct MissingHeader;
+
+/// Consensus parameters of a Bitcoin-derived chain whose headers are relayed.
+///
+/// The pallet is not instantiable and the other pallets depend on its single instance, so a
+/// runtime relays exactly one chain. Relaying a second chain, e.g. Litecoin alongside Bitcoin,
+/// requires a separate runtime.
+pub trait ChainParams {
+    /// Number of blocks between two difficulty adjustments
+    const DIFFICULTY_ADJUSTMENT_INTERVAL: u32;
+
+    /// Expected time (in seconds) between two blocks
+    const TARGET_SPACING: u32;
+
+    /// Expected time (in seconds) of a difficulty adjustment interval
+    const TARGET_TIMESPAN: u32;
+
+    /// Maximum factor by which the target may change in a single adjustment
+    const TARGET_TIMESPAN_DIVISOR: u32;
+
+    /// Highest target (lowest difficulty) permitted before rounding
+    const UNROUNDED_MAX_TARGET: U256;
+
+    /// Lowest block version that is accepted
+    const MIN_BLOCK_VERSION: i32;
+
+    /// Number of blocks whose median timestamp a new block must exceed
+    const MEDIAN_TIME_SPAN: usize;
+
+    /// Maximum amount of time (in seconds) that a block timestamp may be ahead of the current time
+    const MAX_FUTURE_BLOCK_TIME: u32;
+
+    /// Returns the hash that must satisfy the target of the block header. Chains such as
+    /// Litecoin use a different proof-of-work function than the sha256d block hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_header` - the parsed block header
+    fn pow_hash(block_header: &BlockHeader) -> H256Le {
+        block_header.hash
+    }
+
+    /// Returns the target that the given block header must use, or `None` to apply Bitcoin's
+    /// rules: retarget every `DIFFICULTY_ADJUSTMENT_INTERVAL` blocks and, on testnet, permit
+    /// blocks at minimum difficulty. Chains that adjust the difficulty with every block, such as
+    /// Bitcoin Cash, compute the target from the preceding headers here. Not called on regtest,
+    /// which never retargets. Headers more than `PruningDepth` blocks below the best block may
+    /// have been removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `chain` - the stored block headers, which include the predecessor of `block_header`
+    /// * `block_header` - the block header to compute the target for
+    /// * `block_height` - the height of the block header
+    /// * `network` - the network of the relayed chain
+    fn retarget<C: HeaderChain>(
+        _chain: &C,
+        _block_header: &BlockHeader,
+        _block_height: u32,
+        _network: Network,
+    ) -> Result<Option<U256>, MissingHeader> {
+        Ok(None)
+    }
+}
+
+/// Consensus parameters of Bitcoin.
+pub struct BitcoinChainParams;
+
+impl ChainParams for BitcoinChainParams {
+    const DIFFICULTY_ADJUSTMENT_INTERVAL: u32 = DIFFICULTY_ADJUSTMENT_INTERVAL;
+    const TARGET_SPACING: u32 = TARGET_SPACING;
+    const TARGET_TIMESPAN: u32 = TARGET_TIMESPAN;
+    const TARGET_TIMESPAN_DIVISOR: u32 = TARGET_TIMESPAN_DIVISOR;
+    const UNROUNDED_MAX_TARGET: U256 = UNROUNDED_MAX_TARGET;
+    const MIN_BLOCK_VERSION: i32 = MIN_BLOCK_VERSION;
+    const MEDIAN_TIME_SPAN: usize = MEDIAN_TIME_SPAN;
+    const MAX_FUTURE_BLOCK_TIME: u32 = MAX_FUTURE_BLOCK_TIME;
+}
@@ -37,6 +37,9 @@ pub trait WeightInfo {
 	fn verify_and_validate_transaction() -> Weight;
 	fn verify_transaction_inclusion() -> Weight;
 	fn validate_transaction() -> Weight;
+	fn resolve_revoked_request() -> Weight;
+	fn set_confirmation_tiers(n: u32, ) -> Weight;
+	fn import_block_header_snapshot(n: u32, ) -> Weight;
 }
 
 /// Weights for btc_relay using the Substrate node and recommended hardware.
@@ -69,6 +72,44 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 		(11_660_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(1 as Weight))
 	}
+	// Storage: BTCRelay RevokedRequests (r:1 w:1)
+	// Estimate: accept_new_issues of vault-registry (17_480_000), which likewise reads and writes one
+	// map entry and deposits an event
+	fn resolve_revoked_request() -> Weight {
+		(17_480_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: BTCRelay ConfirmationTiers (r:0 w:1)
+	// Estimate: adjust_secure_collateral_threshold of vault-registry (4_321_000), which likewise
+	// writes one value as root, plus 100_000 per tier for checking its order against the previous one
+	fn set_confirmation_tiers(n: u32, ) -> Weight {
+		(4_321_000 as Weight)
+			.saturating_add((100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: BTCRelay BestBlock (r:1 w:1)
+	// Storage: BTCRelay DisableDifficultyCheck (r:1 w:0)
+	// Storage: BTCRelay Checkpoints (r:1 w:0)
+	// Storage: BTCRelay ChainCounter (r:1 w:1)
+	// Storage: BTCRelay ChainsIndex (r:1 w:1)
+	// Storage: BTCRelay Chains (r:0 w:1)
+	// Storage: BTCRelay ChainsHashes (r:1 w:1)
+	// Storage: BTCRelay BlockHeaders (r:1 w:1)
+	// Storage: BTCRelay BitcoinNetwork (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:0 w:1)
+	// Storage: BTCRelay StartBlockHeight (r:0 w:1)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Estimate: initialize of relay (52_878_000) plus, per header, store_block_header of relay
+	// (68_306_000), which parses, hashes and checks the proof of work of a header as the import does
+	fn import_block_header_snapshot(n: u32, ) -> Weight {
+		(52_878_000 as Weight)
+			.saturating_add((68_306_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(n as Weight)))
+	}
 }
 
 // For backwards compatibility and tests
@@ -100,5 +141,43 @@ impl WeightInfo for () {
 		(11_660_000 as Weight)
 			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
 	}
+	// Storage: BTCRelay RevokedRequests (r:1 w:1)
+	// Estimate: accept_new_issues of vault-registry (17_480_000), which likewise reads and writes one
+	// map entry and deposits an event
+	fn resolve_revoked_request() -> Weight {
+		(17_480_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: BTCRelay ConfirmationTiers (r:0 w:1)
+	// Estimate: adjust_secure_collateral_threshold of vault-registry (4_321_000), which likewise
+	// writes one value as root, plus 100_000 per tier for checking its order against the previous one
+	fn set_confirmation_tiers(n: u32, ) -> Weight {
+		(4_321_000 as Weight)
+			.saturating_add((100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: BTCRelay BestBlock (r:1 w:1)
+	// Storage: BTCRelay DisableDifficultyCheck (r:1 w:0)
+	// Storage: BTCRelay Checkpoints (r:1 w:0)
+	// Storage: BTCRelay ChainCounter (r:1 w:1)
+	// Storage: BTCRelay ChainsIndex (r:1 w:1)
+	// Storage: BTCRelay Chains (r:0 w:1)
+	// Storage: BTCRelay ChainsHashes (r:1 w:1)
+	// Storage: BTCRelay BlockHeaders (r:1 w:1)
+	// Storage: BTCRelay BitcoinNetwork (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:0 w:1)
+	// Storage: BTCRelay StartBlockHeight (r:0 w:1)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Estimate: initialize of relay (52_878_000) plus, per header, store_block_header of relay
+	// (68_306_000), which parses, hashes and checks the proof of work of a header as the import does
+	fn import_block_header_snapshot(n: u32, ) -> Weight {
+		(52_878_000 as Weight)
+			.saturating_add((68_306_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(n as Weight)))
+	}
 }
 
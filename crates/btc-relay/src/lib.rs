@@ -31,6 +31,7 @@
 
 mod ext;
 
+pub mod chain_params;
 pub mod types;
 
 #[cfg(any(feature = "runtime-benchmarks", test))]
@@ -53,15 +54,17 @@ use mocktopus::macros::mockable;
 
 use frame_support::{
     dispatch::{DispatchError, DispatchResult},
-    ensure, runtime_print,
+    ensure, runtime_print, storage,
     traits::Get,
     transactional,
+    weights::Weight,
 };
-use frame_system::ensure_signed;
+use frame_system::{ensure_root, ensure_signed};
 use sp_core::{H256, U256};
-use sp_runtime::traits::{CheckedAdd, CheckedDiv, CheckedSub, One};
+use sp_runtime::traits::{CheckedAdd, CheckedDiv, CheckedSub, One, UniqueSaturatedInto};
 use sp_std::{
     convert::{TryFrom, TryInto},
+    marker::PhantomData,
     prelude::*,
 };
 
@@ -69,11 +72,14 @@ use sp_std::{
 pub use bitcoin::{self, Address as BtcAddress, PublicKey as BtcPublicKey};
 use bitcoin::{
     merkle::{MerkleProof, ProofResult},
-    parser::{parse_block_header, parse_transaction},
+    parser::{parse_block_header_lenient, parse_transaction},
     types::{BlockChain, BlockHeader, H256Le, RawBlockHeader, Transaction, Value},
-    Error as BitcoinError,
+    utils::round_target,
+    Error as BitcoinError, Network,
 };
-pub use types::{OpReturnPaymentData, RichBlockHeader};
+pub use chain_params::{BitcoinChainParams, ChainParams, HeaderChain, MissingHeader};
+use types::{BlockChainV1, ChainworkMigrationState, RichBlockHeaderV0, RichBlockHeaderV1, Version};
+pub use types::{OpReturnPaymentData, PaymentVerification, RichBlockHeader, SettledRequest};
 
 pub use pallet::*;
 
@@ -88,7 +94,7 @@ pub mod pallet {
     pub struct Pallet<T>(_);
 
     #[pallet::config]
-    pub trait Config: frame_system::Config + security::Config {
+    pub trait Config: frame_system::Config + pallet_timestamp::Config + security::Config {
         /// The overarching event type.
         type Event: From<Event<Self>>
             + Into<<Self as frame_system::Config>::Event>
@@ -99,10 +105,31 @@ pub mod pallet {
 
         #[pallet::constant]
         type ParachainBlocksPerBitcoinBlock: Get<<Self as frame_system::Config>::BlockNumber>;
+
+        /// Consensus parameters of the relayed chain. A runtime relays a single chain, see
+        /// `ChainParams`.
+        type ChainParams: ChainParams;
     }
 
     #[pallet::hooks]
-    impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+    impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+        fn on_initialize(_n: T::BlockNumber) -> Weight {
+            // pruning is paused while the migration walks the stored chains
+            if ChainworkMigration::<T>::exists() {
+                return Self::migrate_chainwork(MAX_MIGRATED_HEADERS_PER_BLOCK)
+                    .saturating_add(T::DbWeight::get().reads(1));
+            }
+            Self::prune_block_headers(MAX_PRUNED_BLOCKS_PER_BLOCK).saturating_add(T::DbWeight::get().reads(1))
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            let version = StorageVersion::<T>::get();
+            if version != Version::V2 && !ChainworkMigration::<T>::exists() {
+                return Self::migrate_to_v2(version).saturating_add(T::DbWeight::get().reads(2));
+            }
+            T::DbWeight::get().reads(2)
+        }
+    }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
@@ -183,7 +210,7 @@ pub mod pallet {
         /// # Arguments
         /// * `raw_tx` - raw Bitcoin transaction
         /// * `expected_btc` - expected amount of BTC (satoshis) sent to the recipient
-        /// * `recipient_btc_address` - expected Bitcoin address of recipient (p2sh, p2pkh, p2wpkh)
+        /// * `recipient_btc_address` - expected Bitcoin address of recipient (p2sh, p2pkh, p2wpkh, p2wsh, p2tr)
         /// * `op_return_id` - 32 byte hash identifier expected in OP_RETURN (replay protection)
         #[pallet::weight(<T as Config>::WeightInfo::validate_transaction())]
         #[transactional]
@@ -202,6 +229,131 @@ pub mod pallet {
             Self::_validate_transaction(transaction, expected_btc, recipient_btc_address, op_return_id)?;
             Ok(().into())
         }
+
+        /// Add a checkpoint that every stored chain must agree with.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `block_height` - height of the checkpointed block
+        /// * `block_hash` - hash of the checkpointed block
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn add_checkpoint(
+            origin: OriginFor<T>,
+            block_height: u32,
+            block_hash: H256Le,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            Checkpoints::<T>::insert(block_height, block_hash);
+            Ok(().into())
+        }
+
+        /// Remove the checkpoint at the given height.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `block_height` - height of the checkpointed block
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn remove_checkpoint(origin: OriginFor<T>, block_height: u32) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            Checkpoints::<T>::remove(block_height);
+            Ok(().into())
+        }
+
+        /// Mark a request whose payment was removed from the main chain as reviewed.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `request` - the revoked request
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(<T as Config>::WeightInfo::resolve_revoked_request())]
+        #[transactional]
+        pub fn resolve_revoked_request(origin: OriginFor<T>, request: SettledRequest) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            ensure!(
+                RevokedRequests::<T>::contains_key(&request),
+                Error::<T>::RevokedRequestNotFound
+            );
+            RevokedRequests::<T>::remove(&request);
+            Self::deposit_event(<Event<T>>::ResolveRevokedRequest(request));
+            Ok(().into())
+        }
+
+        /// Set the confirmations required for payments of up to a given amount.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `tiers` - at most `MAX_CONFIRMATION_TIERS` (max_amount, confirmations) pairs, sorted by strictly
+        ///   increasing amount and non-decreasing confirmations
+        ///
+        /// # Weight: `O(n)` where `n` is the number of tiers
+        #[pallet::weight(<T as Config>::WeightInfo::set_confirmation_tiers(tiers.len() as u32))]
+        #[transactional]
+        pub fn set_confirmation_tiers(origin: OriginFor<T>, tiers: Vec<(Value, u32)>) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            Self::ensure_valid_confirmation_tiers(&tiers)?;
+            ConfirmationTiers::<T>::put(tiers);
+            Ok(().into())
+        }
+
+        /// Set the number of blocks below the best block for which headers are retained.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `pruning_depth` - the new pruning depth, zero disables pruning
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn set_pruning_depth(origin: OriginFor<T>, pruning_depth: u32) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            Self::ensure_valid_pruning_depth(pruning_depth, Self::disable_difficulty_check())?;
+            PruningDepth::<T>::put(pruning_depth);
+            Ok(().into())
+        }
+
+        /// Initialize the relay from a trusted snapshot of contiguous main chain headers. Fails if
+        /// the relay is already initialized.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `raw_block_headers` - at most `DIFFICULTY_ADJUSTMENT_INTERVAL` 80 byte raw Bitcoin block headers, each
+        ///   extending the previous one
+        /// * `start_height` - block height of the first header
+        ///
+        /// # Weight: `O(n)` where `n` is the number of headers
+        #[pallet::weight(<T as Config>::WeightInfo::import_block_header_snapshot(raw_block_headers.len() as u32))]
+        #[transactional]
+        pub fn import_block_header_snapshot(
+            origin: OriginFor<T>,
+            raw_block_headers: Vec<RawBlockHeader>,
+            start_height: u32,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            // the headers since the last difficulty adjustment always fit into a snapshot
+            ensure!(
+                raw_block_headers.len() <= T::ChainParams::DIFFICULTY_ADJUSTMENT_INTERVAL as usize,
+                Error::<T>::TooManyBlockHeaders
+            );
+            let block_headers = raw_block_headers
+                .iter()
+                .map(Self::parse_raw_block_header)
+                .collect::<Result<Vec<_>, _>>()?;
+            Self::initialize_from_snapshot(block_headers, start_height)?;
+            Ok(().into())
+        }
     }
 
     #[pallet::event]
@@ -216,8 +368,18 @@ pub mod pallet {
         StoreForkHeader(u32, u32, H256Le, T::AccountId),
         /// new_chain_tip, chain height, fork_depth
         ChainReorg(H256Le, u32, u32),
-        /// main chain height, fork height, fork id
+        /// main chain height, fork height, fork id; emitted when the fork has more chainwork
+        /// than the main chain, but not enough to replace it yet
         ForkAheadOfMainChain(u32, u32, u32),
+        /// block_header_hash, requests settled by the block that was removed from the main chain
+        RevokeSettledRequests(H256Le, Vec<SettledRequest>),
+        /// request
+        ResolveRevokedRequest(SettledRequest),
+        /// start_height, best_block_height, best_block_hash
+        ImportBlockHeaderSnapshot(u32, u32, H256Le),
+        /// block heights migrated, block heights to migrate in total; block headers can be
+        /// stored again once both are equal
+        MigrateChainwork(u32, u32),
     }
 
     #[pallet::error]
@@ -240,6 +402,22 @@ pub mod pallet {
         LowDiff,
         /// Incorrect difficulty target specified in block header
         DiffTargetHeader,
+        /// Block timestamp is not greater than the median time of the previous blocks
+        TimestampBeforeMedianTimePast,
+        /// Block timestamp is too far ahead of the current time
+        TimestampTooFarInFuture,
+        /// Block hash does not match the checkpoint at this height
+        CheckpointMismatch,
+        /// Relay must be initialized at or below the highest checkpoint
+        InitializationAboveCheckpoint,
+        /// Request was not revoked or has already been resolved
+        RevokedRequestNotFound,
+        /// Confirmation tiers must be sorted by amount and confirmations
+        InvalidConfirmationTiers,
+        /// Number of confirmation tiers exceeds `MAX_CONFIRMATION_TIERS`
+        TooManyConfirmationTiers,
+        /// Snapshot must contain the header of the last difficulty adjustment
+        MissingRetargetHeader,
         /// Malformed transaction identifier
         MalformedTxid,
         /// Transaction has less confirmations of Bitcoin blocks than required
@@ -316,6 +494,29 @@ pub mod pallet {
         RelayerNotAuthorized,
         /// Transaction does meet the requirements to be a valid op-return payment
         InvalidOpReturnTransaction,
+        /// Bitcoin address is not valid Base58Check or Bech32
+        InvalidAddressEncoding,
+        /// Checksum of the encoded Bitcoin address is invalid
+        InvalidAddressChecksum,
+        /// Signature uses a non-standard sighash type
+        InvalidSigHashType,
+        /// Transaction has no input at the given index
+        InvalidInputIndex,
+        /// Input script or signature of the transaction is invalid
+        ScriptVerificationFailed,
+        /// No block headers were submitted
+        EmptyBlockHeaders,
+        /// Submitted block headers do not each extend the previous one
+        NonContiguousBlockHeaders,
+        /// Number of submitted block headers exceeds the difficulty adjustment interval
+        TooManyBlockHeaders,
+        /// No payment transactions were submitted
+        EmptyPayments,
+        /// Pruning depth is below the difficulty adjustment interval
+        InvalidPruningDepth,
+        /// Block headers cannot be stored until the migration of the chainwork has completed, see
+        /// the `MigrateChainwork` events for its progress
+        ChainworkMigrationInProgress,
     }
 
     /// Store Bitcoin block headers
@@ -323,8 +524,8 @@ pub mod pallet {
     pub(super) type BlockHeaders<T: Config> =
         StorageMap<_, Blake2_128Concat, H256Le, RichBlockHeader<T::BlockNumber>, ValueQuery>;
 
-    /// Priority queue of BlockChain elements, ordered by the maximum height (descending).
-    /// The first index into this mapping (0) is considered to be the longest chain. The value
+    /// Priority queue of BlockChain elements, ordered by the cumulative chainwork (descending).
+    /// The first index into this mapping (0) is considered to be the heaviest chain. The value
     /// of the entry is the index into `ChainsIndex` to retrieve the `BlockChain`.
     #[pallet::storage]
     pub(super) type Chains<T: Config> = StorageMap<_, Blake2_128Concat, u32, u32>;
@@ -375,6 +576,63 @@ pub mod pallet {
     #[pallet::getter(fn disable_inclusion_check)]
     pub(super) type DisableInclusionCheck<T: Config> = StorageValue<_, bool, ValueQuery>;
 
+    /// Number of blocks below the best block height for which headers are retained. Older
+    /// main chain headers and forks that fell behind are pruned. Zero disables pruning.
+    #[pallet::storage]
+    #[pallet::getter(fn pruning_depth)]
+    pub(super) type PruningDepth<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Bitcoin network whose difficulty rules are used to validate block headers.
+    #[pallet::storage]
+    #[pallet::getter(fn bitcoin_network)]
+    pub(super) type BitcoinNetwork<T: Config> = StorageValue<_, Network, ValueQuery>;
+
+    /// Known (height, hash) pairs of the Bitcoin main chain. Headers conflicting with a
+    /// checkpoint are rejected.
+    #[pallet::storage]
+    #[pallet::getter(fn checkpoint)]
+    pub(super) type Checkpoints<T: Config> = StorageMap<_, Blake2_128Concat, u32, H256Le, OptionQuery>;
+
+    /// Target of the last block in the difficulty period that was not mined at minimum difficulty,
+    /// for testnet blocks mined at minimum difficulty.
+    #[pallet::storage]
+    pub(super) type TestnetPeriodTargets<T: Config> = StorageMap<_, Blake2_128Concat, H256Le, U256, OptionQuery>;
+
+    /// (max_amount, confirmations) pairs sorted by amount. A payment of at most `max_amount`
+    /// satoshis requires `confirmations` blocks, larger payments require `StableBitcoinConfirmations`.
+    #[pallet::storage]
+    #[pallet::getter(fn confirmation_tiers)]
+    pub(super) type ConfirmationTiers<T: Config> = StorageValue<_, Vec<(Value, u32)>, ValueQuery>;
+
+    /// Requests whose payments are included in the given block. Entries are kept as long as the
+    /// block header is, so that reorgs of any depth within `PruningDepth` revoke them, and are
+    /// removed along with the header when it is pruned.
+    #[pallet::storage]
+    #[pallet::getter(fn settled_requests)]
+    pub(super) type SettledRequests<T: Config> =
+        StorageMap<_, Blake2_128Concat, H256Le, Vec<SettledRequest>, ValueQuery>;
+
+    /// Requests whose payment block was removed from the main chain, mapped to the hash of that
+    /// block. Entries are kept until they are resolved by governance.
+    #[pallet::storage]
+    #[pallet::getter(fn revoked_request)]
+    pub(super) type RevokedRequests<T: Config> = StorageMap<_, Blake2_128Concat, SettledRequest, H256Le, OptionQuery>;
+
+    /// Progress of the migration to V2, which translates the headers stored before. Block headers
+    /// cannot be stored while it exists, see `MAX_MIGRATED_HEADERS_PER_BLOCK`.
+    #[pallet::storage]
+    pub(super) type ChainworkMigration<T: Config> = StorageValue<_, ChainworkMigrationState, OptionQuery>;
+
+    #[pallet::type_value]
+    pub(super) fn DefaultForStorageVersion() -> Version {
+        Version::V0
+    }
+
+    /// Build storage at V1 (requires default 0).
+    #[pallet::storage]
+    #[pallet::getter(fn storage_version)]
+    pub(super) type StorageVersion<T: Config> = StorageValue<_, Version, ValueQuery, DefaultForStorageVersion>;
+
     #[pallet::genesis_config]
     pub struct GenesisConfig<T: Config> {
         /// Global security parameter k for stable Bitcoin transactions
@@ -385,6 +643,16 @@ pub mod pallet {
         pub disable_difficulty_check: bool,
         /// Whether the module should perform inclusion checks.
         pub disable_inclusion_check: bool,
+        /// Number of blocks below the best block for which headers are retained, zero disables pruning.
+        pub pruning_depth: u32,
+        /// Bitcoin network whose difficulty rules are used to validate block headers.
+        pub bitcoin_network: Network,
+        /// Known (height, hash) pairs of the Bitcoin main chain.
+        pub checkpoints: Vec<(u32, H256Le)>,
+        /// Confirmations required for payments of up to a given amount.
+        pub confirmation_tiers: Vec<(Value, u32)>,
+        /// Start height and contiguous main chain headers to initialize the relay with, if any.
+        pub block_header_snapshot: Option<(u32, Vec<BlockHeader>)>,
     }
 
     #[cfg(feature = "std")]
@@ -395,6 +663,11 @@ pub mod pallet {
                 parachain_confirmations: Default::default(),
                 disable_difficulty_check: Default::default(),
                 disable_inclusion_check: Default::default(),
+                pruning_depth: Default::default(),
+                bitcoin_network: Default::default(),
+                checkpoints: Default::default(),
+                confirmation_tiers: Default::default(),
+                block_header_snapshot: Default::default(),
             }
         }
     }
@@ -406,6 +679,25 @@ pub mod pallet {
             StableParachainConfirmations::<T>::put(self.parachain_confirmations);
             DisableDifficultyCheck::<T>::put(self.disable_difficulty_check);
             DisableInclusionCheck::<T>::put(self.disable_inclusion_check);
+            assert!(
+                Pallet::<T>::ensure_valid_pruning_depth(self.pruning_depth, self.disable_difficulty_check).is_ok(),
+                "pruning depth must be at least the difficulty adjustment interval"
+            );
+            PruningDepth::<T>::put(self.pruning_depth);
+            BitcoinNetwork::<T>::put(self.bitcoin_network);
+            for (block_height, block_hash) in self.checkpoints.iter() {
+                Checkpoints::<T>::insert(block_height, block_hash);
+            }
+            assert!(
+                Pallet::<T>::ensure_valid_confirmation_tiers(&self.confirmation_tiers).is_ok(),
+                "confirmation tiers must be sorted by amount and confirmations"
+            );
+            ConfirmationTiers::<T>::put(self.confirmation_tiers.clone());
+            if let Some((start_height, block_headers)) = self.block_header_snapshot.clone() {
+                Pallet::<T>::initialize_from_snapshot(block_headers, start_height)
+                    .expect("invalid block header snapshot");
+            }
+            StorageVersion::<T>::put(Version::V2);
         }
     }
 }
@@ -424,6 +716,10 @@ pub const TARGET_TIMESPAN: u32 = 14 * 24 * 60 * 60;
 // Used in Bitcoin's retarget algorithm
 pub const TARGET_TIMESPAN_DIVISOR: u32 = 4;
 
+/// Minimum block version, as per bip65 versions below 4 are rejected
+// https://github.com/bitcoin/bips/blob/master/bip-0065.mediawiki#spv-clients
+pub const MIN_BLOCK_VERSION: i32 = 4;
+
 // Accepted minimum number of transaction outputs for okd validation
 pub const ACCEPTED_MIN_TRANSACTION_OUTPUTS: u32 = 1;
 
@@ -433,6 +729,9 @@ pub const ACCEPTED_MIN_TRANSACTION_OUTPUTS_WITH_OP_RETURN: usize = 2;
 // Accepted maximum number of transaction outputs for validation of redeem/replace/refund
 pub const ACCEPTED_MAX_TRANSACTION_OUTPUTS: usize = 3;
 
+// Accepted maximum number of recipients of a batched redeem payment
+pub const ACCEPTED_MAX_BATCH_RECIPIENTS: usize = 20;
+
 /// Unrounded Maximum Target
 /// 0x00000000FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF
 pub const UNROUNDED_MAX_TARGET: U256 = U256([
@@ -442,19 +741,214 @@ pub const UNROUNDED_MAX_TARGET: U256 = U256([
     0x0000_0000_ffff_ffffu64,
 ]);
 
+/// Number of blocks used to compute the median time past
+// https://github.com/bitcoin/bitcoin/blob/5ba5becbb5d8c794efe579caeea7eea64f895a13/src/chain.h#L280
+pub const MEDIAN_TIME_SPAN: usize = 11;
+
+/// Maximum amount of time (in seconds) that a block timestamp may be ahead of the current time
+// https://github.com/bitcoin/bitcoin/blob/5ba5becbb5d8c794efe579caeea7eea64f895a13/src/chain.h#L22
+pub const MAX_FUTURE_BLOCK_TIME: u32 = 2 * 60 * 60;
+
 /// Main chain id
 pub const MAIN_CHAIN_ID: u32 = 0;
 
+/// Maximum number of block headers pruned in a single parachain block
+pub const MAX_PRUNED_BLOCKS_PER_BLOCK: u32 = 10;
+
+/// Maximum number of forks checked for pruning in a single parachain block
+pub const MAX_PRUNING_FORK_SCAN: u32 = 10;
+
+/// Maximum number of confirmation tiers
+pub const MAX_CONFIRMATION_TIERS: u32 = 10;
+
+/// Maximum number of block headers translated in a single parachain block during the migration
+/// to V2. No block headers can be stored until the migration completes, which takes
+/// `ceil(stored headers / MAX_MIGRATED_HEADERS_PER_BLOCK)` parachain blocks, e.g. 500 blocks for
+/// a relay that stores 100000 headers. Relayers can follow the progress by the `MigrateChainwork`
+/// events and should hold back their headers until then.
+pub const MAX_MIGRATED_HEADERS_PER_BLOCK: u32 = 200;
+
 /// Number of outputs expected in the accepted transaction format
 /// See: <https://interlay.gitlab.io/polkabtc-spec/btcrelay-spec/intro/accepted-format.html>
 pub const ACCEPTED_NO_TRANSACTION_OUTPUTS: u32 = 2;
 
+/// Provides the block headers stored in the relay to `ChainParams::retarget`
+pub struct StoredHeaders<T>(PhantomData<T>);
+
+impl<T: Config> HeaderChain for StoredHeaders<T> {
+    fn block_header(&self, block_hash: H256Le) -> Option<BlockHeader> {
+        BlockHeaders::<T>::try_get(block_hash)
+            .ok()
+            .map(|rich_block_header| rich_block_header.block_header)
+    }
+
+    fn chainwork(&self, block_hash: H256Le) -> Option<U256> {
+        BlockHeaders::<T>::try_get(block_hash)
+            .ok()
+            .map(|rich_block_header| rich_block_header.chainwork)
+    }
+}
+
 #[cfg_attr(test, mockable)]
 impl<T: Config> Pallet<T> {
+    /// Starts the migration to V2, which adds the cumulative chainwork to the stored chains and
+    /// headers. Only the chains are translated here, the headers are translated by
+    /// `migrate_chainwork` over the following blocks, see `MAX_MIGRATED_HEADERS_PER_BLOCK`.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - the storage version in whose layout the headers are stored
+    fn migrate_to_v2(version: Version) -> Weight {
+        let mut chains = Vec::new();
+        let mut total: u32 = 0;
+        ChainsIndex::<T>::translate::<BlockChainV1, _>(|_, chain| {
+            chains.push((chain.start_height, chain.chain_id));
+            total = total.saturating_add(chain.max_height.saturating_sub(chain.start_height).saturating_add(1));
+            Some(BlockChain {
+                chain_id: chain.chain_id,
+                start_height: chain.start_height,
+                max_height: chain.max_height,
+                chainwork: U256::zero(),
+            })
+        });
+        let migrated = chains.len() as Weight;
+
+        // the parent of a fork is always processed before the fork itself
+        chains.sort();
+        ChainworkMigration::<T>::put(ChainworkMigrationState {
+            version,
+            chain_ids: chains.into_iter().map(|(_, chain_id)| chain_id).collect(),
+            block_height: 0,
+            migrated: 0,
+            total,
+        });
+
+        T::DbWeight::get().reads_writes(migrated, migrated.saturating_add(1))
+    }
+
+    /// Translates at most `max_migrated` headers for the migration to V2, computing their
+    /// chainwork and, for headers stored in V0, their median time past. Chains are processed in
+    /// order of their start height, so the predecessors of a header are always migrated before
+    /// it, and as in `store_rich_header`, a header whose parent is not stored only accounts for
+    /// its own work. Once all chains are processed, the forks are reordered by their chainwork
+    /// and the migration completes. The progress is reported by a `MigrateChainwork` event.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_migrated` - maximum number of block headers to migrate
+    ///
+    /// # Returns
+    ///
+    /// The weight consumed
+    fn migrate_chainwork(max_migrated: u32) -> Weight {
+        let mut reads: Weight = 1;
+        let mut writes: Weight = 1;
+        let mut migration = match ChainworkMigration::<T>::get() {
+            Some(migration) => migration,
+            None => return T::DbWeight::get().reads(reads),
+        };
+        let mut remaining = max_migrated;
+
+        while remaining > 0 && !migration.chain_ids.is_empty() {
+            let chain_id = migration.chain_ids[0];
+            reads += 1;
+            let mut chain = match ChainsIndex::<T>::get(chain_id) {
+                Some(chain) => chain,
+                None => {
+                    migration.chain_ids.remove(0);
+                    continue;
+                }
+            };
+
+            migration.block_height = migration.block_height.max(chain.start_height);
+            while remaining > 0 && migration.block_height <= chain.max_height {
+                reads += 1;
+                if let Ok(block_hash) = ChainsHashes::<T>::try_get(chain_id, migration.block_height) {
+                    reads += 2;
+                    writes += 1;
+                    if let Some(mut header) = Self::get_unmigrated_block_header(migration.version, block_hash) {
+                        if migration.version == Version::V0 {
+                            reads += T::ChainParams::MEDIAN_TIME_SPAN as Weight;
+                            header.median_time_past = Self::compute_median_time_past(&header.block_header);
+                        }
+                        let prev_chainwork = BlockHeaders::<T>::try_get(header.block_header.hash_prev_block)
+                            .map(|prev_header| prev_header.chainwork)
+                            .unwrap_or_default();
+                        header.chainwork = prev_chainwork.saturating_add(header.block_header.work());
+                        BlockHeaders::<T>::insert(block_hash, header);
+                    }
+                }
+                migration.block_height += 1;
+                migration.migrated = migration.migrated.saturating_add(1);
+                remaining -= 1;
+            }
+
+            if migration.block_height > chain.max_height {
+                reads += 2;
+                writes += 1;
+                // the tip was just migrated, but the stored progress does not reflect that yet
+                chain.chainwork = Self::get_block_hash(chain_id, chain.max_height)
+                    .ok()
+                    .and_then(|tip_hash| BlockHeaders::<T>::try_get(tip_hash).ok())
+                    .map(|tip| tip.chainwork)
+                    .unwrap_or_default();
+                Self::set_block_chain_from_id(chain_id, &chain);
+                migration.chain_ids.remove(0);
+                migration.block_height = 0;
+            }
+        }
+        Self::deposit_event(<Event<T>>::MigrateChainwork(migration.migrated, migration.total));
+
+        if migration.chain_ids.is_empty() {
+            // the main chain keeps the first position, the forks follow by descending chainwork
+            let mut forks = ChainsIndex::<T>::iter_values()
+                .filter(|chain| chain.chain_id != MAIN_CHAIN_ID)
+                .collect::<Vec<_>>();
+            forks.sort_by(|a, b| b.chainwork.cmp(&a.chainwork));
+            reads += forks.len() as Weight;
+            for (position, fork) in forks.iter().enumerate() {
+                writes += 1;
+                Self::set_chain_from_position_and_id(position as u32 + 1, fork.chain_id);
+            }
+            writes += 1;
+            ChainworkMigration::<T>::kill();
+            StorageVersion::<T>::put(Version::V2);
+        } else {
+            ChainworkMigration::<T>::put(migration);
+        }
+
+        T::DbWeight::get().reads_writes(reads, writes)
+    }
+
+    /// Decodes a block header that the migration to V2 has not reached yet
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - the storage version in whose layout the header is stored
+    /// * `block_hash` - the hash of the header
+    fn get_unmigrated_block_header(version: Version, block_hash: H256Le) -> Option<RichBlockHeader<T::BlockNumber>> {
+        let key = BlockHeaders::<T>::hashed_key_for(block_hash);
+        match version {
+            Version::V0 => storage::unhashed::get::<RichBlockHeaderV0<T::BlockNumber>>(&key).map(Into::into),
+            Version::V1 => storage::unhashed::get::<RichBlockHeaderV1<T::BlockNumber>>(&key).map(Into::into),
+            Version::V2 => BlockHeaders::<T>::try_get(block_hash).ok(),
+        }
+    }
+
     pub fn initialize(relayer: T::AccountId, basic_block_header: BlockHeader, block_height: u32) -> DispatchResult {
         // Check if BTC-Relay was already initialized
         ensure!(!Self::best_block_exists(), Error::<T>::AlreadyInitialized);
 
+        // A relay started above all checkpoints could be on any chain, so it must
+        // start at or below the highest one and build up through it
+        if let Some(highest_checkpoint) = Checkpoints::<T>::iter_keys().max() {
+            ensure!(
+                block_height <= highest_checkpoint,
+                Error::<T>::InitializationAboveCheckpoint
+            );
+        }
+        Self::ensure_no_checkpoint_conflict(block_height, basic_block_header.hash)?;
+
         // construct the BlockChain struct
         Self::create_and_store_blockchain(block_height, &basic_block_header)?;
 
@@ -469,6 +963,77 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
+    /// Initializes the relay with a trusted run of contiguous main chain headers, rather than
+    /// with a single header. The snapshot must include the last difficulty adjustment, so that
+    /// the target of subsequent headers can be verified.
+    ///
+    /// Only an uninitialized relay can be bootstrapped. Resuming an initialized relay from a
+    /// snapshot, e.g. after a state migration, is not supported, since the stored chains, forks
+    /// and settled requests would have to be reconciled with the snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_headers` - the headers, each extending the previous one
+    /// * `start_height` - block height of the first header
+    pub fn initialize_from_snapshot(block_headers: Vec<BlockHeader>, start_height: u32) -> DispatchResult {
+        ensure!(!Self::best_block_exists(), Error::<T>::AlreadyInitialized);
+        let best_block_header = *block_headers.last().ok_or(Error::<T>::EmptyBlockHeaders)?;
+        ensure!(
+            block_headers
+                .windows(2)
+                .all(|pair| pair[1].hash_prev_block == pair[0].hash),
+            Error::<T>::NonContiguousBlockHeaders
+        );
+        let best_block_height = u32::try_from(block_headers.len() - 1)
+            .ok()
+            .and_then(|len| start_height.checked_add(len))
+            .ok_or(Error::<T>::ArithmeticOverflow)?;
+
+        // as in `initialize`, the snapshot must start at or below the highest checkpoint
+        if let Some(highest_checkpoint) = Checkpoints::<T>::iter_keys().max() {
+            ensure!(
+                start_height <= highest_checkpoint,
+                Error::<T>::InitializationAboveCheckpoint
+            );
+        }
+
+        if !Self::disable_difficulty_check() {
+            let interval = T::ChainParams::DIFFICULTY_ADJUSTMENT_INTERVAL;
+            ensure!(
+                best_block_height - best_block_height % interval >= start_height,
+                Error::<T>::MissingRetargetHeader
+            );
+        }
+
+        let mut blockchain = None;
+        for (block_height, block_header) in (start_height..).zip(block_headers.iter()) {
+            ensure!(
+                T::ChainParams::pow_hash(block_header).as_u256() < block_header.target,
+                Error::<T>::LowDiff
+            );
+            Self::ensure_no_checkpoint_conflict(block_height, block_header.hash)?;
+
+            blockchain = Some(match blockchain {
+                None => {
+                    let chain_id = Self::create_and_store_blockchain(block_height, block_header)?;
+                    Self::get_block_chain_from_id(chain_id)?
+                }
+                Some(blockchain) => Self::extend_blockchain(block_height, block_header, blockchain)?,
+            });
+        }
+
+        Self::update_chain_head(&best_block_header, best_block_height);
+        StartBlockHeight::<T>::set(start_height);
+
+        Self::deposit_event(<Event<T>>::ImportBlockHeaderSnapshot(
+            start_height,
+            best_block_height,
+            best_block_header.hash,
+        ));
+
+        Ok(())
+    }
+
     /// wraps _store_block_header, but differentiates between DuplicateError and OutdatedError
     #[transactional]
     pub fn store_block_header(relayer: &T::AccountId, basic_block_header: BlockHeader) -> DispatchResult {
@@ -484,9 +1049,30 @@ impl<T: Config> Pallet<T> {
         ret
     }
 
+    /// Stores a run of block headers in which every header extends the previous one. The
+    /// headers are stored atomically: if any of them is rejected, none of them are stored.
+    #[transactional]
+    pub fn store_block_headers(relayer: &T::AccountId, block_headers: Vec<BlockHeader>) -> DispatchResult {
+        ensure!(!block_headers.is_empty(), Error::<T>::EmptyBlockHeaders);
+        ensure!(
+            block_headers
+                .windows(2)
+                .all(|pair| pair[1].hash_prev_block == pair[0].hash),
+            Error::<T>::NonContiguousBlockHeaders
+        );
+        for block_header in block_headers {
+            Self::store_block_header(relayer, block_header)?;
+        }
+        Ok(())
+    }
+
     fn _store_block_header(relayer: &T::AccountId, basic_block_header: BlockHeader) -> DispatchResult {
         // Make sure Parachain is not shutdown
         ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+        ensure!(
+            !ChainworkMigration::<T>::exists(),
+            Error::<T>::ChainworkMigrationInProgress
+        );
 
         let prev_header = Self::get_block_header_from_hash(basic_block_header.hash_prev_block)?;
 
@@ -555,7 +1141,12 @@ impl<T: Config> Pallet<T> {
     }
 
     pub fn parse_raw_block_header(raw_block_header: &RawBlockHeader) -> Result<BlockHeader, DispatchError> {
-        Ok(parse_block_header(raw_block_header).map_err(Error::<T>::from)?)
+        let block_header = parse_block_header_lenient(raw_block_header).map_err(Error::<T>::from)?;
+        ensure!(
+            block_header.version >= T::ChainParams::MIN_BLOCK_VERSION,
+            Error::<T>::MalformedHeader
+        );
+        Ok(block_header)
     }
 
     // helper for the dispatchable
@@ -584,10 +1175,55 @@ impl<T: Config> Pallet<T> {
         transaction: Transaction,
         recipient_btc_address: BtcAddress,
     ) -> Result<(BtcAddress, V), DispatchError> {
+        let tx_id = transaction.tx_id();
+        let (refund_address, payment_value) = Self::get_issue_payment::<Value>(transaction, recipient_btc_address)?;
+
         // Verify that the transaction is indeed included in the main chain
-        Self::_verify_transaction_inclusion(transaction.tx_id(), merkle_proof, None)?;
+        let confirmations = Self::get_required_confirmations(payment_value);
+        Self::_verify_transaction_inclusion(tx_id, merkle_proof, Some(confirmations))?;
 
-        Self::get_issue_payment(transaction, recipient_btc_address)
+        let payment_value = payment_value.try_into().map_err(|_| Error::<T>::InvalidPaymentAmount)?;
+        Ok((refund_address, payment_value))
+    }
+
+    /// Checks that all the given transactions are included in the main chain and sums their
+    /// payments to the recipient. The refund address is taken from the first transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `payments` - merkle proofs and transactions of the payments
+    /// * `recipient_btc_address` - the address the payments are expected to be made to
+    pub fn get_and_verify_issue_payments<V: TryFrom<Value>>(
+        payments: Vec<(MerkleProof, Transaction)>,
+        recipient_btc_address: BtcAddress,
+    ) -> Result<(BtcAddress, V), DispatchError> {
+        let mut refund_address = None;
+        let mut total_payment_value: Value = 0;
+        let mut merkle_proofs = Vec::with_capacity(payments.len());
+
+        for (merkle_proof, transaction) in payments {
+            merkle_proofs.push((transaction.tx_id(), merkle_proof));
+
+            let (input_address, payment_value) = Self::get_issue_payment::<Value>(transaction, recipient_btc_address)?;
+            refund_address.get_or_insert(input_address);
+            total_payment_value = total_payment_value
+                .checked_add(payment_value)
+                .ok_or(Error::<T>::ArithmeticOverflow)?;
+        }
+
+        // every payment needs the confirmations of the total amount, so that splitting a large
+        // payment into smaller ones does not settle it faster
+        let confirmations = Self::get_required_confirmations(total_payment_value);
+        for (tx_id, merkle_proof) in merkle_proofs {
+            Self::_verify_transaction_inclusion(tx_id, merkle_proof, Some(confirmations))?;
+        }
+
+        let refund_address = refund_address.ok_or(Error::<T>::EmptyPayments)?;
+        let total_payment_value = total_payment_value
+            .try_into()
+            .map_err(|_| Error::<T>::InvalidPaymentAmount)?;
+
+        Ok((refund_address, total_payment_value))
     }
 
     fn get_issue_payment<V: TryFrom<i64>>(
@@ -601,15 +1237,19 @@ impl<T: Config> Pallet<T> {
             .extract_address()
             .map_err(|_| Error::<T>::MalformedTransaction)?;
 
-        // using the on-chain key derivation scheme we only expect a simple
-        // payment to the vault's new deposit address
-        let extr_payment_value = transaction
-            .outputs
-            .into_iter()
-            .find_map(|x| match x.extract_address() {
-                Ok(address) if address == recipient_btc_address => Some(x.value),
-                _ => None,
-            })
+        // using the on-chain key derivation scheme we only expect payments
+        // to the vault's new deposit address, all of which are counted
+        let mut extr_payment_value: Option<Value> = None;
+        for output in transaction.outputs {
+            if matches!(output.extract_address(), Ok(address) if address == recipient_btc_address) {
+                let value = extr_payment_value
+                    .unwrap_or_default()
+                    .checked_add(output.value)
+                    .ok_or(Error::<T>::ArithmeticOverflow)?;
+                extr_payment_value = Some(value);
+            }
+        }
+        let extr_payment_value = extr_payment_value
             .ok_or(Error::<T>::MalformedTransaction)?
             .try_into()
             .map_err(|_| Error::<T>::InvalidPaymentAmount)?;
@@ -617,6 +1257,45 @@ impl<T: Config> Pallet<T> {
         Ok((input_address, extr_payment_value))
     }
 
+    /// Checks that the transaction is included in the main chain and carries the given OP_RETURN,
+    /// returning the amount it pays to the recipient. Unlike `verify_and_validate_op_return_transaction`
+    /// the amount is not required to match an expected value, so that a request can be paid in parts.
+    /// The required confirmations are those of the full request amount, so that splitting a payment
+    /// into smaller parts does not lower them.
+    ///
+    /// # Arguments
+    ///
+    /// * `merkle_proof` - the merkle proof of the transaction
+    /// * `transaction` - the parsed transaction
+    /// * `recipient_btc_address` - the address the payment is expected to be made to
+    /// * `request_amount` - the total amount of the request
+    /// * `op_return_id` - the id of the request, expected in the OP_RETURN output
+    pub fn get_and_verify_op_return_payment<V: TryFrom<Value> + TryInto<Value>>(
+        merkle_proof: MerkleProof,
+        transaction: Transaction,
+        recipient_btc_address: BtcAddress,
+        request_amount: V,
+        op_return_id: H256,
+    ) -> Result<V, DispatchError> {
+        let request_amount: Value = request_amount
+            .try_into()
+            .map_err(|_| Error::<T>::InvalidPaymentAmount)?;
+        let tx_id = transaction.tx_id();
+        let payment_data = OpReturnPaymentData::<T>::try_from(transaction)?;
+        ensure!(payment_data.op_return == op_return_id, Error::<T>::InvalidPayment);
+        let amount_paid = payment_data
+            .amount_paid_to(recipient_btc_address)
+            .ok_or(Error::<T>::InvalidPayment)?;
+
+        // Verify that the transaction is indeed included in the main chain
+        let confirmations = Self::get_required_confirmations(request_amount);
+        Self::_verify_transaction_inclusion(tx_id, merkle_proof, Some(confirmations))?;
+
+        amount_paid
+            .try_into()
+            .map_err(|_| Error::<T>::InvalidPaymentAmount.into())
+    }
+
     /// interface to redeem,replace,refund to check that the payment is included and is valid
     pub fn verify_and_validate_op_return_transaction<V: TryInto<Value>>(
         merkle_proof: MerkleProof,
@@ -625,14 +1304,168 @@ impl<T: Config> Pallet<T> {
         expected_btc: V,
         op_return_id: H256,
     ) -> Result<(), DispatchError> {
+        let expected_btc: Value = expected_btc.try_into().map_err(|_| Error::<T>::InvalidPaymentAmount)?;
+
         // Verify that the transaction is indeed included in the main chain
-        Self::_verify_transaction_inclusion(transaction.tx_id(), merkle_proof, None)?;
+        let confirmations = Self::get_required_confirmations(expected_btc);
+        Self::_verify_transaction_inclusion(transaction.tx_id(), merkle_proof, Some(confirmations))?;
 
         // Parse transaction and check that it matches the given parameters
         Self::validate_op_return_transaction(transaction, recipient_btc_address, expected_btc, op_return_id)?;
         Ok(())
     }
 
+    /// Runs the checks of `get_and_verify_issue_payment` without failing, reporting the payment
+    /// that was found and the error of the first check that failed, if any
+    ///
+    /// # Arguments
+    ///
+    /// * `raw_merkle_proof` - The raw merkle proof as returned by bitcoin `gettxoutproof`
+    /// * `raw_tx` - raw Bitcoin transaction
+    /// * `recipient_btc_address` - the address the payment is expected to be made to
+    /// * `expected_amount` - the amount the request expects to be paid
+    pub fn dry_run_issue_payment<V: TryFrom<Value> + Clone>(
+        raw_merkle_proof: &[u8],
+        raw_tx: &[u8],
+        recipient_btc_address: BtcAddress,
+        expected_amount: V,
+    ) -> PaymentVerification<V> {
+        let mut verification = PaymentVerification {
+            amount_found: None,
+            expected_amount,
+            refund_address: None,
+            error: None,
+        };
+
+        let mut verify = || -> DispatchResult {
+            let transaction = Self::parse_transaction(raw_tx)?;
+            let merkle_proof = Self::parse_merkle_proof(raw_merkle_proof)?;
+
+            // extract the payment first so that it is reported even if the inclusion check fails
+            let payment = Self::get_issue_payment::<Value>(transaction.clone(), recipient_btc_address);
+            if let Ok((refund_address, amount)) = &payment {
+                verification.refund_address = Some(*refund_address);
+                verification.amount_found = V::try_from(*amount).ok();
+            }
+
+            let confirmations = payment
+                .as_ref()
+                .ok()
+                .map(|(_, amount)| Self::get_required_confirmations(*amount));
+            Self::_verify_transaction_inclusion(transaction.tx_id(), merkle_proof, confirmations)?;
+            let (_, amount) = payment?;
+            V::try_from(amount).map_err(|_| Error::<T>::InvalidPaymentAmount)?;
+            Ok(())
+        };
+        verification.error = verify().err();
+
+        verification
+    }
+
+    /// Runs the checks of `verify_and_validate_op_return_transaction` without failing, reporting
+    /// the payment that was found and the error of the first check that failed, if any
+    ///
+    /// # Arguments
+    ///
+    /// * `raw_merkle_proof` - The raw merkle proof as returned by bitcoin `gettxoutproof`
+    /// * `raw_tx` - raw Bitcoin transaction
+    /// * `recipient_btc_address` - the address the payment is expected to be made to
+    /// * `expected_amount` - the amount the request expects to be paid
+    /// * `op_return_id` - 32 byte hash identifier expected in OP_RETURN (replay protection)
+    pub fn dry_run_op_return_payment<V: TryFrom<Value> + TryInto<Value> + Clone>(
+        raw_merkle_proof: &[u8],
+        raw_tx: &[u8],
+        recipient_btc_address: BtcAddress,
+        expected_amount: V,
+        op_return_id: H256,
+    ) -> PaymentVerification<V> {
+        let mut verification = PaymentVerification {
+            amount_found: None,
+            expected_amount: expected_amount.clone(),
+            refund_address: None,
+            error: None,
+        };
+
+        let mut verify = || -> DispatchResult {
+            let transaction = Self::parse_transaction(raw_tx)?;
+            let merkle_proof = Self::parse_merkle_proof(raw_merkle_proof)?;
+
+            // extract the payment first so that it is reported even if the inclusion check fails
+            let payment_data = OpReturnPaymentData::<T>::try_from(transaction.clone());
+            if let Ok(payment_data) = &payment_data {
+                verification.amount_found = payment_data
+                    .amount_paid_to(recipient_btc_address)
+                    .and_then(|amount| V::try_from(amount).ok());
+                verification.refund_address = payment_data.return_to_self_address(recipient_btc_address);
+            }
+
+            let expected_amount: Value = expected_amount
+                .clone()
+                .try_into()
+                .map_err(|_| Error::<T>::InvalidPaymentAmount)?;
+            let confirmations = Self::get_required_confirmations(expected_amount);
+            Self::_verify_transaction_inclusion(transaction.tx_id(), merkle_proof, Some(confirmations))?;
+            payment_data?.ensure_valid_payment_to(expected_amount, recipient_btc_address, Some(op_return_id))?;
+            Ok(())
+        };
+        verification.error = verify().err();
+
+        verification
+    }
+
+    /// Runs the checks of `get_and_verify_op_return_payment` without failing, reporting the part
+    /// payment that was found and the error of the first check that failed, if any. The amount is
+    /// not checked, since a part payment may pay any amount up to what is left of the request.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw_merkle_proof` - The raw merkle proof as returned by bitcoin `gettxoutproof`
+    /// * `raw_tx` - raw Bitcoin transaction
+    /// * `recipient_btc_address` - the address the payment is expected to be made to
+    /// * `expected_amount` - the amount that is left to be paid
+    /// * `request_amount` - the amount of the whole request, which sets the required confirmations
+    /// * `op_return_id` - 32 byte hash identifier expected in OP_RETURN (replay protection)
+    pub fn dry_run_op_return_part_payment<V: TryFrom<Value> + TryInto<Value> + Clone>(
+        raw_merkle_proof: &[u8],
+        raw_tx: &[u8],
+        recipient_btc_address: BtcAddress,
+        expected_amount: V,
+        request_amount: V,
+        op_return_id: H256,
+    ) -> PaymentVerification<V> {
+        let mut verification = PaymentVerification {
+            amount_found: None,
+            expected_amount,
+            refund_address: None,
+            error: None,
+        };
+
+        let mut verify = || -> DispatchResult {
+            let transaction = Self::parse_transaction(raw_tx)?;
+            let merkle_proof = Self::parse_merkle_proof(raw_merkle_proof)?;
+            let tx_id = transaction.tx_id();
+
+            let payment_data = OpReturnPaymentData::<T>::try_from(transaction)?;
+            let amount_paid = payment_data.amount_paid_to(recipient_btc_address);
+            verification.amount_found = amount_paid.and_then(|amount| V::try_from(amount).ok());
+            verification.refund_address = payment_data.return_to_self_address(recipient_btc_address);
+            ensure!(payment_data.op_return == op_return_id, Error::<T>::InvalidPayment);
+            let amount_paid = amount_paid.ok_or(Error::<T>::InvalidPayment)?;
+
+            let request_amount: Value = request_amount
+                .clone()
+                .try_into()
+                .map_err(|_| Error::<T>::InvalidPaymentAmount)?;
+            let confirmations = Self::get_required_confirmations(request_amount);
+            Self::_verify_transaction_inclusion(tx_id, merkle_proof, Some(confirmations))?;
+            V::try_from(amount_paid).map_err(|_| Error::<T>::InvalidPaymentAmount)?;
+            Ok(())
+        };
+        verification.error = verify().err();
+
+        verification
+    }
+
     pub fn _verify_transaction_inclusion(
         tx_id: H256Le,
         merkle_proof: MerkleProof,
@@ -665,7 +1498,7 @@ impl<T: Config> Pallet<T> {
         ext::security::ensure_parachain_status_not_shutdown::<T>()?;
 
         let best_block_height = Self::get_best_block_height();
-        Self::ensure_no_ongoing_fork(best_block_height)?;
+        Self::ensure_no_ongoing_fork()?;
 
         let rich_header = Self::get_block_header_from_hash(block_hash)?;
 
@@ -682,6 +1515,45 @@ impl<T: Config> Pallet<T> {
         Ok(rich_header.block_header)
     }
 
+    /// interface to redeem to check that a payment to several recipients is included and is valid
+    ///
+    /// # Arguments
+    ///
+    /// * `merkle_proof` - the merkle proof of the transaction
+    /// * `transaction` - the parsed transaction
+    /// * `expected_payments` - the recipients and the amount each of them is expected to receive
+    /// * `op_return_id` - the id of the batch, expected in the OP_RETURN output
+    pub fn verify_and_validate_batch_op_return_transaction<V: TryInto<Value>>(
+        merkle_proof: MerkleProof,
+        transaction: Transaction,
+        expected_payments: Vec<(BtcAddress, V)>,
+        op_return_id: H256,
+    ) -> Result<(), DispatchError> {
+        let expected_payments = expected_payments
+            .into_iter()
+            .map(|(address, amount)| {
+                amount
+                    .try_into()
+                    .map(|amount| (address, amount))
+                    .map_err(|_| Error::<T>::InvalidPaymentAmount)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut total_amount: Value = 0;
+        for (_, amount) in expected_payments.iter() {
+            total_amount = total_amount
+                .checked_add(*amount)
+                .ok_or(Error::<T>::ArithmeticOverflow)?;
+        }
+
+        // Verify that the transaction is indeed included in the main chain
+        let confirmations = Self::get_required_confirmations(total_amount);
+        Self::_verify_transaction_inclusion(transaction.tx_id(), merkle_proof, Some(confirmations))?;
+
+        let payment_data = OpReturnPaymentData::<T>::try_from_batch(transaction)?;
+        payment_data.ensure_valid_batch_payment_to(&expected_payments, op_return_id)?;
+        Ok(())
+    }
+
     /// Checks if transaction is valid. Returns the return-to-self address, if any, for theft checking purposes
     fn validate_op_return_transaction<V: TryInto<i64>>(
         transaction: Transaction,
@@ -800,8 +1672,17 @@ impl<T: Config> Pallet<T> {
         Ok(ChainsHashes::<T>::get(chain_id, block_height))
     }
 
-    /// Get a block header from its hash
-    fn get_block_header_from_hash(block_hash: H256Le) -> Result<RichBlockHeader<T::BlockNumber>, DispatchError> {
+    /// Get a block header from its hash. While the migration to V2 runs, the headers it has not
+    /// reached yet are stored in the layout of the previous version and have no chainwork.
+    pub fn get_block_header_from_hash(block_hash: H256Le) -> Result<RichBlockHeader<T::BlockNumber>, DispatchError> {
+        if let Some(migration) = ChainworkMigration::<T>::get() {
+            // all layouts start with the position of the header, which tells if it was migrated
+            let header =
+                Self::get_unmigrated_block_header(migration.version, block_hash).ok_or(Error::<T>::BlockNotFound)?;
+            if !migration.is_migrated(header.chain_id, header.block_height) {
+                return Ok(header);
+            }
+        }
         BlockHeaders::<T>::try_get(block_hash).or(Err(Error::<T>::BlockNotFound.into()))
     }
 
@@ -819,6 +1700,28 @@ impl<T: Config> Pallet<T> {
         Self::get_block_header_from_hash(block_hash)
     }
 
+    /// Get a block header of the main chain from its height
+    pub fn get_main_chain_block_header(block_height: u32) -> Result<RichBlockHeader<T::BlockNumber>, DispatchError> {
+        let main_chain = Self::get_block_chain_from_id(MAIN_CHAIN_ID)?;
+        Self::get_block_header_from_height(&main_chain, block_height)
+    }
+
+    /// Get all forks, ordered by chainwork (descending), together with the hash of their tip
+    pub fn get_forks() -> Vec<(BlockChain, H256Le)> {
+        let mut forks = Vec::new();
+        // position 0 is the main chain
+        let mut position = 1;
+        while let Ok(chain_id) = Self::get_chain_id_from_position(position) {
+            if let Ok(blockchain) = Self::get_block_chain_from_id(chain_id) {
+                if let Ok(tip_hash) = Self::get_block_hash(chain_id, blockchain.max_height) {
+                    forks.push((blockchain, tip_hash));
+                }
+            }
+            position += 1;
+        }
+        forks
+    }
+
     /// Storage setter functions
     /// Set a new chain with position and id
     fn set_chain_from_position_and_id(position: u32, id: u32) {
@@ -870,8 +1773,10 @@ impl<T: Config> Pallet<T> {
         // get a new chain id
         let chain_id = Self::increment_chain_counter()?;
 
+        let chainwork = Self::store_rich_header(basic_block_header.clone(), block_height, chain_id)?;
+
         // generate an empty blockchain
-        let blockchain = Self::generate_blockchain(chain_id, block_height, basic_block_header.hash);
+        let blockchain = Self::generate_blockchain(chain_id, block_height, basic_block_header.hash, chainwork);
 
         // Store a pointer to BlockChain in ChainsIndex
         Self::set_block_chain_from_id(blockchain.chain_id, &blockchain);
@@ -879,13 +1784,11 @@ impl<T: Config> Pallet<T> {
         // Store the reference to the blockchain in Chains
         Self::insert_sorted(&blockchain)?;
 
-        Self::store_rich_header(basic_block_header.clone(), block_height, blockchain.chain_id);
-
         Ok(blockchain.chain_id)
     }
 
     /// Generate the raw blockchain from a chain Id and with a single block
-    fn generate_blockchain(chain_id: u32, block_height: u32, block_hash: H256Le) -> BlockChain {
+    fn generate_blockchain(chain_id: u32, block_height: u32, block_hash: H256Le, chainwork: U256) -> BlockChain {
         // initialize an empty chain
 
         Self::insert_block_hash(chain_id, block_height, block_hash);
@@ -894,6 +1797,7 @@ impl<T: Config> Pallet<T> {
             chain_id,
             start_height: block_height,
             max_height: block_height,
+            chainwork,
         }
     }
 
@@ -918,11 +1822,10 @@ impl<T: Config> Pallet<T> {
         }
         Self::insert_block_hash(blockchain.chain_id, block_height, basic_block_header.hash);
 
+        blockchain.chainwork = Self::store_rich_header(basic_block_header.clone(), block_height, blockchain.chain_id)?;
         blockchain.max_height = block_height;
         Self::set_block_chain_from_id(blockchain.chain_id, &blockchain);
 
-        Self::store_rich_header(basic_block_header.clone(), block_height, blockchain.chain_id);
-
         Ok(blockchain)
     }
 
@@ -931,6 +1834,70 @@ impl<T: Config> Pallet<T> {
         Self::bitcoin_confirmations()
     }
 
+    /// Get the confirmations required for a payment of the given amount. Tiers can only lower the
+    /// confirmations below `StableBitcoinConfirmations`.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - the amount of the payment in satoshis
+    pub fn get_required_confirmations(amount: Value) -> u32 {
+        let stable_confirmations = Self::get_stable_transaction_confirmations();
+        Self::confirmation_tiers()
+            .into_iter()
+            .find(|(max_amount, _)| amount <= *max_amount)
+            .map_or(stable_confirmations, |(_, confirmations)| {
+                confirmations.min(stable_confirmations)
+            })
+    }
+
+    fn ensure_valid_confirmation_tiers(tiers: &[(Value, u32)]) -> DispatchResult {
+        ensure!(
+            tiers.len() <= MAX_CONFIRMATION_TIERS as usize,
+            Error::<T>::TooManyConfirmationTiers
+        );
+        for pair in tiers.windows(2) {
+            let ((amount1, confirmations1), (amount2, confirmations2)) = (pair[0], pair[1]);
+            ensure!(
+                amount1 < amount2 && confirmations1 <= confirmations2,
+                Error::<T>::InvalidConfirmationTiers
+            );
+        }
+        Ok(())
+    }
+
+    fn ensure_valid_pruning_depth(pruning_depth: u32, disable_difficulty_check: bool) -> DispatchResult {
+        // the last retarget header must be retained to verify the difficulty of the next period
+        ensure!(
+            pruning_depth == 0
+                || disable_difficulty_check
+                || pruning_depth >= T::ChainParams::DIFFICULTY_ADJUSTMENT_INTERVAL,
+            Error::<T>::InvalidPruningDepth
+        );
+        Ok(())
+    }
+
+    /// Get the position of the last element in `Chains` by binary search, relying on the keys
+    /// being consecutive and on there being fewer positions than chain ids ever assigned.
+    ///
+    /// # Returns
+    ///
+    /// The last position, or `None` if `Chains` is empty, and the number of storage reads
+    fn get_last_chain_position() -> (Option<u32>, Weight) {
+        let mut reads: Weight = 1;
+        let (mut low, mut high) = (0, Self::get_chain_counter());
+        // invariant: positions below `low` exist, positions from `high` onwards do not
+        while low < high {
+            let mid = low + (high - low) / 2;
+            reads += 1;
+            if Chains::<T>::contains_key(mid) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        (low.checked_sub(1), reads)
+    }
+
     // *********************************
     // END: Storage getter functions
     // *********************************
@@ -971,24 +1938,72 @@ impl<T: Config> Pallet<T> {
         );
 
         // Check that the PoW hash satisfies the target set in the block header
-        ensure!(block_header.hash.as_u256() < block_header.target, Error::<T>::LowDiff);
+        ensure!(
+            T::ChainParams::pow_hash(block_header).as_u256() < block_header.target,
+            Error::<T>::LowDiff
+        );
+
+        // Check that the block does not conflict with a checkpoint
+        Self::ensure_no_checkpoint_conflict(block_height, block_header.hash)?;
+
+        // Check that the timestamp is greater than the median time of the previous blocks
+        ensure!(
+            block_header.timestamp > prev_block_header.median_time_past,
+            Error::<T>::TimestampBeforeMedianTimePast
+        );
+
+        // Check that the timestamp is not too far in the future, if the parachain time is known
+        let now = Self::current_timestamp();
+        if now > 0 {
+            ensure!(
+                block_header.timestamp <= now.saturating_add(T::ChainParams::MAX_FUTURE_BLOCK_TIME),
+                Error::<T>::TimestampTooFarInFuture
+            );
+        }
 
         if Self::disable_difficulty_check() {
             return Ok(());
         }
 
-        let expected_target =
-            if block_height >= DIFFICULTY_ADJUSTMENT_INTERVAL && block_height % DIFFICULTY_ADJUSTMENT_INTERVAL == 0 {
-                Self::compute_new_target(&prev_block_header, block_height)?
-            } else {
-                prev_block_header.block_header.target
-            };
+        let is_retarget_height = block_height >= T::ChainParams::DIFFICULTY_ADJUSTMENT_INTERVAL
+            && block_height % T::ChainParams::DIFFICULTY_ADJUSTMENT_INTERVAL == 0;
+
+        let network = Self::bitcoin_network();
+        let chain_target = match network {
+            // regtest never retargets
+            Network::Regtest => Some(prev_block_header.block_header.target),
+            _ => T::ChainParams::retarget(&StoredHeaders::<T>(PhantomData), block_header, block_height, network)
+                .map_err(|_| Error::<T>::BlockNotFound)?,
+        };
+
+        let expected_target = match chain_target {
+            Some(target) => target,
+            None if is_retarget_height => Self::compute_new_target(&prev_block_header, block_height)?,
+            None if network == Network::Testnet => Self::compute_testnet_target(block_header, prev_block_header)?,
+            None => prev_block_header.block_header.target,
+        };
 
         ensure!(block_header.target == expected_target, Error::<T>::DiffTargetHeader);
 
         Ok(())
     }
 
+    /// Checks that the block hash matches the checkpoint at the given height, if any
+    ///
+    /// # Arguments
+    ///
+    /// * `block_height`: height of the block
+    /// * `block_hash`: hash of the block
+    fn ensure_no_checkpoint_conflict(block_height: u32, block_hash: H256Le) -> DispatchResult {
+        match Self::checkpoint(block_height) {
+            Some(checkpoint_hash) => {
+                ensure!(checkpoint_hash == block_hash, Error::<T>::CheckpointMismatch);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
     /// Computes Bitcoin's PoW retarget algorithm for a given block height
     ///
     /// # Arguments
@@ -1001,27 +2016,104 @@ impl<T: Config> Pallet<T> {
     ) -> Result<U256, DispatchError> {
         // get time of last retarget
         let last_retarget_time = Self::get_last_retarget_time(prev_block_header.chain_id, block_height)?;
-        // Compute new target
-        let actual_timespan = if ((prev_block_header.block_header.timestamp as u64 - last_retarget_time) as u32)
-            < (TARGET_TIMESPAN / TARGET_TIMESPAN_DIVISOR)
-        {
-            TARGET_TIMESPAN / TARGET_TIMESPAN_DIVISOR
-        } else {
-            TARGET_TIMESPAN * TARGET_TIMESPAN_DIVISOR
-        };
+        // limit the adjustment to a factor of TARGET_TIMESPAN_DIVISOR in either direction
+        let actual_timespan = (prev_block_header.block_header.timestamp as u64)
+            .saturating_sub(last_retarget_time)
+            .max((T::ChainParams::TARGET_TIMESPAN / T::ChainParams::TARGET_TIMESPAN_DIVISOR) as u64)
+            .min((T::ChainParams::TARGET_TIMESPAN * T::ChainParams::TARGET_TIMESPAN_DIVISOR) as u64);
 
         let new_target = U256::from(actual_timespan)
             .checked_mul(prev_block_header.block_header.target)
             .ok_or(Error::<T>::ArithmeticOverflow)?
-            .checked_div(U256::from(TARGET_TIMESPAN))
+            .checked_div(U256::from(T::ChainParams::TARGET_TIMESPAN))
             .ok_or(Error::<T>::ArithmeticUnderflow)?;
 
         // ensure target does not exceed max. target
-        Ok(if new_target > UNROUNDED_MAX_TARGET {
-            UNROUNDED_MAX_TARGET
+        let new_target = if new_target > T::ChainParams::UNROUNDED_MAX_TARGET {
+            T::ChainParams::UNROUNDED_MAX_TARGET
         } else {
             new_target
-        })
+        };
+
+        // the block header stores the target in compact form
+        Ok(round_target(&new_target).map_err(Error::<T>::from)?)
+    }
+
+    /// Computes the expected target of a testnet block outside of a retarget height. Testnet
+    /// permits a block at minimum difficulty if it is timestamped more than twice the target
+    /// spacing after its predecessor. Otherwise the block must use the target of the last block
+    /// in the current difficulty period that was not mined at minimum difficulty.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_header` - the block header to compute the target for
+    /// * `prev_block_header` - the previous block header in the chain
+    // https://github.com/bitcoin/bitcoin/blob/5ba5becbb5d8c794efe579caeea7eea64f895a13/src/pow.cpp#L19-L36
+    fn compute_testnet_target(
+        block_header: &BlockHeader,
+        prev_block_header: RichBlockHeader<T::BlockNumber>,
+    ) -> Result<U256, DispatchError> {
+        if block_header.timestamp as u64
+            > prev_block_header.block_header.timestamp as u64 + 2 * T::ChainParams::TARGET_SPACING as u64
+        {
+            return Self::get_min_difficulty_target();
+        }
+        Self::get_testnet_period_target(&prev_block_header)
+    }
+
+    /// Returns the target of the last block up to and including the given testnet block that
+    /// was not mined at minimum difficulty, or that is the first block of its difficulty period.
+    /// The target is looked up in `TestnetPeriodTargets` for blocks mined at minimum difficulty.
+    /// Blocks without an entry are walked back, stopping at the first block of the relay.
+    ///
+    /// # Arguments
+    ///
+    /// * `rich_block_header` - the testnet block header
+    fn get_testnet_period_target(rich_block_header: &RichBlockHeader<T::BlockNumber>) -> Result<U256, DispatchError> {
+        let min_difficulty_target = Self::get_min_difficulty_target()?;
+        let start_height = StartBlockHeight::<T>::get();
+
+        let mut last_block_header = rich_block_header.clone();
+        while last_block_header.block_height % T::ChainParams::DIFFICULTY_ADJUSTMENT_INTERVAL != 0
+            && last_block_header.block_header.target == min_difficulty_target
+        {
+            if let Ok(target) = TestnetPeriodTargets::<T>::try_get(last_block_header.block_header.hash) {
+                return Ok(target);
+            }
+            if last_block_header.block_height <= start_height {
+                break;
+            }
+            last_block_header = match BlockHeaders::<T>::try_get(last_block_header.block_header.hash_prev_block) {
+                Ok(prev_block_header) => prev_block_header,
+                Err(_) => break,
+            };
+        }
+        Ok(last_block_header.block_header.target)
+    }
+
+    /// Records the period target of a testnet block mined at minimum difficulty, so that the
+    /// target of its successors is found without walking back the difficulty period.
+    ///
+    /// # Arguments
+    ///
+    /// * `rich_block_header` - the newly stored block header
+    fn store_testnet_period_target(rich_block_header: &RichBlockHeader<T::BlockNumber>) -> DispatchResult {
+        if Self::bitcoin_network() != Network::Testnet
+            || rich_block_header.block_height % T::ChainParams::DIFFICULTY_ADJUSTMENT_INTERVAL == 0
+            || rich_block_header.block_header.target != Self::get_min_difficulty_target()?
+        {
+            return Ok(());
+        }
+        if let Ok(prev_block_header) = BlockHeaders::<T>::try_get(rich_block_header.block_header.hash_prev_block) {
+            let target = Self::get_testnet_period_target(&prev_block_header)?;
+            TestnetPeriodTargets::<T>::insert(rich_block_header.block_header.hash, target);
+        }
+        Ok(())
+    }
+
+    /// Returns the target of a block mined at minimum difficulty
+    fn get_min_difficulty_target() -> Result<U256, DispatchError> {
+        Ok(round_target(&T::ChainParams::UNROUNDED_MAX_TARGET).map_err(Error::<T>::from)?)
     }
 
     /// Returns the timestamp of the last difficulty retarget on the specified BlockChain, given the current block
@@ -1033,8 +2125,10 @@ impl<T: Config> Pallet<T> {
     /// * `block_height` - current block height
     fn get_last_retarget_time(chain_id: u32, block_height: u32) -> Result<u64, DispatchError> {
         let block_chain = Self::get_block_chain_from_id(chain_id)?;
-        let last_retarget_header =
-            Self::get_block_header_from_height(&block_chain, block_height - DIFFICULTY_ADJUSTMENT_INTERVAL)?;
+        let last_retarget_header = Self::get_block_header_from_height(
+            &block_chain,
+            block_height - T::ChainParams::DIFFICULTY_ADJUSTMENT_INTERVAL,
+        )?;
         Ok(last_retarget_header.block_header.timestamp as u64)
     }
 
@@ -1056,6 +2150,7 @@ impl<T: Config> Pallet<T> {
     /// Ok((best_block_hash, best_block_height)) if successful, Err otherwise
     fn swap_main_blockchain(fork: &BlockChain) -> Result<(H256Le, u32), DispatchError> {
         let new_best_block = Self::get_block_hash(fork.chain_id, fork.max_height)?;
+        let old_main_max_height = Self::get_block_chain_from_id(MAIN_CHAIN_ID)?.max_height;
 
         // Set BestBlock and BestBlockHeight to the submitted block
         Self::set_best_block(new_best_block);
@@ -1096,16 +2191,26 @@ impl<T: Config> Pallet<T> {
             }
         }
 
-        // update the max_height of main chain
+        // if the fork has more work but fewer blocks, the remaining old main chain blocks also need
+        // to be moved to `fork`
+        for block_height in fork.max_height.saturating_add(1)..=old_main_max_height {
+            if let Ok(replaced_block_hash) = ChainsHashes::<T>::try_get(MAIN_CHAIN_ID, block_height) {
+                ChainsHashes::<T>::remove(MAIN_CHAIN_ID, block_height);
+                Self::move_block_from_mainchain(block_height, replaced_block_hash, fork.chain_id);
+            }
+        }
+
+        // update the max_height and chainwork of main chain
         Self::mutate_block_chain_from_id(
             MAIN_CHAIN_ID,
             BlockChain {
                 max_height: fork.max_height,
+                chainwork: fork.chainwork,
                 ..Self::get_block_chain_from_id(MAIN_CHAIN_ID)?
             },
         );
 
-        // we swapped main chain and `fork`, so it will need to be resorted. The new max_height of this fork
+        // we swapped main chain and `fork`, so it will need to be resorted. The new chainwork of this fork
         // is strictly smaller than before, so do a single bubble sort pass to the right
         let start = Self::get_chain_position_from_chain_id(fork.chain_id)?;
         // ideally we'd iterate over start..Chains::<T>::len(), but unfortunately Chains does not implement
@@ -1117,9 +2222,9 @@ impl<T: Config> Pallet<T> {
                 break;
             }
 
-            let height1 = Self::get_block_chain_from_id(Self::get_chain_id_from_position(i)?)?.max_height;
-            let height2 = Self::get_block_chain_from_id(Self::get_chain_id_from_position(i + 1)?)?.max_height;
-            if height1 < height2 {
+            let chainwork1 = Self::get_block_chain_from_id(Self::get_chain_id_from_position(i)?)?.chainwork;
+            let chainwork2 = Self::get_block_chain_from_id(Self::get_chain_id_from_position(i + 1)?)?.chainwork;
+            if chainwork1 < chainwork2 {
                 Self::swap_chain(i, i + 1);
             } else {
                 break;
@@ -1149,15 +2254,46 @@ impl<T: Config> Pallet<T> {
 
         // if there was a block at block_height in the mainchain, we need to move it
         if let Ok(replaced_block_hash) = replaced_block_hash {
-            ChainsHashes::<T>::insert(chain_id_for_old_main_blocks, block_height, replaced_block_hash);
-            BlockHeaders::<T>::mutate(&replaced_block_hash, |header| {
-                header.chain_id = chain_id_for_old_main_blocks
-            });
+            Self::move_block_from_mainchain(block_height, replaced_block_hash, chain_id_for_old_main_blocks);
         }
 
         Ok(())
     }
 
+    /// Stores a block that was removed from the main chain in the given fork, and flags the
+    /// requests it settled for review.
+    fn move_block_from_mainchain(block_height: u32, block_hash: H256Le, chain_id: u32) {
+        ChainsHashes::<T>::insert(chain_id, block_height, block_hash);
+        BlockHeaders::<T>::mutate(&block_hash, |header| header.chain_id = chain_id);
+        Self::revoke_settled_requests(block_hash);
+    }
+
+    /// Records that the payment of the given request is included in the given main chain block,
+    /// so that the request can be revoked if the block is removed from the main chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - hash of the block that includes the payment
+    /// * `request` - the request settled by the payment
+    pub fn record_settled_request(block_hash: H256Le, request: SettledRequest) {
+        // nothing is recorded for blocks that are not stored, since they are never pruned
+        if Self::block_header_exists(block_hash) {
+            SettledRequests::<T>::append(block_hash, request);
+        }
+    }
+
+    /// Flags the requests settled by a block that was removed from the main chain for review.
+    fn revoke_settled_requests(block_hash: H256Le) {
+        let requests = SettledRequests::<T>::take(block_hash);
+        if requests.is_empty() {
+            return;
+        }
+        for request in requests.iter() {
+            RevokedRequests::<T>::insert(request, block_hash);
+        }
+        Self::deposit_event(<Event<T>>::RevokeSettledRequests(block_hash, requests));
+    }
+
     // returns (child, parent)
     fn enumerate_chain_links(
         start: H256Le,
@@ -1182,8 +2318,8 @@ impl<T: Config> Pallet<T> {
         })
     }
     /// Checks if a newly inserted fork results in an update to the sorted
-    /// Chains mapping. This happens when the max height of the fork is greater
-    /// than the max height of the previous element in the Chains mapping.
+    /// Chains mapping. This happens when the chainwork of the fork is greater
+    /// than the chainwork of the previous element in the Chains mapping.
     ///
     /// # Arguments
     ///
@@ -1191,11 +2327,11 @@ impl<T: Config> Pallet<T> {
     fn reorganize_chains(fork: &BlockChain) -> Result<(), DispatchError> {
         // get the position of the fork in Chains
         let fork_position: u32 = Self::get_chain_position_from_chain_id(fork.chain_id)?;
-        // check if the previous element in Chains has a lower block_height
+        // check if the previous element in Chains has less chainwork
         let mut current_position = fork_position;
-        let mut current_height = fork.max_height;
+        let mut current_chainwork = fork.chainwork;
 
-        // swap elements as long as previous block height is smaller
+        // swap elements as long as previous chainwork is smaller
         while current_position > 0 {
             // get the previous position
             let prev_position = current_position - 1;
@@ -1208,17 +2344,17 @@ impl<T: Config> Pallet<T> {
                 continue;
             };
 
-            // get the previous blockchain height
-            let prev_height = Self::get_block_chain_from_id(prev_blockchain_id)?.max_height;
-            // swap elements if block height is greater
-            if prev_height < current_height {
+            // get the previous blockchain
+            let prev_blockchain = Self::get_block_chain_from_id(prev_blockchain_id)?;
+            // swap elements if chainwork is greater
+            if prev_blockchain.chainwork < current_chainwork {
                 // Check if swap occurs on the main chain element
                 if prev_blockchain_id == MAIN_CHAIN_ID {
                     // if the previous position is the top element
-                    // and the current height is more than the
-                    // STABLE_TRANSACTION_CONFIRMATIONS ahead
+                    // and the current chainwork is more than
+                    // STABLE_TRANSACTION_CONFIRMATIONS blocks ahead
                     // we are swapping the main chain
-                    if prev_height + Self::get_stable_transaction_confirmations() <= current_height {
+                    if Self::is_ahead_by_stable_confirmations(fork, &prev_blockchain)? {
                         // Swap the mainchain. As an optimization, this function returns the
                         // new best block hash and its height
                         let (new_chain_tip, block_height) = Self::swap_main_blockchain(&fork)?;
@@ -1228,9 +2364,9 @@ impl<T: Config> Pallet<T> {
                         Self::deposit_event(<Event<T>>::ChainReorg(new_chain_tip, block_height, fork_depth));
                     } else {
                         Self::deposit_event(<Event<T>>::ForkAheadOfMainChain(
-                            prev_height,     // main chain height
-                            fork.max_height, // fork height
-                            fork.chain_id,   // fork id
+                            prev_blockchain.max_height, // main chain height
+                            fork.max_height,            // fork height
+                            fork.chain_id,              // fork id
                         ));
                     }
                     // successful reorg
@@ -1242,7 +2378,7 @@ impl<T: Config> Pallet<T> {
 
                 // update the current chain to the previous one
                 current_position = prev_position;
-                current_height = prev_height;
+                current_chainwork = prev_blockchain.chainwork;
             } else {
                 break;
             }
@@ -1251,7 +2387,36 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
-    /// Insert a new fork into the Chains mapping sorted by its max height
+    /// Checks if the fork has accumulated at least STABLE_TRANSACTION_CONFIRMATIONS blocks worth of
+    /// work more than the main chain, measured at the difficulty of the main chain's tip
+    ///
+    /// # Arguments
+    ///
+    /// * `fork` - the fork that may replace the main chain
+    /// * `main_chain` - the current main chain
+    fn is_ahead_by_stable_confirmations(fork: &BlockChain, main_chain: &BlockChain) -> Result<bool, DispatchError> {
+        let required_chainwork = main_chain
+            .chainwork
+            .checked_add(Self::get_stable_chainwork_lead(main_chain)?)
+            .ok_or(Error::<T>::ArithmeticOverflow)?;
+        Ok(fork.chainwork >= required_chainwork)
+    }
+
+    /// Get the work of STABLE_TRANSACTION_CONFIRMATIONS blocks at the difficulty of the main chain's
+    /// tip, which is the lead one chain needs over another to be stable
+    fn get_stable_chainwork_lead(main_chain: &BlockChain) -> Result<U256, DispatchError> {
+        Self::get_chain_tip_work(main_chain)?
+            .checked_mul(Self::get_stable_transaction_confirmations().into())
+            .ok_or(Error::<T>::ArithmeticOverflow.into())
+    }
+
+    /// Get the work of the highest block in the given chain
+    fn get_chain_tip_work(blockchain: &BlockChain) -> Result<U256, DispatchError> {
+        let tip_hash = Self::get_block_hash(blockchain.chain_id, blockchain.max_height)?;
+        Ok(Self::get_block_header_from_hash(tip_hash)?.block_header.work())
+    }
+
+    /// Insert a new fork into the Chains mapping sorted by its chainwork
     ///
     /// # Arguments
     ///
@@ -1267,17 +2432,17 @@ impl<T: Config> Pallet<T> {
         // by default, we insert it as the last element
         let mut position_blockchain = max_chain_element;
 
-        // Starting from the second highest element, find where to insert the new fork
-        // the previous element's block height should be higher or equal
-        // the next element's block height should be lower or equal
+        // Starting from the second heaviest element, find where to insert the new fork
+        // the previous element's chainwork should be higher or equal
+        // the next element's chainwork should be lower or equal
         // NOTE: we never want to insert a new main chain through this function
         for (curr_position, curr_chain_id) in chains.iter().skip(1) {
-            // get the height of the current chain_id
-            let curr_height = Self::get_block_chain_from_id(*curr_chain_id)?.max_height;
+            // get the chainwork of the current chain_id
+            let curr_chainwork = Self::get_block_chain_from_id(*curr_chain_id)?.chainwork;
 
-            // if the height of the current blockchain is lower than
+            // if the chainwork of the current blockchain is lower than
             // the new blockchain, it should be inserted at that position
-            if curr_height <= blockchain.max_height {
+            if curr_chainwork <= blockchain.chainwork {
                 position_blockchain = *curr_position;
                 break;
             };
@@ -1301,6 +2466,109 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
+    /// Removes block headers that are more than `PruningDepth` blocks below the best block.
+    /// Main chain headers are pruned from the bottom of the chain upwards, after which forks
+    /// whose tip fell below the pruning height are removed. `Chains` is sorted by chainwork, so
+    /// such forks can sit anywhere behind the main chain: the last `MAX_PRUNING_FORK_SCAN`
+    /// positions, which hold the lightest forks, are checked against the pruning height. The
+    /// positions of removed forks are closed by moving the later forks down by one.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_pruned` - maximum number of block headers to remove
+    ///
+    /// # Returns
+    ///
+    /// The weight consumed
+    fn prune_block_headers(max_pruned: u32) -> Weight {
+        let mut reads: Weight = 1;
+        let mut writes: Weight = 0;
+        let db_weight = |reads, writes| T::DbWeight::get().reads_writes(reads, writes);
+
+        let pruning_depth = Self::pruning_depth();
+        if pruning_depth == 0 || !Self::best_block_exists() {
+            return db_weight(reads + 1, writes);
+        }
+        reads += 2;
+        let prune_below = Self::get_best_block_height().saturating_sub(pruning_depth);
+        let mut remaining = max_pruned;
+
+        // prune the oldest main chain headers
+        reads += 1;
+        if let Ok(mut main_chain) = Self::get_block_chain_from_id(MAIN_CHAIN_ID) {
+            let start_height = main_chain.start_height;
+            while remaining > 0 && main_chain.start_height < prune_below {
+                reads += 1;
+                writes += Self::remove_block_hash(MAIN_CHAIN_ID, main_chain.start_height);
+                main_chain.start_height += 1;
+                remaining -= 1;
+            }
+            if main_chain.start_height != start_height {
+                writes += 1;
+                Self::set_block_chain_from_id(MAIN_CHAIN_ID, &main_chain);
+            }
+        }
+
+        // remove forks that fell behind, scanning the lightest forks at the end of `Chains`
+        let (last_position, chains_reads) = Self::get_last_chain_position();
+        reads += chains_reads;
+        let mut last_position = match last_position {
+            Some(last_position) if last_position > 0 => last_position,
+            _ => return db_weight(reads, writes),
+        };
+        let first_position = last_position.saturating_sub(MAX_PRUNING_FORK_SCAN - 1).max(1);
+        for position in (first_position..=last_position).rev() {
+            if remaining == 0 {
+                break;
+            }
+            reads += 2;
+            let mut fork = match Self::get_chain_id_from_position(position).and_then(Self::get_block_chain_from_id) {
+                Ok(fork) if fork.max_height < prune_below => fork,
+                _ => continue,
+            };
+
+            while remaining > 0 && fork.start_height <= fork.max_height {
+                reads += 1;
+                writes += Self::remove_block_hash(fork.chain_id, fork.start_height);
+                fork.start_height += 1;
+                remaining -= 1;
+            }
+
+            writes += 1;
+            if fork.start_height > fork.max_height {
+                ChainsIndex::<T>::remove(fork.chain_id);
+                // keep the positions consecutive by moving the removed fork to the end
+                for later_position in position..last_position {
+                    reads += 2;
+                    writes += 2;
+                    Self::swap_chain(later_position, later_position + 1);
+                }
+                writes += 1;
+                Chains::<T>::remove(last_position);
+                last_position -= 1;
+            } else {
+                Self::set_block_chain_from_id(fork.chain_id, &fork);
+            }
+        }
+
+        db_weight(reads, writes)
+    }
+
+    /// Removes the block at the given height from the chain along with its header, returning
+    /// the number of storage writes
+    fn remove_block_hash(chain_id: u32, block_height: u32) -> Weight {
+        match ChainsHashes::<T>::try_get(chain_id, block_height) {
+            Ok(block_hash) => {
+                ChainsHashes::<T>::remove(chain_id, block_height);
+                BlockHeaders::<T>::remove(block_hash);
+                SettledRequests::<T>::remove(block_hash);
+                TestnetPeriodTargets::<T>::remove(block_hash);
+                4
+            }
+            Err(_) => 0,
+        }
+    }
+
     /// Checks if the given transaction confirmations are greater/equal to the
     /// requested confirmations (and/or the global k security parameter)
     ///
@@ -1347,31 +2615,96 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
-    fn ensure_no_ongoing_fork(best_block_height: u32) -> Result<(), DispatchError> {
+    /// Ensures that the main chain is ahead of the next best fork by the work of
+    /// STABLE_TRANSACTION_CONFIRMATIONS blocks. Chains are compared by chainwork rather than by
+    /// height, so a long fork of low difficulty blocks does not hold up transaction verification.
+    fn ensure_no_ongoing_fork() -> Result<(), DispatchError> {
         // check if there is a next best fork
-        match Self::get_chain_id_from_position(1) {
-            // if yes, check that the main chain is at least Self::confirmations() ahead
-            Ok(id) => {
-                let next_best_fork_height = Self::get_block_chain_from_id(id)?.max_height;
-
-                runtime_print!("Best block height: {}", best_block_height);
-                runtime_print!("Next best fork height: {}", next_best_fork_height);
-                // fail if there is an ongoing fork
+        if let Ok(id) = Self::get_chain_id_from_position(1) {
+            let main_chain = Self::get_block_chain_from_id(MAIN_CHAIN_ID)?;
+            let next_best_fork = Self::get_block_chain_from_id(id)?;
+
+            // the chainwork is not known until the migration to V2 completes, so the chains are
+            // compared by height as before
+            if ChainworkMigration::<T>::exists() {
                 ensure!(
-                    best_block_height >= next_best_fork_height + Self::get_stable_transaction_confirmations(),
+                    main_chain.max_height >= next_best_fork.max_height + Self::get_stable_transaction_confirmations(),
                     Error::<T>::OngoingFork
                 );
+                return Ok(());
             }
-            // else, do nothing if there is no fork
-            Err(_) => {}
+
+            runtime_print!("Best chainwork: {:?}", main_chain.chainwork);
+            runtime_print!("Next best fork chainwork: {:?}", next_best_fork.chainwork);
+            // fail if there is an ongoing fork
+            let required_chainwork = next_best_fork
+                .chainwork
+                .checked_add(Self::get_stable_chainwork_lead(&main_chain)?)
+                .ok_or(Error::<T>::ArithmeticOverflow)?;
+            ensure!(main_chain.chainwork >= required_chainwork, Error::<T>::OngoingFork);
         }
         Ok(())
     }
 
-    fn store_rich_header(basic_block_header: BlockHeader, block_height: u32, chain_id: u32) {
+    /// Stores the enriched block header and returns its cumulative chainwork
+    fn store_rich_header(
+        basic_block_header: BlockHeader,
+        block_height: u32,
+        chain_id: u32,
+    ) -> Result<U256, DispatchError> {
         let para_height = ext::security::active_block_number::<T>();
-        let block_header = RichBlockHeader::new(basic_block_header, chain_id, block_height, para_height);
+        let mut block_header = RichBlockHeader::new(basic_block_header, chain_id, block_height, para_height);
+        block_header.median_time_past = Self::compute_median_time_past(&basic_block_header);
+
+        // the first stored header only accounts for its own work
+        let prev_chainwork = BlockHeaders::<T>::try_get(basic_block_header.hash_prev_block)
+            .map(|prev_header| prev_header.chainwork)
+            .unwrap_or_default();
+        block_header.chainwork = prev_chainwork
+            .checked_add(basic_block_header.work())
+            .ok_or(Error::<T>::ArithmeticOverflow)?;
+
+        Self::store_testnet_period_target(&block_header)?;
         Self::set_block_header_from_hash(basic_block_header.hash, &block_header);
+        Ok(block_header.chainwork)
+    }
+
+    /// Computes the median timestamp of the given block and its `ChainParams::MEDIAN_TIME_SPAN - 1`
+    /// predecessors.
+    /// Returns zero if not all predecessors are stored (e.g. right after initialization), since
+    /// the median of a partial window could reject valid successors.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_header`: the block header to compute the median time past for
+    fn compute_median_time_past(block_header: &BlockHeader) -> u32 {
+        let median_time_span = T::ChainParams::MEDIAN_TIME_SPAN;
+        let mut timestamps = Vec::with_capacity(median_time_span);
+        timestamps.push(block_header.timestamp);
+
+        let mut prev_hash = block_header.hash_prev_block;
+        while timestamps.len() < median_time_span {
+            // stop at the first predecessor that is not (or no longer) stored
+            let prev_header = match BlockHeaders::<T>::try_get(prev_hash) {
+                Ok(rich_header) => rich_header.block_header,
+                Err(_) => break,
+            };
+            timestamps.push(prev_header.timestamp);
+            prev_hash = prev_header.hash_prev_block;
+        }
+
+        if timestamps.len() < median_time_span {
+            return 0;
+        }
+
+        timestamps.sort_unstable();
+        timestamps[median_time_span / 2]
+    }
+
+    /// Returns the current parachain time in seconds
+    fn current_timestamp() -> u32 {
+        let now: u64 = <pallet_timestamp::Pallet<T>>::get().unique_saturated_into();
+        (now / 1000).try_into().unwrap_or(u32::MAX)
     }
 
     fn update_chain_head(basic_block_header: &BlockHeader, block_height: u32) {
@@ -1398,6 +2731,11 @@ impl<T: Config> From<BitcoinError> for Error<T> {
             BitcoinError::InvalidBtcHash => Self::InvalidBtcHash,
             BitcoinError::InvalidScript => Self::InvalidScript,
             BitcoinError::InvalidBtcAddress => Self::InvalidBtcAddress,
+            BitcoinError::InvalidAddressEncoding => Self::InvalidAddressEncoding,
+            BitcoinError::InvalidAddressChecksum => Self::InvalidAddressChecksum,
+            BitcoinError::InvalidSigHashType => Self::InvalidSigHashType,
+            BitcoinError::InvalidInputIndex => Self::InvalidInputIndex,
+            BitcoinError::ScriptVerificationFailed => Self::ScriptVerificationFailed,
             BitcoinError::ArithmeticOverflow => Self::ArithmeticOverflow,
             BitcoinError::ArithmeticUnderflow => Self::ArithmeticUnderflow,
         }
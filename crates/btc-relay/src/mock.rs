@@ -74,6 +74,7 @@ impl Config for Test {
     type Event = TestEvent;
     type ParachainBlocksPerBitcoinBlock = ParachainBlocksPerBitcoinBlock;
     type WeightInfo = ();
+    type ChainParams = crate::BitcoinChainParams;
 }
 
 parameter_types! {
@@ -106,6 +107,11 @@ impl ExtBuilder {
             parachain_confirmations: PARACHAIN_CONFIRMATIONS,
             disable_difficulty_check: false,
             disable_inclusion_check: false,
+            pruning_depth: 0,
+            bitcoin_network: bitcoin::Network::Mainnet,
+            checkpoints: vec![],
+            confirmation_tiers: vec![],
+            block_header_snapshot: None,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
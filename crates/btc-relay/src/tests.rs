@@ -1,16 +1,23 @@
 /// Tests for BTC-Relay
 use sp_core::U256;
 
-use crate::{ext, mock::*, types::*, BtcAddress, Error};
+use crate::{
+    ext, mock::*, types::*, BitcoinChainParams, BtcAddress, ChainParams, Error, HeaderChain, MissingHeader,
+    StoredHeaders,
+};
 
 type Event = crate::Event<Test>;
 
-use crate::{Chains, ChainsIndex};
+use crate::{
+    BitcoinNetwork, BlockHeaders, Chains, ChainsIndex, ChainworkMigration, PruningDepth, StorageVersion,
+    TestnetPeriodTargets,
+};
 use bitcoin::{formatter::TryFormattable, merkle::*, parser::*, types::*};
-use frame_support::{assert_err, assert_ok};
+use frame_support::{assert_err, assert_ok, dispatch::DispatchError};
 use mocktopus::mocking::*;
 use sp_std::{
     convert::{TryFrom, TryInto},
+    marker::PhantomData,
     str::FromStr,
 };
 /// # Getters and setters
@@ -48,6 +55,8 @@ fn get_block_header_from_hash_succeeds() {
             block_height,
             chain_id,
             para_height: Default::default(),
+            median_time_past: Default::default(),
+            chainwork: Default::default(),
         };
 
         BTCRelay::set_block_header_from_hash(rich_header.block_hash(), &rich_header);
@@ -144,6 +153,8 @@ fn store_block_header_on_mainchain_succeeds() {
             block_height,
             chain_id,
             para_height: Default::default(),
+            median_time_past: Default::default(),
+            chainwork: Default::default(),
         };
         BTCRelay::get_block_header_from_hash.mock_safe(move |_| MockResult::Return(Ok(rich_header)));
 
@@ -174,6 +185,8 @@ fn store_block_header_on_fork_succeeds() {
             block_height: block_height - 1,
             chain_id,
             para_height: Default::default(),
+            median_time_past: Default::default(),
+            chainwork: Default::default(),
         };
         BTCRelay::get_block_header_from_hash.mock_safe(move |_| MockResult::Return(Ok(rich_header)));
 
@@ -232,17 +245,17 @@ mod store_block_header_tests {
         }
         let chains_index = chains_index.into_iter().map(|(_, value)| value).collect::<Vec<_>>();
 
-        // for all i > 0, `ChainsIndex[i].maxHeight < ChainsIndex[0].maxHeight + STABLE_BITCOIN_CONFIRMATIONS`
+        // for all i > 0, `ChainsIndex[i].chainwork < ChainsIndex[0].chainwork + STABLE_BITCOIN_CONFIRMATIONS` blocks
+        // worth of work at the difficulty of the main chain's tip
+        let stable_lead = BTCRelay::get_stable_chainwork_lead(&mainchain).unwrap();
         for chain in chains_index.iter().skip(1) {
-            assert!(chain.max_height < mainchain.max_height + BTCRelay::get_stable_transaction_confirmations());
+            assert!(chain.chainwork < mainchain.chainwork + stable_lead);
         }
 
-        // The values in ``Chains`` MUST be such that for each ``0 < i < j``, ``ChainsIndex[Chains[i]].maxHeight >=
-        // ChainsIndex[Chains[j]].maxHeight``.
+        // The values in ``Chains`` MUST be such that for each ``0 < i < j``, ``ChainsIndex[Chains[i]].chainwork >=
+        // ChainsIndex[Chains[j]].chainwork``.
         for i in 1..chains.len() - 1 {
-            if chains_index[chains[i] as usize].max_height < chains_index[chains[i + 1] as usize].max_height {
-                assert!(chains_index[chains[i] as usize].max_height >= chains_index[chains[i + 1] as usize].max_height);
-            }
+            assert!(chains_index[chains[i] as usize].chainwork >= chains_index[chains[i + 1] as usize].chainwork);
         }
 
         // ChainsIndex[i].chainRef = i
@@ -292,7 +305,7 @@ mod store_block_header_tests {
     fn assert_is_block(height: u32, block_header: &BlockHeader) {
         Security::set_active_block_number(ext::security::active_block_number::<Test>() + 1000);
 
-        BTCRelay::ensure_no_ongoing_fork.mock_safe(|_| MockResult::Return(Ok(())));
+        BTCRelay::ensure_no_ongoing_fork.mock_safe(|| MockResult::Return(Ok(())));
         assert_ok!(BTCRelay::verify_block_header_inclusion(block_header.hash, Some(0)));
         BTCRelay::ensure_no_ongoing_fork.clear_mock();
 
@@ -344,16 +357,46 @@ mod store_block_header_tests {
         })
     }
 
+    #[test]
+    fn store_block_header_long_low_work_fork_is_not_ongoing() {
+        run_test(|| {
+            BTCRelay::verify_block_header.mock_safe(|_, _, _| MockResult::Return(Ok(())));
+
+            let genesis = sample_block_header();
+            assert_ok!(BTCRelay::initialize(3, genesis, 10));
+
+            let main_chain = sp_std::iter::successors(Some(genesis), |prev| Some(from_prev(prev.nonce + 1, prev.hash)))
+                .skip(1)
+                .take(BTCRelay::get_stable_transaction_confirmations() as usize + 1)
+                .collect::<Vec<_>>();
+            for block in main_chain.iter() {
+                store_header_and_check_invariants(block);
+            }
+
+            // a fork of minimum difficulty blocks, as on testnet, that is taller than the main chain
+            let mut prev = genesis.hash;
+            for i in 0..main_chain.len() as u32 * 3 {
+                let mut block = from_prev(1000 + i, prev);
+                block.target = U256::max_value() >> 32;
+                block.update_hash().unwrap();
+                store_header_and_check_invariants(&block);
+                prev = block.hash;
+            }
+            let fork = BTCRelay::get_block_chain_from_id(BTCRelay::get_chain_id_from_position(1).unwrap()).unwrap();
+            assert!(fork.max_height > BTCRelay::get_best_block_height());
+
+            assert_ok!(BTCRelay::ensure_no_ongoing_fork());
+            assert_ok!(BTCRelay::verify_block_header_inclusion(main_chain[0].hash, None));
+        })
+    }
+
     fn assert_best_block(block_header: &BlockHeader, height: u32) {
         assert_eq!(BTCRelay::get_best_block_height(), height);
         assert_is_block(height, &block_header);
     }
 
     fn assert_ongoing_fork() {
-        assert_err!(
-            BTCRelay::ensure_no_ongoing_fork(BTCRelay::get_best_block_height()),
-            TestError::OngoingFork
-        );
+        assert_err!(BTCRelay::ensure_no_ongoing_fork(), TestError::OngoingFork);
     }
 
     fn store_header_and_check_invariants(block: &BlockHeader) {
@@ -653,6 +696,7 @@ fn check_and_do_reorg_new_fork_is_main_chain() {
         assert_eq!(current_position, fork_position);
 
         BTCRelay::swap_main_blockchain.mock_safe(move |_| MockResult::Return(Ok((best_block_hash, fork_block_height))));
+        BTCRelay::get_chain_tip_work.mock_safe(|_| MockResult::Return(Ok(1.into())));
 
         assert_ok!(BTCRelay::reorganize_chains(&fork));
         // assert that the new main chain is set
@@ -695,6 +739,7 @@ fn check_and_do_reorg_new_fork_below_stable_transaction_confirmations() {
         assert_eq!(current_position, fork_position);
 
         BTCRelay::swap_main_blockchain.mock_safe(move |_| MockResult::Return(Ok((best_block_hash, fork_block_height))));
+        BTCRelay::get_chain_tip_work.mock_safe(|_| MockResult::Return(Ok(1.into())));
 
         assert_ok!(BTCRelay::reorganize_chains(&fork));
         // assert that the fork has not overtaken the main chain
@@ -839,6 +884,22 @@ fn test_verify_block_header_correct_retarget_decrease_succeeds() {
     })
 }
 
+#[test]
+fn test_parse_raw_block_header_below_min_version_fails() {
+    run_test(|| {
+        // the bitcoin genesis block has version 1
+        let raw_block_header = RawBlockHeader::from_hex(
+            "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c",
+        )
+        .unwrap();
+        assert!(<Test as crate::Config>::ChainParams::MIN_BLOCK_VERSION > 1);
+        assert_err!(
+            BTCRelay::parse_raw_block_header(&raw_block_header),
+            TestError::MalformedHeader
+        );
+    })
+}
+
 #[test]
 fn test_verify_block_header_missing_retarget_succeeds() {
     run_test(|| {
@@ -896,6 +957,434 @@ fn test_compute_new_target() {
     assert_eq!(new_target, curr_block_header.target);
 }
 
+#[test]
+fn test_compute_new_target_decrease() {
+    let chain_id: u32 = 0;
+    let block_height: u32 = 560448;
+    let retarget_headers = sample_retarget_interval_decrease();
+
+    let last_retarget_time = parse_block_header(&retarget_headers[0]).unwrap().timestamp as u64;
+    let prev_block_header = RichBlockHeader::<BlockNumber>::new(
+        parse_block_header(&retarget_headers[1]).unwrap(),
+        chain_id,
+        block_height - 1,
+        Default::default(),
+    );
+
+    let curr_block_header = parse_block_header(&retarget_headers[2]).unwrap();
+
+    BTCRelay::get_last_retarget_time.mock_safe(move |_, _| MockResult::Return(Ok(last_retarget_time)));
+
+    let new_target = BTCRelay::compute_new_target(&prev_block_header, block_height).unwrap();
+
+    assert_eq!(new_target, curr_block_header.target);
+}
+
+/// Chain parameters that halve the target of the previous block with every block
+struct HalvingChainParams;
+
+impl ChainParams for HalvingChainParams {
+    const DIFFICULTY_ADJUSTMENT_INTERVAL: u32 = crate::DIFFICULTY_ADJUSTMENT_INTERVAL;
+    const TARGET_SPACING: u32 = crate::TARGET_SPACING;
+    const TARGET_TIMESPAN: u32 = crate::TARGET_TIMESPAN;
+    const TARGET_TIMESPAN_DIVISOR: u32 = crate::TARGET_TIMESPAN_DIVISOR;
+    const UNROUNDED_MAX_TARGET: U256 = crate::UNROUNDED_MAX_TARGET;
+    const MIN_BLOCK_VERSION: i32 = crate::MIN_BLOCK_VERSION;
+    const MEDIAN_TIME_SPAN: usize = crate::MEDIAN_TIME_SPAN;
+    const MAX_FUTURE_BLOCK_TIME: u32 = crate::MAX_FUTURE_BLOCK_TIME;
+
+    fn retarget<C: HeaderChain>(
+        chain: &C,
+        block_header: &BlockHeader,
+        _block_height: u32,
+        _network: bitcoin::Network,
+    ) -> Result<Option<U256>, MissingHeader> {
+        let prev_block_header = chain.block_header(block_header.hash_prev_block).ok_or(MissingHeader)?;
+        Ok(Some(prev_block_header.target / 2))
+    }
+}
+
+#[test]
+fn retarget_reads_stored_headers() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let chain = StoredHeaders::<Test>(PhantomData);
+        assert_eq!(chain.chainwork(genesis.hash), Some(genesis.work()));
+
+        let block_header = generate_block_headers(1, genesis.hash, target).remove(0);
+        assert_eq!(
+            HalvingChainParams::retarget(&chain, &block_header, 1, bitcoin::Network::Mainnet),
+            Ok(Some(genesis.target / 2))
+        );
+
+        let orphan_header = generate_block_headers(1, block_header.hash, target).remove(0);
+        assert_eq!(
+            HalvingChainParams::retarget(&chain, &orphan_header, 2, bitcoin::Network::Mainnet),
+            Err(MissingHeader)
+        );
+    })
+}
+
+/// Chain parameters of Litecoin, whose proof of work is the scrypt hash of the block header
+struct LitecoinChainParams;
+
+impl ChainParams for LitecoinChainParams {
+    const DIFFICULTY_ADJUSTMENT_INTERVAL: u32 = 2016;
+    const TARGET_SPACING: u32 = 150;
+    const TARGET_TIMESPAN: u32 = 2016 * 150;
+    const TARGET_TIMESPAN_DIVISOR: u32 = 4;
+    const UNROUNDED_MAX_TARGET: U256 = U256([
+        <u64>::max_value(),
+        <u64>::max_value(),
+        <u64>::max_value(),
+        0x00000fffffffffff,
+    ]);
+    const MIN_BLOCK_VERSION: i32 = 1;
+    const MEDIAN_TIME_SPAN: usize = crate::MEDIAN_TIME_SPAN;
+    const MAX_FUTURE_BLOCK_TIME: u32 = crate::MAX_FUTURE_BLOCK_TIME;
+
+    fn pow_hash(block_header: &BlockHeader) -> H256Le {
+        let raw_header = block_header.try_format().unwrap();
+        let mut hash = [0u8; 32];
+        scrypt::scrypt(
+            &raw_header,
+            &raw_header,
+            &scrypt::Params::new(10, 1, 1).unwrap(),
+            &mut hash,
+        )
+        .unwrap();
+        H256Le::from_bytes_le(&hash)
+    }
+}
+
+#[test]
+fn pow_hash_uses_chain_params() {
+    // Litecoin genesis block
+    let block_header = parse_block_header_lenient(
+        &RawBlockHeader::from_hex(
+            "01000000".to_owned()
+                + "0000000000000000000000000000000000000000000000000000000000000000"
+                + "d9ced4ed1130f7b7faad9be25323ffafa33232a17c3edf6cfd97bee6bafbdd97"
+                + "b9aa8e4ef0ff0f1ecd513f7c",
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        block_header.hash,
+        H256Le::from_hex_be("12a765e31ffd4059bada1e25190f6e98c99d9714d334efa41a195a7e7e04bfe2")
+    );
+
+    // the scrypt hash satisfies the target while the block hash does not
+    assert_eq!(
+        LitecoinChainParams::pow_hash(&block_header),
+        H256Le::from_hex_be("0000050c34a64b415b6b15b37f2216634b5b1669cb9a2e38d76f7213b0671e00")
+    );
+    assert!(LitecoinChainParams::pow_hash(&block_header).as_u256() < block_header.target);
+    assert!(BitcoinChainParams::pow_hash(&block_header).as_u256() >= block_header.target);
+}
+
+/// Number of blocks whose work and time span determine the target of the next Bitcoin Cash block
+const BITCOIN_CASH_ADJUSTMENT_WINDOW: u32 = 144;
+
+/// Chain parameters of Bitcoin Cash, which adjusts the difficulty with every block (cw-144)
+struct BitcoinCashChainParams;
+
+impl BitcoinCashChainParams {
+    /// Returns the header with the median timestamp of the given header and its two predecessors
+    fn suitable_block_header<C: HeaderChain>(
+        chain: &C,
+        block_header: BlockHeader,
+    ) -> Result<BlockHeader, MissingHeader> {
+        let prev_block_header = chain.block_header(block_header.hash_prev_block).ok_or(MissingHeader)?;
+        let prev_prev_block_header = chain
+            .block_header(prev_block_header.hash_prev_block)
+            .ok_or(MissingHeader)?;
+        let mut block_headers = [prev_prev_block_header, prev_block_header, block_header];
+        block_headers.sort_by_key(|block_header| block_header.timestamp);
+        Ok(block_headers[1])
+    }
+}
+
+impl ChainParams for BitcoinCashChainParams {
+    const DIFFICULTY_ADJUSTMENT_INTERVAL: u32 = crate::DIFFICULTY_ADJUSTMENT_INTERVAL;
+    const TARGET_SPACING: u32 = crate::TARGET_SPACING;
+    const TARGET_TIMESPAN: u32 = crate::TARGET_TIMESPAN;
+    const TARGET_TIMESPAN_DIVISOR: u32 = crate::TARGET_TIMESPAN_DIVISOR;
+    const UNROUNDED_MAX_TARGET: U256 = U256([
+        <u64>::max_value(),
+        <u64>::max_value(),
+        <u64>::max_value(),
+        0x0fffffffffffffff,
+    ]);
+    const MIN_BLOCK_VERSION: i32 = crate::MIN_BLOCK_VERSION;
+    const MEDIAN_TIME_SPAN: usize = crate::MEDIAN_TIME_SPAN;
+    const MAX_FUTURE_BLOCK_TIME: u32 = crate::MAX_FUTURE_BLOCK_TIME;
+
+    fn retarget<C: HeaderChain>(
+        chain: &C,
+        block_header: &BlockHeader,
+        _block_height: u32,
+        _network: bitcoin::Network,
+    ) -> Result<Option<U256>, MissingHeader> {
+        let prev_block_header = chain.block_header(block_header.hash_prev_block).ok_or(MissingHeader)?;
+        let mut first_block_header = prev_block_header;
+        for _ in 0..BITCOIN_CASH_ADJUSTMENT_WINDOW {
+            first_block_header = chain
+                .block_header(first_block_header.hash_prev_block)
+                .ok_or(MissingHeader)?;
+        }
+        let last = Self::suitable_block_header(chain, prev_block_header)?;
+        let first = Self::suitable_block_header(chain, first_block_header)?;
+
+        let work =
+            chain.chainwork(last.hash).ok_or(MissingHeader)? - chain.chainwork(first.hash).ok_or(MissingHeader)?;
+        let timespan = last
+            .timestamp
+            .saturating_sub(first.timestamp)
+            .max(BITCOIN_CASH_ADJUSTMENT_WINDOW * Self::TARGET_SPACING / 2)
+            .min(BITCOIN_CASH_ADJUSTMENT_WINDOW * Self::TARGET_SPACING * 2);
+        let work = work * Self::TARGET_SPACING / timespan;
+
+        // the target whose work is `work`, i.e. (2^256 - work) / work
+        let target = (!work + U256::one()) / work;
+        Ok(Some(target.min(Self::UNROUNDED_MAX_TARGET)))
+    }
+}
+
+/// Stored block headers along with their cumulative chainwork
+struct MemoryHeaderChain(std::collections::BTreeMap<H256Le, (BlockHeader, U256)>);
+
+impl HeaderChain for MemoryHeaderChain {
+    fn block_header(&self, block_hash: H256Le) -> Option<BlockHeader> {
+        self.0.get(&block_hash).map(|(block_header, _)| *block_header)
+    }
+
+    fn chainwork(&self, block_hash: H256Le) -> Option<U256> {
+        self.0.get(&block_hash).map(|(_, chainwork)| *chainwork)
+    }
+}
+
+/// Generates a chain of block headers mined at the given target whose timestamps are the given
+/// number of seconds apart
+fn generate_header_chain(count: u32, target: U256, spacing: u32) -> (MemoryHeaderChain, BlockHeader) {
+    let miner = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+    let mut chain = std::collections::BTreeMap::new();
+    let mut prev_hash = H256Le::zero();
+    let mut chainwork = U256::zero();
+    let mut block_header = BlockHeader::default();
+    for i in 0..count {
+        block_header = BlockBuilder::new()
+            .with_coinbase(&miner, 50, i)
+            .with_timestamp(1588814835 + i * spacing)
+            .with_previous_hash(prev_hash)
+            .mine(target)
+            .unwrap()
+            .header;
+        chainwork += block_header.work();
+        chain.insert(block_header.hash, (block_header, chainwork));
+        prev_hash = block_header.hash;
+    }
+    (MemoryHeaderChain(chain), block_header)
+}
+
+#[test]
+fn retarget_adjusts_target_with_every_block() {
+    let target = U256::from(2).pow(250.into());
+    let expected_target = |work: U256| (!work + U256::one()) / work;
+    let next_header = |prev_block_header: BlockHeader| BlockHeader {
+        hash_prev_block: prev_block_header.hash,
+        ..Default::default()
+    };
+
+    // blocks at the target spacing keep the work per block
+    let (chain, tip) = generate_header_chain(BITCOIN_CASH_ADJUSTMENT_WINDOW + 3, target, crate::TARGET_SPACING);
+    let work = tip.work();
+    assert_eq!(
+        BitcoinCashChainParams::retarget(&chain, &next_header(tip), 0, bitcoin::Network::Mainnet),
+        Ok(Some(expected_target(work)))
+    );
+
+    // blocks at twice the target spacing halve the work per block
+    let (chain, tip) = generate_header_chain(BITCOIN_CASH_ADJUSTMENT_WINDOW + 3, target, 2 * crate::TARGET_SPACING);
+    let doubled_target = BitcoinCashChainParams::retarget(&chain, &next_header(tip), 0, bitcoin::Network::Mainnet)
+        .unwrap()
+        .unwrap();
+    assert_eq!(doubled_target, expected_target(work / 2));
+    assert!(doubled_target > target);
+
+    // the window must be stored
+    let (chain, tip) = generate_header_chain(BITCOIN_CASH_ADJUSTMENT_WINDOW + 2, target, crate::TARGET_SPACING);
+    assert_eq!(
+        BitcoinCashChainParams::retarget(&chain, &next_header(tip), 0, bitcoin::Network::Mainnet),
+        Err(MissingHeader)
+    );
+}
+
+fn sample_rich_header_with_target(
+    target: U256,
+    timestamp: u32,
+    block_height: u32,
+    hash_prev_block: H256Le,
+) -> RichBlockHeader<BlockNumber> {
+    RichBlockHeader::<BlockNumber>::new(
+        BlockHeader {
+            target,
+            timestamp,
+            hash: H256Le::from(block_height),
+            hash_prev_block,
+            ..Default::default()
+        },
+        0,
+        block_height,
+        Default::default(),
+    )
+}
+
+#[test]
+fn test_verify_block_header_testnet_min_difficulty_succeeds() {
+    run_test(|| {
+        BitcoinNetwork::<Test>::set(bitcoin::Network::Testnet);
+        BTCRelay::block_header_exists.mock_safe(|_| MockResult::Return(false));
+
+        let min_difficulty_target = U256::from(0xffff) << 208;
+        let prev_block_header =
+            sample_rich_header_with_target(min_difficulty_target >> 4, 1_000_000, 100, H256Le::zero());
+
+        // more than 20 minutes after the previous block
+        let block_header = sample_rich_header_with_target(min_difficulty_target, 1_001_201, 101, H256Le::from(100));
+        assert_ok!(BTCRelay::verify_block_header(
+            &block_header.block_header,
+            101,
+            prev_block_header
+        ));
+
+        // exactly 20 minutes after the previous block
+        let block_header = sample_rich_header_with_target(min_difficulty_target, 1_001_200, 101, H256Le::from(100));
+        assert_err!(
+            BTCRelay::verify_block_header(&block_header.block_header, 101, prev_block_header),
+            TestError::DiffTargetHeader
+        );
+    })
+}
+
+#[test]
+fn test_verify_block_header_testnet_skips_min_difficulty_blocks() {
+    run_test(|| {
+        BitcoinNetwork::<Test>::set(bitcoin::Network::Testnet);
+        BTCRelay::block_header_exists.mock_safe(|_| MockResult::Return(false));
+
+        let min_difficulty_target = U256::from(0xffff) << 208;
+        let target = min_difficulty_target >> 4;
+        // block 100 was mined at the regular target, block 101 at minimum difficulty
+        let regular_block_header = sample_rich_header_with_target(target, 1_000_000, 100, H256Le::zero());
+        let min_difficulty_block_header =
+            sample_rich_header_with_target(min_difficulty_target, 1_001_500, 101, H256Le::from(100));
+        BlockHeaders::<Test>::insert(H256Le::from(100), regular_block_header);
+
+        let block_header = sample_rich_header_with_target(target, 1_001_600, 102, H256Le::from(101));
+        assert_ok!(BTCRelay::verify_block_header(
+            &block_header.block_header,
+            102,
+            min_difficulty_block_header
+        ));
+
+        let block_header = sample_rich_header_with_target(min_difficulty_target, 1_001_600, 102, H256Le::from(101));
+        assert_err!(
+            BTCRelay::verify_block_header(&block_header.block_header, 102, min_difficulty_block_header),
+            TestError::DiffTargetHeader
+        );
+    })
+}
+
+#[test]
+fn test_verify_block_header_testnet_stops_at_start_height() {
+    run_test(|| {
+        BitcoinNetwork::<Test>::set(bitcoin::Network::Testnet);
+        BTCRelay::block_header_exists.mock_safe(|_| MockResult::Return(false));
+
+        // the relay was started at a block mined at minimum difficulty whose parent is not stored
+        let min_difficulty_target = U256::from(0xffff) << 208;
+        let start_block_header =
+            sample_rich_header_with_target(min_difficulty_target, 1_000_000, 101, H256Le::from(100));
+        crate::StartBlockHeight::<Test>::set(101);
+
+        let block_header = sample_rich_header_with_target(min_difficulty_target, 1_000_100, 102, H256Le::from(101));
+        assert_ok!(BTCRelay::verify_block_header(
+            &block_header.block_header,
+            102,
+            start_block_header
+        ));
+    })
+}
+
+#[test]
+fn test_store_rich_header_records_testnet_period_target() {
+    run_test(|| {
+        BitcoinNetwork::<Test>::set(bitcoin::Network::Testnet);
+
+        let min_difficulty_target = U256::from(0xffff) << 208;
+        let target = min_difficulty_target >> 4;
+        let regular_block_header = sample_rich_header_with_target(target, 1_000_000, 100, H256Le::zero());
+        BlockHeaders::<Test>::insert(H256Le::from(100), regular_block_header);
+
+        let min_difficulty_block_header =
+            sample_rich_header_with_target(min_difficulty_target, 1_001_500, 101, H256Le::from(100));
+        assert_ok!(BTCRelay::store_rich_header(
+            min_difficulty_block_header.block_header,
+            101,
+            crate::MAIN_CHAIN_ID
+        ));
+        assert_eq!(TestnetPeriodTargets::<Test>::get(H256Le::from(101)), Some(target));
+
+        // the target is found without reading the regular block
+        BlockHeaders::<Test>::remove(H256Le::from(100));
+        let min_difficulty_block_header = BTCRelay::get_block_header_from_hash(H256Le::from(101)).unwrap();
+        let block_header = sample_rich_header_with_target(target, 1_001_600, 102, H256Le::from(101));
+        assert_ok!(BTCRelay::verify_block_header(
+            &block_header.block_header,
+            102,
+            min_difficulty_block_header
+        ));
+    })
+}
+
+#[test]
+fn test_verify_block_header_mainnet_rejects_min_difficulty_blocks() {
+    run_test(|| {
+        BTCRelay::block_header_exists.mock_safe(|_| MockResult::Return(false));
+
+        let min_difficulty_target = U256::from(0xffff) << 208;
+        let prev_block_header =
+            sample_rich_header_with_target(min_difficulty_target >> 4, 1_000_000, 100, H256Le::zero());
+
+        let block_header = sample_rich_header_with_target(min_difficulty_target, 1_001_201, 101, H256Le::from(100));
+        assert_err!(
+            BTCRelay::verify_block_header(&block_header.block_header, 101, prev_block_header),
+            TestError::DiffTargetHeader
+        );
+    })
+}
+
+#[test]
+fn test_verify_block_header_regtest_no_retarget_succeeds() {
+    run_test(|| {
+        BitcoinNetwork::<Test>::set(bitcoin::Network::Regtest);
+        BTCRelay::block_header_exists.mock_safe(|_| MockResult::Return(false));
+
+        let target = U256::from(0x7fffff) << 232;
+        let prev_block_header = sample_rich_header_with_target(target, 1_000_000, 2015, H256Le::zero());
+
+        let block_header = sample_rich_header_with_target(target, 1_000_001, 2016, H256Le::from(2015));
+        assert_ok!(BTCRelay::verify_block_header(
+            &block_header.block_header,
+            2016,
+            prev_block_header
+        ));
+    })
+}
+
 #[test]
 fn test_verify_block_header_duplicate_fails() {
     run_test(|| {
@@ -1204,72 +1693,357 @@ fn test_verify_and_validate_transaction_succeeds() {
 }
 
 #[test]
-fn test_verify_transaction_inclusion_succeeds() {
+fn test_dry_run_op_return_payment_succeeds() {
     run_test(|| {
-        let chain_id = 0;
-        let fork_ref = 1;
-        let start = 10;
-        let main_chain_height = 300;
-        let fork_chain_height = 280;
-        // Random init since we mock this
-        let raw_merkle_proof = vec![0u8; 100];
-        let confirmations = None;
-        let rich_block_header = sample_rich_tx_block_header(chain_id, main_chain_height);
+        let recipient_btc_address =
+            BtcAddress::P2SH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        let op_return_id = H256::from_slice(
+            &hex::decode("e5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned()).unwrap(),
+        );
+        let outputs = vec![sample_valid_payment_output(), sample_valid_data_output()];
+        BTCRelay::parse_transaction.mock_safe(move |_| MockResult::Return(Ok(sample_transaction_parsed(&outputs))));
+        BTCRelay::parse_merkle_proof.mock_safe(|_| MockResult::Return(Ok(sample_merkle_proof())));
+        BTCRelay::_verify_transaction_inclusion.mock_safe(|_, _, _| MockResult::Return(Ok(())));
 
-        let proof = sample_merkle_proof();
-        let proof_result = sample_valid_proof_result();
+        let verification =
+            BTCRelay::dry_run_op_return_payment(&[], &[], recipient_btc_address, 2500200000u128, op_return_id);
+        assert_eq!(
+            verification,
+            PaymentVerification {
+                amount_found: Some(2500200000u128),
+                expected_amount: 2500200000u128,
+                refund_address: None,
+                error: None,
+            }
+        );
 
-        let main = get_empty_block_chain_from_chain_id_and_height(chain_id, start, main_chain_height);
+        // the payment is still reported if a check fails
+        let verification = BTCRelay::dry_run_op_return_payment(&[], &[], recipient_btc_address, 100u128, op_return_id);
+        assert_eq!(verification.amount_found, Some(2500200000u128));
+        assert_eq!(verification.error, Some(TestError::InvalidPaymentAmount.into()));
+
+        BTCRelay::_verify_transaction_inclusion
+            .mock_safe(|_, _, _| MockResult::Return(Err(TestError::InvalidMerkleProof.into())));
+        let verification =
+            BTCRelay::dry_run_op_return_payment(&[], &[], recipient_btc_address, 2500200000u128, op_return_id);
+        assert_eq!(verification.amount_found, Some(2500200000u128));
+        assert_eq!(verification.error, Some(TestError::InvalidMerkleProof.into()));
+    });
+}
 
-        let fork = get_empty_block_chain_from_chain_id_and_height(fork_ref, start, fork_chain_height);
+#[test]
+fn test_dry_run_op_return_part_payment_succeeds() {
+    run_test(|| {
+        let recipient_btc_address =
+            BtcAddress::P2SH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        let op_return_id = H256::from_slice(
+            &hex::decode("e5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned()).unwrap(),
+        );
+        let outputs = vec![sample_valid_payment_output(), sample_valid_data_output()];
+        BTCRelay::parse_transaction.mock_safe(move |_| MockResult::Return(Ok(sample_transaction_parsed(&outputs))));
+        BTCRelay::parse_merkle_proof.mock_safe(|_| MockResult::Return(Ok(sample_merkle_proof())));
+        BTCRelay::_verify_transaction_inclusion.mock_safe(|_, _, confirmations| {
+            // the confirmations are those of the whole request
+            assert_eq!(confirmations, Some(BTCRelay::get_required_confirmations(5000000000)));
+            MockResult::Return(Ok(()))
+        });
 
-        BTCRelay::get_chain_id_from_position.mock_safe(move |_| MockResult::Return(Ok(fork_ref)));
-        BTCRelay::get_block_chain_from_id.mock_safe(move |id| {
-            if id == chain_id {
-                MockResult::Return(Ok(main.clone()))
-            } else {
-                MockResult::Return(Ok(fork.clone()))
+        // any amount is accepted as a part payment
+        let verification = BTCRelay::dry_run_op_return_part_payment(
+            &[],
+            &[],
+            recipient_btc_address,
+            3000000000u128,
+            5000000000u128,
+            op_return_id,
+        );
+        assert_eq!(
+            verification,
+            PaymentVerification {
+                amount_found: Some(2500200000u128),
+                expected_amount: 3000000000u128,
+                refund_address: None,
+                error: None,
             }
-        });
+        );
 
-        BTCRelay::get_best_block_height.mock_safe(move || MockResult::Return(main_chain_height));
+        let verification = BTCRelay::dry_run_op_return_part_payment(
+            &[],
+            &[],
+            recipient_btc_address,
+            3000000000u128,
+            5000000000u128,
+            H256::zero(),
+        );
+        assert_eq!(verification.amount_found, Some(2500200000u128));
+        assert_eq!(verification.error, Some(TestError::InvalidPayment.into()));
+    });
+}
 
-        BTCRelay::parse_merkle_proof.mock_safe(move |_| MockResult::Return(Ok(proof.clone())));
-        BTCRelay::verify_merkle_proof.mock_safe(move |_| MockResult::Return(Ok(proof_result)));
+#[test]
+fn test_dry_run_issue_payment_succeeds() {
+    run_test(|| {
+        let recipient_btc_address =
+            BtcAddress::P2SH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        let outputs = vec![sample_valid_payment_output()];
+        let transaction = sample_transaction_parsed(&outputs);
+        let sender_btc_address = transaction.inputs[0].extract_address().unwrap();
+        BTCRelay::parse_transaction.mock_safe(move |_| MockResult::Return(Ok(transaction.clone())));
+        BTCRelay::parse_merkle_proof.mock_safe(|_| MockResult::Return(Ok(sample_merkle_proof())));
+        BTCRelay::_verify_transaction_inclusion.mock_safe(|_, _, _| MockResult::Return(Ok(())));
 
-        BTCRelay::get_block_header_from_hash.mock_safe(move |_| MockResult::Return(Ok(rich_block_header)));
+        let verification = BTCRelay::dry_run_issue_payment(&[], &[], recipient_btc_address, 3000000000u128);
+        assert_eq!(
+            verification,
+            PaymentVerification {
+                amount_found: Some(2500200000u128),
+                expected_amount: 3000000000u128,
+                refund_address: Some(sender_btc_address),
+                error: None,
+            }
+        );
 
-        BTCRelay::check_bitcoin_confirmations.mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        let verification = BTCRelay::dry_run_issue_payment(&[], &[], BtcAddress::P2SH(H160::zero()), 3000000000u128);
+        assert_eq!(verification.amount_found, None);
+        assert_eq!(verification.error, Some(TestError::MalformedTransaction.into()));
+    });
+}
 
-        BTCRelay::check_parachain_confirmations.mock_safe(|_| MockResult::Return(Ok(())));
+#[test]
+fn test_get_and_verify_issue_payment_sums_outputs() {
+    run_test(|| {
+        let recipient_btc_address =
+            BtcAddress::P2SH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        let outputs = vec![sample_valid_payment_output(), sample_valid_payment_output()];
+        let transaction = sample_transaction_parsed(&outputs);
+        BTCRelay::_verify_transaction_inclusion.mock_safe(|_, _, _| MockResult::Return(Ok(())));
 
-        assert_ok!(BTCRelay::verify_transaction_inclusion(
-            Origin::signed(3),
-            proof_result.transaction_hash,
-            raw_merkle_proof,
-            confirmations
-        ));
+        let (_, amount): (BtcAddress, u128) =
+            BTCRelay::get_and_verify_issue_payment(sample_merkle_proof(), transaction, recipient_btc_address).unwrap();
+        assert_eq!(amount, 2 * 2500200000);
     });
 }
 
 #[test]
-fn test_verify_transaction_inclusion_empty_fork_succeeds() {
+fn test_get_and_verify_issue_payments_succeeds() {
     run_test(|| {
-        let chain_id = 0;
-        let start = 10;
-        let main_chain_height = 300;
-        // Random init since we mock this
-        let raw_merkle_proof = vec![0u8; 100];
-        let confirmations = None;
-        let rich_block_header = sample_rich_tx_block_header(chain_id, main_chain_height);
-
-        let proof = sample_merkle_proof();
-        let proof_result = sample_valid_proof_result();
+        let recipient_btc_address =
+            BtcAddress::P2SH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        let transaction = sample_transaction_parsed(&vec![sample_valid_payment_output()]);
+        let sender_btc_address = transaction.inputs[0].extract_address().unwrap();
+        BTCRelay::_verify_transaction_inclusion.mock_safe(|_, _, _| MockResult::Return(Ok(())));
 
-        let main = get_empty_block_chain_from_chain_id_and_height(chain_id, start, main_chain_height);
+        let payments = vec![
+            (sample_merkle_proof(), transaction.clone()),
+            (sample_merkle_proof(), transaction),
+        ];
+        let (refund_address, amount): (BtcAddress, u128) =
+            BTCRelay::get_and_verify_issue_payments(payments, recipient_btc_address).unwrap();
+        assert_eq!(refund_address, sender_btc_address);
+        assert_eq!(amount, 2 * 2500200000);
+    });
+}
 
-        BTCRelay::get_block_chain_from_id.mock_safe(move |id| {
-            if id == chain_id {
+#[test]
+fn test_get_and_verify_issue_payments_empty_fails() {
+    run_test(|| {
+        assert_err!(
+            BTCRelay::get_and_verify_issue_payments::<u128>(vec![], BtcAddress::P2SH(H160::zero())),
+            TestError::EmptyPayments
+        );
+    });
+}
+
+#[test]
+fn test_get_and_verify_op_return_payment_succeeds() {
+    run_test(|| {
+        let recipient_btc_address =
+            BtcAddress::P2SH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        let op_return_id = H256::from_slice(
+            &hex::decode("e5c17d15b8b1fa2811b7e6da66ffa5e1aaa05922c69068bf90cd585b95bb4675".to_owned()).unwrap(),
+        );
+        let transaction = sample_transaction_parsed(&vec![sample_valid_payment_output(), sample_valid_data_output()]);
+        assert_ok!(BTCRelay::set_confirmation_tiers(
+            Origin::root(),
+            vec![(3_000_000_000, 1)]
+        ));
+        // the payment falls into the tier, but the request amount does not
+        BTCRelay::_verify_transaction_inclusion.mock_safe(|_, _, confirmations| {
+            assert_eq!(confirmations, Some(BITCOIN_CONFIRMATIONS));
+            MockResult::Return(Ok(()))
+        });
+
+        assert_eq!(
+            BTCRelay::get_and_verify_op_return_payment::<u128>(
+                sample_merkle_proof(),
+                transaction.clone(),
+                recipient_btc_address,
+                5_000_000_000,
+                op_return_id
+            ),
+            Ok(2500200000)
+        );
+        assert_err!(
+            BTCRelay::get_and_verify_op_return_payment::<u128>(
+                sample_merkle_proof(),
+                transaction,
+                recipient_btc_address,
+                5_000_000_000,
+                H256::zero()
+            ),
+            TestError::InvalidPayment
+        );
+    });
+}
+
+#[test]
+fn test_get_required_confirmations_uses_tiers() {
+    run_test(|| {
+        assert_eq!(BTCRelay::get_required_confirmations(1), BITCOIN_CONFIRMATIONS);
+
+        assert_ok!(BTCRelay::set_confirmation_tiers(
+            Origin::root(),
+            vec![(100_000, 1), (1_000_000, 3), (10_000_000, BITCOIN_CONFIRMATIONS + 1)]
+        ));
+        assert_eq!(BTCRelay::get_required_confirmations(50_000), 1);
+        assert_eq!(BTCRelay::get_required_confirmations(100_000), 1);
+        assert_eq!(BTCRelay::get_required_confirmations(100_001), 3);
+        // tiers never require more than the global confirmations
+        assert_eq!(BTCRelay::get_required_confirmations(5_000_000), BITCOIN_CONFIRMATIONS);
+        assert_eq!(BTCRelay::get_required_confirmations(20_000_000), BITCOIN_CONFIRMATIONS);
+    });
+}
+
+#[test]
+fn test_set_confirmation_tiers_fails() {
+    run_test(|| {
+        assert_err!(
+            BTCRelay::set_confirmation_tiers(Origin::signed(3), vec![(100_000, 1)]),
+            DispatchError::BadOrigin
+        );
+        assert_err!(
+            BTCRelay::set_confirmation_tiers(Origin::root(), vec![(100_000, 1), (100_000, 2)]),
+            TestError::InvalidConfirmationTiers
+        );
+        assert_err!(
+            BTCRelay::set_confirmation_tiers(Origin::root(), vec![(100_000, 2), (1_000_000, 1)]),
+            TestError::InvalidConfirmationTiers
+        );
+        let tiers = (1..=crate::MAX_CONFIRMATION_TIERS as i64 + 1)
+            .map(|amount| (amount * 100_000, 1))
+            .collect();
+        assert_err!(
+            BTCRelay::set_confirmation_tiers(Origin::root(), tiers),
+            TestError::TooManyConfirmationTiers
+        );
+        assert_eq!(BTCRelay::confirmation_tiers(), vec![]);
+    });
+}
+
+#[test]
+fn test_get_and_verify_issue_payments_requires_confirmations_of_total_amount() {
+    run_test(|| {
+        let recipient_btc_address =
+            BtcAddress::P2SH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        let transaction = sample_transaction_parsed(&vec![sample_valid_payment_output()]);
+        assert_ok!(BTCRelay::set_confirmation_tiers(
+            Origin::root(),
+            vec![(3_000_000_000, 1), (6_000_000_000, 2)]
+        ));
+
+        BTCRelay::_verify_transaction_inclusion.mock_safe(|_, _, confirmations| {
+            assert_eq!(confirmations, Some(1));
+            MockResult::Return(Ok(()))
+        });
+        assert_ok!(BTCRelay::get_and_verify_issue_payment::<u128>(
+            sample_merkle_proof(),
+            transaction.clone(),
+            recipient_btc_address
+        ));
+
+        // each payment is below the first tier, but their sum is not
+        BTCRelay::_verify_transaction_inclusion.mock_safe(|_, _, confirmations| {
+            assert_eq!(confirmations, Some(2));
+            MockResult::Return(Ok(()))
+        });
+        let payments = vec![
+            (sample_merkle_proof(), transaction.clone()),
+            (sample_merkle_proof(), transaction),
+        ];
+        assert_ok!(BTCRelay::get_and_verify_issue_payments::<u128>(
+            payments,
+            recipient_btc_address
+        ));
+    });
+}
+
+#[test]
+fn test_verify_transaction_inclusion_succeeds() {
+    run_test(|| {
+        let chain_id = 0;
+        let fork_ref = 1;
+        let start = 10;
+        let main_chain_height = 300;
+        let fork_chain_height = 280;
+        // Random init since we mock this
+        let raw_merkle_proof = vec![0u8; 100];
+        let confirmations = None;
+        let rich_block_header = sample_rich_tx_block_header(chain_id, main_chain_height);
+
+        let proof = sample_merkle_proof();
+        let proof_result = sample_valid_proof_result();
+
+        let main = get_empty_block_chain_from_chain_id_and_height(chain_id, start, main_chain_height);
+
+        let fork = get_empty_block_chain_from_chain_id_and_height(fork_ref, start, fork_chain_height);
+
+        BTCRelay::get_chain_id_from_position.mock_safe(move |_| MockResult::Return(Ok(fork_ref)));
+        BTCRelay::get_block_chain_from_id.mock_safe(move |id| {
+            if id == chain_id {
+                MockResult::Return(Ok(main.clone()))
+            } else {
+                MockResult::Return(Ok(fork.clone()))
+            }
+        });
+
+        BTCRelay::get_best_block_height.mock_safe(move || MockResult::Return(main_chain_height));
+
+        BTCRelay::parse_merkle_proof.mock_safe(move |_| MockResult::Return(Ok(proof.clone())));
+        BTCRelay::verify_merkle_proof.mock_safe(move |_| MockResult::Return(Ok(proof_result)));
+
+        BTCRelay::get_block_header_from_hash.mock_safe(move |_| MockResult::Return(Ok(rich_block_header)));
+
+        BTCRelay::check_bitcoin_confirmations.mock_safe(|_, _, _| MockResult::Return(Ok(())));
+
+        BTCRelay::check_parachain_confirmations.mock_safe(|_| MockResult::Return(Ok(())));
+
+        assert_ok!(BTCRelay::verify_transaction_inclusion(
+            Origin::signed(3),
+            proof_result.transaction_hash,
+            raw_merkle_proof,
+            confirmations
+        ));
+    });
+}
+
+#[test]
+fn test_verify_transaction_inclusion_empty_fork_succeeds() {
+    run_test(|| {
+        let chain_id = 0;
+        let start = 10;
+        let main_chain_height = 300;
+        // Random init since we mock this
+        let raw_merkle_proof = vec![0u8; 100];
+        let confirmations = None;
+        let rich_block_header = sample_rich_tx_block_header(chain_id, main_chain_height);
+
+        let proof = sample_merkle_proof();
+        let proof_result = sample_valid_proof_result();
+
+        let main = get_empty_block_chain_from_chain_id_and_height(chain_id, start, main_chain_height);
+
+        BTCRelay::get_block_chain_from_id.mock_safe(move |id| {
+            if id == chain_id {
                 MockResult::Return(Ok(main.clone()))
             } else {
                 MockResult::Return(Err(TestError::InvalidChainID.into()))
@@ -1410,6 +2184,7 @@ fn test_verify_transaction_inclusion_fails_with_ongoing_fork() {
     run_test(|| {
         BTCRelay::get_chain_id_from_position.mock_safe(|_| MockResult::Return(Ok(1)));
         BTCRelay::get_block_chain_from_id.mock_safe(|_| MockResult::Return(Ok(BlockChain::default())));
+        BTCRelay::get_chain_tip_work.mock_safe(|_| MockResult::Return(Ok(U256::one())));
         BTCRelay::parse_merkle_proof.mock_safe(|_| MockResult::Return(Ok(sample_merkle_proof())));
         BTCRelay::verify_merkle_proof.mock_safe(|_| MockResult::Return(Ok(sample_valid_proof_result())));
 
@@ -1559,12 +2334,17 @@ fn store_generated_block_headers() {
     let get_header = |block: &Block| RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
 
     run_test(|| {
-        let mut last_block = BlockBuilder::new().with_coinbase(&miner, 50, 0).mine(target).unwrap();
+        let mut last_block = BlockBuilder::new()
+            .with_coinbase(&miner, 50, 0)
+            .with_timestamp(1588814835)
+            .mine(target)
+            .unwrap();
         let last_block_header = BTCRelay::parse_raw_block_header(&get_header(&last_block)).unwrap();
         assert_ok!(BTCRelay::initialize(3, last_block_header, 0));
         for i in 1..20 {
             last_block = BlockBuilder::new()
                 .with_coinbase(&miner, 50, i)
+                .with_timestamp(1588814835 + i)
                 .with_previous_hash(last_block.header.hash)
                 .mine(target)
                 .unwrap();
@@ -1578,6 +2358,814 @@ fn store_generated_block_headers() {
     })
 }
 
+fn generate_block_headers(count: u32, prev_hash: H256Le, target: U256) -> Vec<BlockHeader> {
+    let miner = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+    let mut prev_hash = prev_hash;
+    (1..=count)
+        .map(|i| {
+            let block = BlockBuilder::new()
+                .with_coinbase(&miner, 50, i)
+                .with_timestamp(1588814835 + i)
+                .with_previous_hash(prev_hash)
+                .mine(target)
+                .unwrap();
+            prev_hash = block.header.hash;
+            block.header
+        })
+        .collect()
+}
+
+fn initialize_generated_genesis(target: U256) -> BlockHeader {
+    let miner = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+    let genesis = BlockBuilder::new()
+        .with_coinbase(&miner, 50, 0)
+        .with_timestamp(1588814835)
+        .mine(target)
+        .unwrap();
+    assert_ok!(BTCRelay::initialize(3, genesis.header, 0));
+    genesis.header
+}
+
+#[test]
+fn store_block_headers_succeeds() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let block_headers = generate_block_headers(10, genesis.hash, target);
+        let last_hash = block_headers.last().unwrap().hash;
+
+        assert_ok!(BTCRelay::store_block_headers(&3, block_headers));
+
+        assert_eq!(BTCRelay::get_best_block_height(), 10);
+        assert_eq!(BTCRelay::get_best_block(), last_hash);
+    })
+}
+
+#[test]
+fn store_block_headers_empty_fails() {
+    run_test(|| {
+        assert_err!(BTCRelay::store_block_headers(&3, vec![]), TestError::EmptyBlockHeaders);
+    })
+}
+
+#[test]
+fn store_block_headers_non_contiguous_fails() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let mut block_headers = generate_block_headers(4, genesis.hash, target);
+        block_headers.swap(1, 2);
+
+        assert_err!(
+            BTCRelay::store_block_headers(&3, block_headers),
+            TestError::NonContiguousBlockHeaders
+        );
+        assert_eq!(BTCRelay::get_best_block_height(), 0);
+    })
+}
+
+#[test]
+fn store_block_headers_is_atomic() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let block_headers = generate_block_headers(5, genesis.hash, target);
+
+        // reject the fourth header after the first three have been stored
+        let invalid_hash = block_headers[3].hash;
+        BTCRelay::verify_block_header.mock_safe(move |block_header, _, _| {
+            if block_header.hash == invalid_hash {
+                MockResult::Return(Err(TestError::LowDiff.into()))
+            } else {
+                MockResult::Continue
+            }
+        });
+
+        assert_err!(
+            BTCRelay::store_block_headers(&3, block_headers.clone()),
+            TestError::LowDiff
+        );
+
+        assert_eq!(BTCRelay::get_best_block_height(), 0);
+        assert_eq!(BTCRelay::get_best_block(), genesis.hash);
+        assert_err!(
+            BTCRelay::get_block_header_from_hash(block_headers[0].hash),
+            TestError::BlockNotFound
+        );
+    })
+}
+
+fn generate_block_header_with_timestamp(prev_hash: H256Le, timestamp: u32, target: U256) -> BlockHeader {
+    let miner = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+    BlockBuilder::new()
+        .with_coinbase(&miner, 50, timestamp)
+        .with_timestamp(timestamp)
+        .with_previous_hash(prev_hash)
+        .mine(target)
+        .unwrap()
+        .header
+}
+
+#[test]
+fn initialize_from_snapshot_succeeds() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        // the snapshot includes the retarget header at height 2016
+        let block_headers = generate_block_headers(12, H256Le::zero(), target);
+        let last_header = *block_headers.last().unwrap();
+        assert_ok!(BTCRelay::initialize_from_snapshot(block_headers.clone(), 2014));
+
+        assert_eq!(BTCRelay::get_best_block_height(), 2025);
+        assert_eq!(BTCRelay::get_best_block(), last_header.hash);
+        assert_eq!(
+            BTCRelay::get_block_hash(crate::MAIN_CHAIN_ID, 2016).unwrap(),
+            block_headers[2].hash
+        );
+        let rich_header = BTCRelay::get_block_header_from_hash(last_header.hash).unwrap();
+        assert_ne!(rich_header.median_time_past, 0);
+
+        let snapshot_event = TestEvent::BTCRelay(Event::ImportBlockHeaderSnapshot(2014, 2025, last_header.hash));
+        assert!(System::events().iter().any(|a| a.event == snapshot_event));
+
+        // later headers are verified against the imported history
+        let next_header = generate_block_header_with_timestamp(last_header.hash, last_header.timestamp + 1, target);
+        assert_ok!(BTCRelay::store_block_header(&3, next_header));
+        assert_eq!(BTCRelay::get_best_block_height(), 2026);
+
+        assert_err!(
+            BTCRelay::initialize_from_snapshot(block_headers, 2014),
+            TestError::AlreadyInitialized
+        );
+    })
+}
+
+#[test]
+fn initialize_from_snapshot_without_retarget_header_fails() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        let block_headers = generate_block_headers(12, H256Le::zero(), target);
+        assert_err!(
+            BTCRelay::initialize_from_snapshot(block_headers.clone(), 2017),
+            TestError::MissingRetargetHeader
+        );
+
+        let mut non_contiguous_headers = block_headers;
+        non_contiguous_headers.remove(5);
+        assert_err!(
+            BTCRelay::initialize_from_snapshot(non_contiguous_headers, 2014),
+            TestError::NonContiguousBlockHeaders
+        );
+    })
+}
+
+#[test]
+fn import_block_header_snapshot_requires_root() {
+    run_test(|| {
+        assert_err!(
+            BTCRelay::import_block_header_snapshot(Origin::signed(3), vec![], 0),
+            DispatchError::BadOrigin
+        );
+        assert_err!(
+            BTCRelay::import_block_header_snapshot(Origin::root(), vec![], 0),
+            TestError::EmptyBlockHeaders
+        );
+
+        let interval = <Test as crate::Config>::ChainParams::DIFFICULTY_ADJUSTMENT_INTERVAL;
+        let raw_block_headers = vec![RawBlockHeader::from_bytes(&[0u8; 80]).unwrap(); interval as usize + 1];
+        assert_err!(
+            BTCRelay::import_block_header_snapshot(Origin::root(), raw_block_headers, 0),
+            TestError::TooManyBlockHeaders
+        );
+    })
+}
+
+#[test]
+fn store_block_header_computes_median_time_past() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let block_headers = generate_block_headers(10, genesis.hash, target);
+        assert_ok!(BTCRelay::store_block_headers(&3, block_headers.clone()));
+
+        // not enough predecessors are known for the first blocks
+        let rich_header = BTCRelay::get_block_header_from_hash(block_headers[8].hash).unwrap();
+        assert_eq!(rich_header.median_time_past, 0);
+
+        // median of the genesis timestamp and the ten generated timestamps
+        let rich_header = BTCRelay::get_block_header_from_hash(block_headers[9].hash).unwrap();
+        assert_eq!(rich_header.median_time_past, 1588814835 + 5);
+    })
+}
+
+#[test]
+fn store_block_header_before_median_time_past_fails() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let block_headers = generate_block_headers(10, genesis.hash, target);
+        let last_hash = block_headers.last().unwrap().hash;
+        assert_ok!(BTCRelay::store_block_headers(&3, block_headers));
+
+        // timestamps may be lower than that of the previous block, but not at or below the median
+        let block_header = generate_block_header_with_timestamp(last_hash, 1588814835 + 5, target);
+        assert_err!(
+            BTCRelay::store_block_header(&3, block_header),
+            TestError::TimestampBeforeMedianTimePast
+        );
+
+        let block_header = generate_block_header_with_timestamp(last_hash, 1588814835 + 6, target);
+        assert_ok!(BTCRelay::store_block_header(&3, block_header));
+    })
+}
+
+#[test]
+fn store_block_header_accumulates_chainwork() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let block_headers = generate_block_headers(3, genesis.hash, target);
+        assert_ok!(BTCRelay::store_block_headers(&3, block_headers.clone()));
+
+        let work = genesis.work();
+        for (idx, block_header) in block_headers.iter().enumerate() {
+            let rich_header = BTCRelay::get_block_header_from_hash(block_header.hash).unwrap();
+            assert_eq!(rich_header.chainwork, work * U256::from(idx as u64 + 2));
+        }
+        let main_chain = BTCRelay::get_block_chain_from_id(crate::MAIN_CHAIN_ID).unwrap();
+        assert_eq!(main_chain.chainwork, work * U256::from(4));
+    })
+}
+
+#[test]
+fn store_block_header_reorgs_to_fork_with_most_chainwork() {
+    let low_work_target = U256::from(2).pow(254.into());
+    let high_work_target = U256::from(2).pow(250.into());
+    run_test(|| {
+        BTCRelay::verify_block_header.mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        let genesis = initialize_generated_genesis(low_work_target);
+
+        let main_headers = generate_block_headers(20, genesis.hash, low_work_target);
+        assert_ok!(BTCRelay::store_block_headers(&3, main_headers.clone()));
+
+        // a shorter fork replaces the main chain once it has accumulated enough work
+        let fork_headers = generate_block_headers(3, genesis.hash, high_work_target);
+        assert_ok!(BTCRelay::store_block_headers(&3, fork_headers.clone()));
+
+        let ahead_event = TestEvent::BTCRelay(Event::ForkAheadOfMainChain(20, 1, 1));
+        assert!(System::events().iter().any(|a| a.event == ahead_event));
+        let reorg_event = TestEvent::BTCRelay(Event::ChainReorg(fork_headers[1].hash, 2, 1));
+        assert!(System::events().iter().any(|a| a.event == reorg_event));
+
+        let fork_tip = fork_headers.last().unwrap().hash;
+        assert_eq!(BTCRelay::get_best_block(), fork_tip);
+        assert_eq!(BTCRelay::get_best_block_height(), 3);
+
+        // the old main chain blocks above the new tip are moved out of the main chain as well
+        assert_err!(
+            BTCRelay::get_block_hash(crate::MAIN_CHAIN_ID, 10),
+            TestError::MissingBlockHeight
+        );
+        let old_main_header = BTCRelay::get_block_header_from_hash(main_headers[9].hash).unwrap();
+        assert_ne!(old_main_header.chain_id, crate::MAIN_CHAIN_ID);
+        assert_eq!(
+            BTCRelay::get_block_hash(old_main_header.chain_id, 10),
+            Ok(main_headers[9].hash)
+        );
+
+        // the longer chain with less work does not become the main chain again
+        let next_header = generate_block_headers(1, main_headers.last().unwrap().hash, low_work_target);
+        assert_ok!(BTCRelay::store_block_headers(&3, next_header));
+        assert_eq!(BTCRelay::get_best_block(), fork_tip);
+    })
+}
+
+#[test]
+fn store_block_header_reorg_revokes_settled_requests() {
+    let low_work_target = U256::from(2).pow(254.into());
+    let high_work_target = U256::from(2).pow(250.into());
+    let issue = SettledRequest::Issue(H256::from_low_u64_be(1));
+    let redeem = SettledRequest::Redeem(H256::from_low_u64_be(2));
+    let replace = SettledRequest::Replace(H256::from_low_u64_be(3));
+    run_test(|| {
+        BTCRelay::verify_block_header.mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        let genesis = initialize_generated_genesis(low_work_target);
+
+        let main_headers = generate_block_headers(20, genesis.hash, low_work_target);
+        assert_ok!(BTCRelay::store_block_headers(&3, main_headers.clone()));
+        BTCRelay::record_settled_request(genesis.hash, replace);
+        BTCRelay::record_settled_request(main_headers[0].hash, issue);
+        BTCRelay::record_settled_request(main_headers[0].hash, redeem);
+        BTCRelay::record_settled_request(main_headers[10].hash, redeem);
+
+        let fork_headers = generate_block_headers(3, genesis.hash, high_work_target);
+        assert_ok!(BTCRelay::store_block_headers(&3, fork_headers));
+
+        // blocks below and above the new main chain tip are both removed from the main chain
+        let revoke_event = TestEvent::BTCRelay(Event::RevokeSettledRequests(main_headers[0].hash, vec![issue, redeem]));
+        assert!(System::events().iter().any(|a| a.event == revoke_event));
+        let revoke_event = TestEvent::BTCRelay(Event::RevokeSettledRequests(main_headers[10].hash, vec![redeem]));
+        assert!(System::events().iter().any(|a| a.event == revoke_event));
+
+        assert_eq!(BTCRelay::revoked_request(issue), Some(main_headers[0].hash));
+        assert_eq!(BTCRelay::revoked_request(redeem), Some(main_headers[10].hash));
+        assert!(BTCRelay::settled_requests(main_headers[0].hash).is_empty());
+
+        // requests settled by blocks that stay in the main chain are not affected
+        assert_eq!(BTCRelay::revoked_request(replace), None);
+        assert_eq!(BTCRelay::settled_requests(genesis.hash), vec![replace]);
+    })
+}
+
+#[test]
+fn store_block_header_reorg_below_stable_depth_revokes_settled_requests() {
+    let low_work_target = U256::from(2).pow(254.into());
+    let high_work_target = U256::from(2).pow(250.into());
+    let issue = SettledRequest::Issue(H256::from_low_u64_be(1));
+    run_test(|| {
+        BTCRelay::verify_block_header.mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        let genesis = initialize_generated_genesis(low_work_target);
+        PruningDepth::<Test>::set(30);
+
+        let main_headers = generate_block_headers(20, genesis.hash, low_work_target);
+        assert_ok!(BTCRelay::store_block_headers(&3, main_headers.clone()));
+
+        // the request is executed once its block is `BITCOIN_CONFIRMATIONS` deep
+        Security::set_active_block_number(1 + PARACHAIN_CONFIRMATIONS);
+        let block_height = 20 - BITCOIN_CONFIRMATIONS + 1;
+        let block_hash = main_headers[block_height as usize - 1].hash;
+        assert_ok!(BTCRelay::verify_block_header_inclusion(block_hash, None));
+        BTCRelay::record_settled_request(block_hash, issue);
+
+        // the record is kept while the block gets deeper
+        for n in 1..=10 {
+            <BTCRelay as frame_support::traits::Hooks<BlockNumber>>::on_initialize(n);
+        }
+        assert_eq!(BTCRelay::settled_requests(block_hash), vec![issue]);
+
+        // a fork from below the block, deeper than `BITCOIN_CONFIRMATIONS`, replaces the main chain
+        let fork_headers = generate_block_headers(3, genesis.hash, high_work_target);
+        assert_ok!(BTCRelay::store_block_headers(&3, fork_headers));
+
+        let revoke_event = TestEvent::BTCRelay(Event::RevokeSettledRequests(block_hash, vec![issue]));
+        assert!(System::events().iter().any(|a| a.event == revoke_event));
+        assert_eq!(BTCRelay::revoked_request(issue), Some(block_hash));
+    })
+}
+
+#[test]
+fn prune_block_headers_removes_settled_requests() {
+    let target = U256::from(2).pow(254.into());
+    let issue = SettledRequest::Issue(H256::from_low_u64_be(1));
+    run_test(|| {
+        BTCRelay::verify_block_header.mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        let genesis = initialize_generated_genesis(target);
+
+        let main_headers = generate_block_headers(20, genesis.hash, target);
+        assert_ok!(BTCRelay::store_block_headers(&3, main_headers.clone()));
+        BTCRelay::record_settled_request(main_headers[0].hash, issue);
+        BTCRelay::record_settled_request(main_headers[14].hash, issue);
+
+        // the records of pruned blocks are removed with them
+        PruningDepth::<Test>::set(10);
+        BTCRelay::prune_block_headers(20);
+        assert!(BTCRelay::settled_requests(main_headers[0].hash).is_empty());
+        assert_eq!(BTCRelay::settled_requests(main_headers[14].hash), vec![issue]);
+
+        // nothing is recorded for blocks that are not stored
+        BTCRelay::record_settled_request(main_headers[0].hash, issue);
+        assert!(BTCRelay::settled_requests(main_headers[0].hash).is_empty());
+    })
+}
+
+#[test]
+fn resolve_revoked_request_succeeds() {
+    let request = SettledRequest::Issue(H256::from_low_u64_be(1));
+    let block_hash = sample_block_header().hash;
+    run_test(|| {
+        assert_err!(
+            BTCRelay::resolve_revoked_request(Origin::root(), request),
+            TestError::RevokedRequestNotFound
+        );
+
+        crate::RevokedRequests::<Test>::insert(request, block_hash);
+        assert_err!(
+            BTCRelay::resolve_revoked_request(Origin::signed(3), request),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(BTCRelay::resolve_revoked_request(Origin::root(), request));
+        assert_eq!(BTCRelay::revoked_request(request), None);
+
+        let resolve_event = TestEvent::BTCRelay(Event::ResolveRevokedRequest(request));
+        assert!(System::events().iter().any(|a| a.event == resolve_event));
+    })
+}
+
+#[test]
+fn store_block_header_too_far_in_future_fails() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let now = genesis.timestamp + 1000;
+        Timestamp::set_timestamp(now as u64 * 1000);
+
+        let block_header =
+            generate_block_header_with_timestamp(genesis.hash, now + crate::MAX_FUTURE_BLOCK_TIME + 1, target);
+        assert_err!(
+            BTCRelay::store_block_header(&3, block_header),
+            TestError::TimestampTooFarInFuture
+        );
+
+        let block_header =
+            generate_block_header_with_timestamp(genesis.hash, now + crate::MAX_FUTURE_BLOCK_TIME, target);
+        assert_ok!(BTCRelay::store_block_header(&3, block_header));
+    })
+}
+
+#[test]
+fn add_checkpoint_requires_root() {
+    run_test(|| {
+        let block_hash = sample_block_header().hash;
+        assert_err!(
+            BTCRelay::add_checkpoint(Origin::signed(3), 1, block_hash),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(BTCRelay::add_checkpoint(Origin::root(), 1, block_hash));
+        assert_eq!(BTCRelay::checkpoint(1), Some(block_hash));
+
+        assert_err!(
+            BTCRelay::remove_checkpoint(Origin::signed(3), 1),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(BTCRelay::remove_checkpoint(Origin::root(), 1));
+        assert_eq!(BTCRelay::checkpoint(1), None);
+    })
+}
+
+#[test]
+fn initialize_conflicting_checkpoint_fails() {
+    run_test(|| {
+        let block_header = sample_block_header();
+        assert_ok!(BTCRelay::add_checkpoint(Origin::root(), 1, H256Le::zero()));
+
+        assert_err!(BTCRelay::initialize(3, block_header, 1), TestError::CheckpointMismatch);
+    })
+}
+
+#[test]
+fn initialize_above_checkpoint_fails() {
+    run_test(|| {
+        let block_header = sample_block_header();
+        assert_ok!(BTCRelay::add_checkpoint(Origin::root(), 1, block_header.hash));
+
+        assert_err!(
+            BTCRelay::initialize(3, block_header, 2),
+            TestError::InitializationAboveCheckpoint
+        );
+        assert_ok!(BTCRelay::initialize(3, block_header, 1));
+    })
+}
+
+#[test]
+fn store_block_header_conflicting_checkpoint_fails() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let block_headers = generate_block_headers(3, genesis.hash, target);
+        assert_ok!(BTCRelay::add_checkpoint(Origin::root(), 2, block_headers[1].hash));
+
+        // a fork that diverges before the checkpoint cannot pass it
+        assert_ok!(BTCRelay::store_block_header(&3, block_headers[0]));
+        let fork_header = generate_block_header_with_timestamp(block_headers[0].hash, 1588814835 + 10, target);
+        assert_err!(
+            BTCRelay::store_block_header(&3, fork_header),
+            TestError::CheckpointMismatch
+        );
+
+        assert_ok!(BTCRelay::store_block_headers(&3, block_headers[1..].to_vec()));
+        assert_eq!(BTCRelay::get_best_block_height(), 3);
+    })
+}
+
+#[test]
+fn prune_block_headers_removes_old_main_chain_headers() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let block_headers = generate_block_headers(10, genesis.hash, target);
+        assert_ok!(BTCRelay::store_block_headers(&3, block_headers.clone()));
+
+        // pruning is disabled by default
+        BTCRelay::prune_block_headers(10);
+        assert!(BTCRelay::block_header_exists(genesis.hash));
+
+        // retain headers from height 6 onwards, pruning at most 3 headers per call
+        PruningDepth::<Test>::set(4);
+        BTCRelay::prune_block_headers(3);
+        assert!(!BTCRelay::block_header_exists(genesis.hash));
+        assert!(!BTCRelay::block_header_exists(block_headers[1].hash));
+        assert!(BTCRelay::block_header_exists(block_headers[2].hash));
+
+        BTCRelay::prune_block_headers(10);
+        assert!(!BTCRelay::block_header_exists(block_headers[4].hash));
+        assert!(BTCRelay::block_header_exists(block_headers[5].hash));
+        assert_eq!(
+            BTCRelay::get_block_chain_from_id(crate::MAIN_CHAIN_ID)
+                .unwrap()
+                .start_height,
+            6
+        );
+        assert_err!(
+            BTCRelay::get_block_hash(crate::MAIN_CHAIN_ID, 5),
+            TestError::MissingBlockHeight
+        );
+        assert_eq!(BTCRelay::get_best_block_height(), 10);
+    })
+}
+
+#[test]
+fn prune_block_headers_removes_dead_forks() {
+    let target = U256::from(2).pow(254.into());
+    let miner = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let block_headers = generate_block_headers(10, genesis.hash, target);
+        assert_ok!(BTCRelay::store_block_headers(&3, block_headers));
+
+        let fork_header = BlockBuilder::new()
+            .with_coinbase(&miner, 25, 1)
+            .with_timestamp(1588814836)
+            .with_previous_hash(genesis.hash)
+            .mine(target)
+            .unwrap()
+            .header;
+        assert_ok!(BTCRelay::store_block_header(&3, fork_header));
+        let fork_id = BTCRelay::get_chain_id_from_position(1).unwrap();
+
+        PruningDepth::<Test>::set(4);
+        BTCRelay::prune_block_headers(20);
+
+        assert!(!BTCRelay::block_header_exists(fork_header.hash));
+        assert_eq!(Chains::<Test>::get(1), None);
+        assert_eq!(ChainsIndex::<Test>::get(fork_id), None);
+        assert_eq!(Chains::<Test>::get(0), Some(crate::MAIN_CHAIN_ID));
+    })
+}
+
+#[test]
+fn prune_block_headers_removes_dead_forks_ahead_of_live_forks() {
+    let target = U256::from(2).pow(254.into());
+    let miner = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let block_headers = generate_block_headers(10, genesis.hash, target);
+        assert_ok!(BTCRelay::store_block_headers(&3, block_headers.clone()));
+
+        let dead_fork_header = BlockBuilder::new()
+            .with_coinbase(&miner, 25, 1)
+            .with_timestamp(1588814836)
+            .with_previous_hash(genesis.hash)
+            .mine(target)
+            .unwrap()
+            .header;
+        assert_ok!(BTCRelay::store_block_header(&3, dead_fork_header));
+        let live_fork_header = generate_block_header_with_timestamp(block_headers[7].hash, 1588814835 + 20, target);
+        assert_ok!(BTCRelay::store_block_header(&3, live_fork_header));
+        let live_fork_id = BTCRelay::get_chain_id_from_position(1).unwrap();
+
+        // a dead fork with more chainwork than a live one is not at the end of `Chains`
+        Chains::<Test>::swap(1, 2);
+
+        PruningDepth::<Test>::set(4);
+        BTCRelay::prune_block_headers(20);
+
+        assert!(!BTCRelay::block_header_exists(dead_fork_header.hash));
+        assert!(BTCRelay::block_header_exists(live_fork_header.hash));
+        assert_eq!(Chains::<Test>::get(1), Some(live_fork_id));
+        assert_eq!(Chains::<Test>::get(2), None);
+    })
+}
+
+/// Stores a main chain of five headers and a fork of one header, then rewrites them in the layout
+/// of `version` as they were stored before V2. Returns the main chain headers, the fork header and
+/// the stored state.
+fn store_headers_before_v2(
+    version: Version,
+) -> (
+    Vec<BlockHeader>,
+    BlockHeader,
+    Vec<(H256Le, RichBlockHeader<BlockNumber>)>,
+    Vec<(u32, BlockChain)>,
+) {
+    let target = U256::from(2).pow(254.into());
+    let miner = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+    let genesis = initialize_generated_genesis(target);
+    let block_headers = generate_block_headers(5, genesis.hash, target);
+    assert_ok!(BTCRelay::store_block_headers(&3, block_headers[..4].to_vec()));
+    let fork_header = BlockBuilder::new()
+        .with_coinbase(&miner, 25, 1)
+        .with_timestamp(1588814836)
+        .with_previous_hash(genesis.hash)
+        .mine(target)
+        .unwrap()
+        .header;
+    assert_ok!(BTCRelay::store_block_header(&3, fork_header));
+
+    let headers = BlockHeaders::<Test>::iter().collect::<Vec<_>>();
+    let chains = ChainsIndex::<Test>::iter().collect::<Vec<_>>();
+    for (block_hash, header) in headers.iter() {
+        let key = BlockHeaders::<Test>::hashed_key_for(block_hash);
+        let header_v0 = RichBlockHeaderV0 {
+            block_header: header.block_header,
+            block_height: header.block_height,
+            chain_id: header.chain_id,
+            para_height: header.para_height,
+        };
+        match version {
+            Version::V0 => frame_support::storage::unhashed::put(&key, &header_v0),
+            _ => frame_support::storage::unhashed::put(
+                &key,
+                &RichBlockHeaderV1 {
+                    block_header: header_v0.block_header,
+                    block_height: header_v0.block_height,
+                    chain_id: header_v0.chain_id,
+                    para_height: header_v0.para_height,
+                    median_time_past: header.median_time_past,
+                },
+            ),
+        }
+    }
+    for (chain_id, chain) in chains.iter() {
+        let chain_v1 = BlockChainV1 {
+            chain_id: chain.chain_id,
+            start_height: chain.start_height,
+            max_height: chain.max_height,
+        };
+        frame_support::storage::unhashed::put(&ChainsIndex::<Test>::hashed_key_for(chain_id), &chain_v1);
+    }
+    StorageVersion::<Test>::put(version);
+
+    (block_headers, fork_header, headers, chains)
+}
+
+fn assert_migrated_to_v2(headers: Vec<(H256Le, RichBlockHeader<BlockNumber>)>, chains: Vec<(u32, BlockChain)>) {
+    assert!(!ChainworkMigration::<Test>::exists());
+    assert!(StorageVersion::<Test>::get() == Version::V2);
+    for (block_hash, header) in headers {
+        assert_eq!(BlockHeaders::<Test>::get(block_hash), header);
+    }
+    for (chain_id, chain) in chains {
+        assert_eq!(BTCRelay::get_block_chain_from_id(chain_id), Ok(chain));
+    }
+}
+
+#[test]
+fn migrate_chainwork_from_v0_succeeds() {
+    run_test(|| {
+        let (block_headers, fork_header, headers, chains) = store_headers_before_v2(Version::V0);
+
+        <BTCRelay as frame_support::traits::Hooks<BlockNumber>>::on_runtime_upgrade();
+        let migration = ChainworkMigration::<Test>::get().unwrap();
+        assert_eq!(migration.version, Version::V0);
+        assert_eq!(migration.total, 6);
+
+        // headers can be read, but not stored, until the migration completes
+        let header = BTCRelay::get_block_header_from_hash(fork_header.hash).unwrap();
+        assert_eq!(header.block_height, 1);
+        assert_eq!(header.chainwork, U256::zero());
+        assert_err!(
+            BTCRelay::store_block_header(&3, block_headers[4]),
+            TestError::ChainworkMigrationInProgress
+        );
+
+        // the migration takes ceil(6 / 4) rounds, reporting its progress in each
+        BTCRelay::migrate_chainwork(4);
+        assert!(System::events()
+            .iter()
+            .any(|a| a.event == TestEvent::BTCRelay(Event::MigrateChainwork(4, 6))));
+        assert!(ChainworkMigration::<Test>::exists());
+        // the fork is not migrated yet, while the main chain is
+        assert_eq!(
+            BTCRelay::get_block_header_from_hash(fork_header.hash)
+                .unwrap()
+                .chainwork,
+            U256::zero()
+        );
+        assert!(!BTCRelay::get_block_header_from_hash(block_headers[2].hash)
+            .unwrap()
+            .chainwork
+            .is_zero());
+
+        BTCRelay::migrate_chainwork(4);
+        assert!(System::events()
+            .iter()
+            .any(|a| a.event == TestEvent::BTCRelay(Event::MigrateChainwork(6, 6))));
+        assert_migrated_to_v2(headers, chains);
+        assert_ok!(BTCRelay::store_block_header(&3, block_headers[4]));
+    })
+}
+
+#[test]
+fn migrate_chainwork_from_v1_succeeds() {
+    run_test(|| {
+        let (block_headers, _, headers, chains) = store_headers_before_v2(Version::V1);
+
+        <BTCRelay as frame_support::traits::Hooks<BlockNumber>>::on_runtime_upgrade();
+        assert_eq!(ChainworkMigration::<Test>::get().unwrap().version, Version::V1);
+
+        // the default limit migrates all six headers in one block
+        <BTCRelay as frame_support::traits::Hooks<BlockNumber>>::on_initialize(1);
+        assert_migrated_to_v2(headers, chains);
+        assert_ok!(BTCRelay::store_block_header(&3, block_headers[4]));
+    })
+}
+
+#[test]
+fn get_last_chain_position_succeeds() {
+    let target = U256::from(2).pow(254.into());
+    let miner = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+    run_test(|| {
+        assert_eq!(BTCRelay::get_last_chain_position().0, None);
+
+        let genesis = initialize_generated_genesis(target);
+        assert_eq!(BTCRelay::get_last_chain_position().0, Some(0));
+
+        for i in 0..3 {
+            let fork_header = BlockBuilder::new()
+                .with_coinbase(&miner, 25, i)
+                .with_timestamp(1588814836)
+                .with_previous_hash(genesis.hash)
+                .mine(target)
+                .unwrap()
+                .header;
+            assert_ok!(BTCRelay::store_block_header(&3, fork_header));
+        }
+        // the first header extends the main chain, the others create a fork each
+        assert_eq!(BTCRelay::get_last_chain_position().0, Some(2));
+    })
+}
+
+#[test]
+fn set_pruning_depth_succeeds() {
+    run_test(|| {
+        let interval = <Test as crate::Config>::ChainParams::DIFFICULTY_ADJUSTMENT_INTERVAL;
+        assert_err!(
+            BTCRelay::set_pruning_depth(Origin::signed(3), interval),
+            DispatchError::BadOrigin
+        );
+        assert_err!(
+            BTCRelay::set_pruning_depth(Origin::root(), interval - 1),
+            TestError::InvalidPruningDepth
+        );
+
+        assert_ok!(BTCRelay::set_pruning_depth(Origin::root(), interval));
+        assert_eq!(BTCRelay::pruning_depth(), interval);
+        assert_ok!(BTCRelay::set_pruning_depth(Origin::root(), 0));
+        assert_eq!(BTCRelay::pruning_depth(), 0);
+
+        // without difficulty checks the retarget header is not needed
+        crate::DisableDifficultyCheck::<Test>::set(true);
+        assert_ok!(BTCRelay::set_pruning_depth(Origin::root(), 1));
+    })
+}
+
+#[test]
+fn get_main_chain_block_header_succeeds() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let block_headers = generate_block_headers(3, genesis.hash, target);
+        assert_ok!(BTCRelay::store_block_headers(&3, block_headers.clone()));
+
+        let rich_header = BTCRelay::get_main_chain_block_header(2).unwrap();
+        assert_eq!(rich_header.block_header, block_headers[1]);
+        assert_eq!(rich_header.block_height, 2);
+        assert_eq!(rich_header.chain_id, crate::MAIN_CHAIN_ID);
+
+        assert_err!(BTCRelay::get_main_chain_block_header(4), TestError::MissingBlockHeight);
+    })
+}
+
+#[test]
+fn get_forks_succeeds() {
+    let target = U256::from(2).pow(254.into());
+    run_test(|| {
+        let genesis = initialize_generated_genesis(target);
+        let block_headers = generate_block_headers(3, genesis.hash, target);
+        assert_ok!(BTCRelay::store_block_headers(&3, block_headers.clone()));
+        assert_eq!(BTCRelay::get_forks(), vec![]);
+
+        let fork_header = generate_block_header_with_timestamp(block_headers[0].hash, 1588814835 + 10, target);
+        assert_ok!(BTCRelay::store_block_header(&3, fork_header));
+
+        let fork_id = BTCRelay::get_chain_id_from_position(1).unwrap();
+        let forks = BTCRelay::get_forks();
+        assert_eq!(forks.len(), 1);
+        assert_eq!(forks[0].0, BTCRelay::get_block_chain_from_id(fork_id).unwrap());
+        assert_eq!(forks[0].0.max_height, 2);
+        assert_eq!(forks[0].1, fork_header.hash);
+    })
+}
+
 mod op_return_payment_data_tests {
     use super::*;
     use itertools::Itertools;
@@ -1650,6 +3238,29 @@ mod op_return_payment_data_tests {
         })
     }
 
+    #[test]
+    fn test_constructing_op_return_payment_data_with_taproot_output_succeeds() {
+        run_test(|| {
+            let amount = 12345;
+            let op_return = H256::from_slice(&[5; 32]);
+            let taproot_address = BtcAddress::P2TRv1(H256::from_slice(&[7; 32]));
+            let transaction = TransactionBuilder::new()
+                .with_version(2)
+                .add_output(TransactionOutput::payment(amount, &taproot_address))
+                .add_output(TransactionOutput::payment(123, &dummy_address2()))
+                .add_output(TransactionOutput::op_return(0, op_return.as_bytes()))
+                .build();
+
+            for transaction in permutations(transaction) {
+                let payment_data = OpReturnPaymentData::<Test>::try_from(transaction).unwrap();
+                assert_ok!(
+                    payment_data.ensure_valid_payment_to(amount, taproot_address, Some(op_return)),
+                    Some(dummy_address2())
+                );
+            }
+        })
+    }
+
     #[test]
     fn test_constructing_op_return_payment_data_with_too_many_outputs_fails() {
         run_test(|| {
@@ -1740,6 +3351,75 @@ mod op_return_payment_data_tests {
         })
     }
 
+    #[test]
+    fn test_constructing_batch_op_return_payment_data_with_many_outputs_succeeds() {
+        run_test(|| {
+            let transaction = TransactionBuilder::new()
+                .with_version(2)
+                .add_output(TransactionOutput::payment(252345, &dummy_address1()))
+                .add_output(TransactionOutput::payment(252345, &dummy_address2()))
+                .add_output(TransactionOutput::payment(252345, &dummy_address3()))
+                .add_output(TransactionOutput::op_return(0, &[0; 32]))
+                .build();
+
+            for transaction in permutations(transaction) {
+                assert_ok!(OpReturnPaymentData::<Test>::try_from_batch(transaction));
+            }
+        })
+    }
+
+    #[test]
+    fn test_constructing_batch_op_return_payment_data_with_two_identical_outputs_fails() {
+        run_test(|| {
+            let transaction = TransactionBuilder::new()
+                .with_version(2)
+                .add_output(TransactionOutput::payment(252345, &dummy_address1()))
+                .add_output(TransactionOutput::payment(252345, &dummy_address2()))
+                .add_output(TransactionOutput::payment(252344145, &dummy_address1()))
+                .add_output(TransactionOutput::op_return(0, &[0; 32]))
+                .build();
+
+            for transaction in permutations(transaction) {
+                assert_err!(
+                    OpReturnPaymentData::<Test>::try_from_batch(transaction),
+                    Error::<Test>::InvalidOpReturnTransaction
+                );
+            }
+        })
+    }
+
+    #[test]
+    fn test_ensure_valid_batch_payment_to_succeeds() {
+        run_test(|| {
+            let op_return = H256::from_slice(&[5; 32]);
+            let transaction = TransactionBuilder::new()
+                .with_version(2)
+                .add_output(TransactionOutput::payment(100, &dummy_address1()))
+                .add_output(TransactionOutput::payment(200, &dummy_address2()))
+                .add_output(TransactionOutput::payment(123, &dummy_address3()))
+                .add_output(TransactionOutput::op_return(0, op_return.as_bytes()))
+                .build();
+            let expected_payments = vec![(dummy_address1(), 100), (dummy_address2(), 200)];
+
+            for transaction in permutations(transaction) {
+                let payment_data = OpReturnPaymentData::<Test>::try_from_batch(transaction).unwrap();
+                assert_ok!(
+                    payment_data.ensure_valid_batch_payment_to(&expected_payments, op_return),
+                    Some(dummy_address3())
+                );
+                assert_err!(
+                    payment_data.ensure_valid_batch_payment_to(&[(dummy_address1(), 100)], op_return),
+                    Error::<Test>::InvalidPayment
+                );
+                assert_err!(
+                    payment_data
+                        .ensure_valid_batch_payment_to(&[(dummy_address1(), 100), (dummy_address2(), 201)], op_return),
+                    Error::<Test>::InvalidPaymentAmount
+                );
+            }
+        })
+    }
+
     #[test]
     fn test_ensure_valid_payment_to_succeeds() {
         run_test(|| {
@@ -1887,6 +3567,7 @@ fn test_check_and_do_reorg() {
                 chain_id: 0,
                 start_height: 1_892_642,
                 max_height: 1_897_317,
+                chainwork: 1_897_317.into(),
             },
         );
 
@@ -1896,6 +3577,7 @@ fn test_check_and_do_reorg() {
                 chain_id: 2,
                 start_height: 1_893_831,
                 max_height: 1_893_831,
+                chainwork: 1_893_831.into(),
             },
         );
 
@@ -1905,6 +3587,7 @@ fn test_check_and_do_reorg() {
                 chain_id: 4,
                 start_height: 1_895_256,
                 max_height: 1_895_256,
+                chainwork: 1_895_256.into(),
             },
         );
 
@@ -1914,6 +3597,7 @@ fn test_check_and_do_reorg() {
                 chain_id: 6,
                 start_height: 1_896_846,
                 max_height: 1_896_846,
+                chainwork: 1_896_846.into(),
             },
         );
 
@@ -1923,10 +3607,12 @@ fn test_check_and_do_reorg() {
                 chain_id: 7,
                 start_height: 1_897_317,
                 max_height: 1_897_910,
+                chainwork: 1_897_910.into(),
             },
         );
 
         BTCRelay::swap_main_blockchain.mock_safe(|_| MockResult::Return(Ok((Default::default(), Default::default()))));
+        BTCRelay::get_chain_tip_work.mock_safe(|_| MockResult::Return(Ok(1.into())));
 
         // we should skip empty `Chains`, this can occur if the
         // previous index is accidentally deleted
@@ -1934,6 +3620,7 @@ fn test_check_and_do_reorg() {
             chain_id: 7,
             start_height: 1_897_317,
             max_height: 1_897_910,
+            chainwork: 1_897_910.into(),
         }));
     })
 }
@@ -2011,10 +3698,12 @@ fn sample_valid_proof_result() -> ProofResult {
 }
 
 fn get_empty_block_chain_from_chain_id_and_height(chain_id: u32, start_height: u32, block_height: u32) -> BlockChain {
+    // every block has a work of one
     let blockchain = BlockChain {
         chain_id,
         start_height,
         max_height: block_height,
+        chainwork: block_height.into(),
     };
 
     blockchain
@@ -2031,6 +3720,8 @@ fn sample_parsed_genesis_header(chain_id: u32, block_height: u32) -> RichBlockHe
         block_height,
         chain_id,
         para_height: Default::default(),
+        median_time_past: Default::default(),
+        chainwork: Default::default(),
     }
 }
 
@@ -2054,6 +3745,8 @@ fn sample_parsed_first_block(chain_id: u32, block_height: u32) -> RichBlockHeade
         block_height,
         chain_id,
         para_height: Default::default(),
+        median_time_past: Default::default(),
+        chainwork: Default::default(),
     }
 }
 
@@ -2090,6 +3783,8 @@ fn sample_rich_tx_block_header(chain_id: u32, block_height: u32) -> RichBlockHea
         block_height,
         chain_id,
         para_height: Default::default(),
+        median_time_past: Default::default(),
+        chainwork: Default::default(),
     }
 }
 
@@ -1,13 +1,103 @@
-use crate::{Error, ACCEPTED_MAX_TRANSACTION_OUTPUTS};
+use crate::{Error, ACCEPTED_MAX_BATCH_RECIPIENTS, ACCEPTED_MAX_TRANSACTION_OUTPUTS};
 use bitcoin::types::{BlockHeader, H256Le, Transaction, Value};
 pub use bitcoin::Address as BtcAddress;
 use codec::{Decode, Encode};
 use frame_support::{dispatch::DispatchError, ensure};
-use sp_core::H256;
+use sp_core::{H256, U256};
 use sp_std::{convert::TryFrom, vec::Vec};
 
+/// Storage version.
+#[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Version {
+    /// Initial version.
+    V0,
+    /// RichBlockHeader with median_time_past
+    V1,
+    /// RichBlockHeader and BlockChain with chainwork
+    V2,
+}
+
+/// Bitcoin chains as stored before V2
+#[derive(Encode, Decode)]
+pub(crate) struct BlockChainV1 {
+    pub chain_id: u32,
+    pub start_height: u32,
+    pub max_height: u32,
+}
+
+/// Progress of the migration to V2, which translates the stored headers one chain at a time
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct ChainworkMigrationState {
+    /// storage version in whose layout the headers that are not yet migrated are stored
+    pub version: Version,
+    /// ids of the chains left to migrate, in order of their start height
+    pub chain_ids: Vec<u32>,
+    /// next height to migrate in the first of these chains, zero if it was not started
+    pub block_height: u32,
+    /// number of block heights migrated so far
+    pub migrated: u32,
+    /// number of block heights to migrate in total
+    pub total: u32,
+}
+
+impl ChainworkMigrationState {
+    /// Checks if the header at the given height of the given chain has been migrated
+    pub fn is_migrated(&self, chain_id: u32, block_height: u32) -> bool {
+        match self.chain_ids.iter().position(|&id| id == chain_id) {
+            None => true,
+            Some(0) => block_height < self.block_height,
+            Some(_) => false,
+        }
+    }
+}
+
+/// Bitcoin Enriched Block Headers as stored in V0
+#[derive(Encode, Decode)]
+pub(crate) struct RichBlockHeaderV0<BlockNumber> {
+    pub block_header: BlockHeader,
+    pub block_height: u32,
+    pub chain_id: u32,
+    pub para_height: BlockNumber,
+}
+
+impl<BlockNumber> From<RichBlockHeaderV0<BlockNumber>> for RichBlockHeader<BlockNumber> {
+    fn from(header: RichBlockHeaderV0<BlockNumber>) -> Self {
+        RichBlockHeader::new(
+            header.block_header,
+            header.chain_id,
+            header.block_height,
+            header.para_height,
+        )
+    }
+}
+
+/// Bitcoin Enriched Block Headers as stored in V1
+#[derive(Encode, Decode)]
+pub(crate) struct RichBlockHeaderV1<BlockNumber> {
+    pub block_header: BlockHeader,
+    pub block_height: u32,
+    pub chain_id: u32,
+    pub para_height: BlockNumber,
+    pub median_time_past: u32,
+}
+
+impl<BlockNumber> From<RichBlockHeaderV1<BlockNumber>> for RichBlockHeader<BlockNumber> {
+    fn from(header: RichBlockHeaderV1<BlockNumber>) -> Self {
+        RichBlockHeader {
+            median_time_past: header.median_time_past,
+            ..RichBlockHeader::new(
+                header.block_header,
+                header.chain_id,
+                header.block_height,
+                header.para_height,
+            )
+        }
+    }
+}
+
 /// Bitcoin Enriched Block Headers
 #[derive(Encode, Decode, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 pub struct RichBlockHeader<BlockNumber> {
     pub block_header: BlockHeader,
     /// height of the block in the bitcoin chain
@@ -16,6 +106,10 @@ pub struct RichBlockHeader<BlockNumber> {
     pub chain_id: u32,
     /// active_block_number of the parachain at the time this block was submitted
     pub para_height: BlockNumber,
+    /// median timestamp of this block and its 10 predecessors, zero if not all of them are known
+    pub median_time_past: u32,
+    /// cumulative work of this block and all of its stored predecessors
+    pub chainwork: U256,
 }
 
 impl<BlockNumber> RichBlockHeader<BlockNumber> {
@@ -34,6 +128,8 @@ impl<BlockNumber> RichBlockHeader<BlockNumber> {
             block_height,
             chain_id,
             para_height,
+            median_time_past: 0,
+            chainwork: U256::zero(),
         }
     }
 
@@ -42,6 +138,29 @@ impl<BlockNumber> RichBlockHeader<BlockNumber> {
     }
 }
 
+/// A request whose Bitcoin payment is included in a relayed block
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum SettledRequest {
+    Issue(H256),
+    Redeem(H256),
+    Replace(H256),
+}
+
+/// Outcome of a dry run of the payment verification of a request
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct PaymentVerification<Balance> {
+    /// the amount paid to the recipient, if a payment was found
+    pub amount_found: Option<Balance>,
+    /// the amount the request expects to be paid
+    pub expected_amount: Balance,
+    /// for issue payments the sender address, otherwise the return-to-self address
+    pub refund_address: Option<BtcAddress>,
+    /// the error of the first check that failed, or none if the payment is valid
+    pub error: Option<DispatchError>,
+}
+
 #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
 pub struct OpReturnPaymentData<T: frame_system::Config> {
     pub op_return: H256,
@@ -54,10 +173,22 @@ impl<T: crate::Config> TryFrom<Transaction> for OpReturnPaymentData<T> {
     type Error = DispatchError;
 
     fn try_from(transaction: Transaction) -> Result<Self, Self::Error> {
+        Self::parse(transaction, ACCEPTED_MAX_TRANSACTION_OUTPUTS)
+    }
+}
+
+impl<T: crate::Config> OpReturnPaymentData<T> {
+    /// Parses a transaction that pays several recipients at once, as used by batched requests
+    pub fn try_from_batch(transaction: Transaction) -> Result<Self, DispatchError> {
+        // one payment per recipient, plus the return-to-self and the op_return
+        Self::parse(transaction, ACCEPTED_MAX_BATCH_RECIPIENTS + 2)
+    }
+
+    fn parse(transaction: Transaction, max_outputs: usize) -> Result<Self, DispatchError> {
         // check the number of outputs - this check is redundant due to the checks below, but
         // this serves to put an upperbound to the number of iterations
         ensure!(
-            transaction.outputs.len() <= ACCEPTED_MAX_TRANSACTION_OUTPUTS,
+            transaction.outputs.len() <= max_outputs,
             Error::<T>::InvalidOpReturnTransaction
         );
 
@@ -80,15 +211,16 @@ impl<T: crate::Config> TryFrom<Transaction> for OpReturnPaymentData<T> {
         // check we have exactly 1 op-return
         ensure!(op_returns.len() == 1, Error::<T>::InvalidOpReturnTransaction);
 
-        // Check that we have either 1 payment, or 2 payments to different addresses. Enforcing the
-        // payments to be unique helps to prevent the vault from paying more than is allowed
-        match payments.len() {
-            1 => (),
-            2 => {
-                // ensure that the addresses are not identical
-                ensure!(payments[0].1 != payments[1].1, Error::<T>::InvalidOpReturnTransaction);
-            }
-            _ => return Err(Error::<T>::InvalidOpReturnTransaction.into()),
+        // Check that we have at least 1 payment, and that all payments are to different addresses.
+        // Enforcing the payments to be unique helps to prevent the vault from paying more than is
+        // allowed. Together with the output limit this allows either 1 or 2 payments for a single
+        // request.
+        ensure!(!payments.is_empty(), Error::<T>::InvalidOpReturnTransaction);
+        for (i, (_, address)) in payments.iter().enumerate() {
+            ensure!(
+                payments[..i].iter().all(|(_, other)| other != address),
+                Error::<T>::InvalidOpReturnTransaction
+            );
         }
 
         Ok(Self {
@@ -97,9 +229,7 @@ impl<T: crate::Config> TryFrom<Transaction> for OpReturnPaymentData<T> {
             _marker: Default::default(),
         })
     }
-}
 
-impl<T: crate::Config> OpReturnPaymentData<T> {
     // ensures this is a valid payment. If it is, it returns the return-to-self address
     pub fn ensure_valid_payment_to(
         &self,
@@ -113,18 +243,49 @@ impl<T: crate::Config> OpReturnPaymentData<T> {
         }
 
         // ensure we have a correct payment to the recipient
-        let paid_amount = self
-            .payments
-            .iter()
-            .find_map(|&(amount, address)| if address == recipient { Some(amount) } else { None })
-            .ok_or(Error::<T>::InvalidPayment)?;
+        let paid_amount = self.amount_paid_to(recipient).ok_or(Error::<T>::InvalidPayment)?;
 
         ensure!(paid_amount == expected_amount, Error::<T>::InvalidPaymentAmount);
 
         // return the return-to-self if it exists, otherwise None
-        Ok(self
+        Ok(self.return_to_self_address(recipient))
+    }
+
+    /// Ensures that every recipient is paid exactly the expected amount. At most one other payment
+    /// is allowed, which is returned as the return-to-self address.
+    pub fn ensure_valid_batch_payment_to(
+        &self,
+        expected_payments: &[(BtcAddress, Value)],
+        op_return: H256,
+    ) -> Result<Option<BtcAddress>, DispatchError> {
+        ensure!(op_return == self.op_return, Error::<T>::InvalidPayment);
+
+        for &(recipient, expected_amount) in expected_payments {
+            let paid_amount = self.amount_paid_to(recipient).ok_or(Error::<T>::InvalidPayment)?;
+            ensure!(paid_amount == expected_amount, Error::<T>::InvalidPaymentAmount);
+        }
+
+        let mut other_payments = self
             .payments
             .iter()
-            .find_map(|&(_, address)| if address != recipient { Some(address) } else { None }))
+            .filter(|(_, address)| !expected_payments.iter().any(|(recipient, _)| recipient == address));
+        let return_to_self = other_payments.next().map(|&(_, address)| address);
+        ensure!(other_payments.next().is_none(), Error::<T>::InvalidPayment);
+
+        Ok(return_to_self)
+    }
+
+    /// Returns the amount paid to the recipient, if any
+    pub fn amount_paid_to(&self, recipient: BtcAddress) -> Option<Value> {
+        self.payments
+            .iter()
+            .find_map(|&(amount, address)| if address == recipient { Some(amount) } else { None })
+    }
+
+    /// Returns the address of the payment that is not made to the recipient, if any
+    pub fn return_to_self_address(&self, recipient: BtcAddress) -> Option<BtcAddress> {
+        self.payments
+            .iter()
+            .find_map(|&(_, address)| if address != recipient { Some(address) } else { None })
     }
 }
@@ -3,18 +3,32 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::Codec;
+use frame_support::dispatch::DispatchError;
 use sp_std::vec::Vec;
 
 sp_api::decl_runtime_apis! {
-    pub trait IssueApi<AccountId, H256, IssueRequest> where
+    pub trait IssueApi<AccountId, H256, IssueRequest, PaymentVerification, IssueGroupStatus> where
         AccountId: Codec,
         H256: Codec,
         IssueRequest: Codec,
+        PaymentVerification: Codec,
+        IssueGroupStatus: Codec,
     {
         /// Get all issue requests for a particular account
         fn get_issue_requests(account_id: AccountId) -> Vec<(H256, IssueRequest)>;
 
         /// Get all issue requests for a particular vault
         fn get_vault_issue_requests(account_id: AccountId) -> Vec<(H256, IssueRequest)>;
+
+        /// Dry-run the payment verification of the issue request, reporting the payment that was
+        /// found and the first check that failed, if any
+        fn verify_issue_payment(
+            issue_id: H256,
+            raw_merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+        ) -> Result<PaymentVerification, DispatchError>;
+
+        /// Get the aggregate status of a group of issue requests opened against several vaults
+        fn get_issue_group_status(group_id: H256) -> Result<IssueGroupStatus, DispatchError>;
     }
 }
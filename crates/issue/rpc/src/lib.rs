@@ -5,14 +5,14 @@ use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
 use jsonrpc_derive::rpc;
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
-use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT, DispatchError};
 use std::sync::Arc;
 
 pub use self::gen_client::Client as IssueClient;
 pub use module_issue_rpc_runtime_api::IssueApi as IssueRuntimeApi;
 
 #[rpc]
-pub trait IssueApi<BlockHash, AccountId, H256, IssueRequest> {
+pub trait IssueApi<BlockHash, AccountId, H256, IssueRequest, PaymentVerification, IssueGroupStatus> {
     #[rpc(name = "issue_getIssueRequests")]
     fn get_issue_requests(&self, account_id: AccountId, at: Option<BlockHash>) -> Result<Vec<(H256, IssueRequest)>>;
 
@@ -22,6 +22,22 @@ pub trait IssueApi<BlockHash, AccountId, H256, IssueRequest> {
         account_id: AccountId,
         at: Option<BlockHash>,
     ) -> Result<Vec<(H256, IssueRequest)>>;
+
+    #[rpc(name = "issue_verifyIssuePayment")]
+    fn verify_issue_payment(
+        &self,
+        issue_id: H256,
+        raw_merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+        at: Option<BlockHash>,
+    ) -> Result<std::result::Result<PaymentVerification, DispatchError>>;
+
+    #[rpc(name = "issue_getIssueGroupStatus")]
+    fn get_issue_group_status(
+        &self,
+        group_id: H256,
+        at: Option<BlockHash>,
+    ) -> Result<std::result::Result<IssueGroupStatus, DispatchError>>;
 }
 
 /// A struct that implements the [`IssueApi`].
@@ -52,15 +68,18 @@ impl From<Error> for i64 {
     }
 }
 
-impl<C, Block, AccountId, H256, IssueRequest> IssueApi<<Block as BlockT>::Hash, AccountId, H256, IssueRequest>
+impl<C, Block, AccountId, H256, IssueRequest, PaymentVerification, IssueGroupStatus>
+    IssueApi<<Block as BlockT>::Hash, AccountId, H256, IssueRequest, PaymentVerification, IssueGroupStatus>
     for Issue<C, Block>
 where
     Block: BlockT,
     C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
-    C::Api: IssueRuntimeApi<Block, AccountId, H256, IssueRequest>,
+    C::Api: IssueRuntimeApi<Block, AccountId, H256, IssueRequest, PaymentVerification, IssueGroupStatus>,
     AccountId: Codec,
     H256: Codec,
     IssueRequest: Codec,
+    PaymentVerification: Codec,
+    IssueGroupStatus: Codec,
 {
     fn get_issue_requests(
         &self,
@@ -91,4 +110,37 @@ where
             data: Some(format!("{:?}", e).into()),
         })
     }
+
+    fn verify_issue_payment(
+        &self,
+        issue_id: H256,
+        raw_merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<std::result::Result<PaymentVerification, DispatchError>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.verify_issue_payment(&at, issue_id, raw_merkle_proof, raw_tx)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to verify issue payment.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn get_issue_group_status(
+        &self,
+        group_id: H256,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<std::result::Result<IssueGroupStatus, DispatchError>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.get_issue_group_status(&at, group_id).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(Error::RuntimeError.into()),
+            message: "Unable to fetch issue group status.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
 }
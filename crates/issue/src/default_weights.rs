@@ -35,7 +35,10 @@ use sp_std::marker::PhantomData;
 /// Weight functions needed for issue.
 pub trait WeightInfo {
 	fn request_issue() -> Weight;
+	fn request_issue_with_auto_vault(n: u32, ) -> Weight;
+	fn request_issue_group(n: u32, m: u32, ) -> Weight;
 	fn execute_issue() -> Weight;
+	fn execute_issue_with_payments(n: u32, ) -> Weight;
 	fn cancel_issue() -> Weight;
 	fn set_issue_period() -> Weight;
 }
@@ -66,6 +69,65 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(17 as Weight))
 			.saturating_add(T::DbWeight::get().writes(4 as Weight))
 	}
+	// Storage: Security Nonce (r:2 w:1)
+	// Storage: System ParentHash (r:1 w:0)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: BTCRelay StartBlockHeight (r:1 w:0)
+	// Storage: BTCRelay StableBitcoinConfirmations (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: Oracle Aggregate (r:1 w:0)
+	// Storage: Fee IssueGriefingCollateral (r:1 w:0)
+	// Storage: Tokens Accounts (r:1 w:1)
+	// Storage: Issue IssueBtcDustValue (r:1 w:0)
+	// Storage: VaultRegistry SecureCollateralThreshold (r:1 w:0)
+	// Storage: Fee IssueFee (r:1 w:0)
+	// Storage: Issue IssuePeriod (r:1 w:0)
+	// Storage: Issue IssueRequests (r:0 w:1)
+	// Storage: VaultRegistry Vaults (r:n w:0)
+	// Storage: Staking Nonce (r:n w:0)
+	// Storage: Staking TotalCurrentStake (r:n w:0)
+	// Estimate: request_issue (500_076_000) plus, per scanned candidate vault, accept_new_issues
+	// (17_480_000), which decodes and checks one vault as the candidate scan does
+	fn request_issue_with_auto_vault(n: u32, ) -> Weight {
+		(500_076_000 as Weight)
+			.saturating_add((17_480_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(16 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Oracle Aggregate (r:1 w:0)
+	// Storage: VaultRegistry SecureCollateralThreshold (r:1 w:0)
+	// Storage: Staking Nonce (r:1 w:0)
+	// Storage: Staking TotalCurrentStake (r:1 w:0)
+	// Storage: Fee IssueGriefingCollateral (r:1 w:0)
+	// Storage: BTCRelay StartBlockHeight (r:1 w:0)
+	// Storage: BTCRelay StableBitcoinConfirmations (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: Tokens Accounts (r:1 w:1)
+	// Storage: Issue IssueBtcDustValue (r:1 w:0)
+	// Storage: Fee IssueFee (r:1 w:0)
+	// Storage: Security Nonce (r:1 w:1)
+	// Storage: System ParentHash (r:1 w:0)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: Issue IssuePeriod (r:1 w:0)
+	// Storage: Issue IssueRequests (r:0 w:1)
+	// Storage: Issue IssueGroups (r:0 w:1)
+	// Estimate: `n` times request_issue (500_076_000) for the requests of the group plus, per
+	// scanned candidate vault (m), accept_new_issues (17_480_000) for decoding and checking it
+	fn request_issue_group(n: u32, m: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((500_076_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((17_480_000 as Weight).saturating_mul(m as Weight))
+			.saturating_add(T::DbWeight::get().reads(13 as Weight))
+			.saturating_add(T::DbWeight::get().reads((10 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(m as Weight)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(n as Weight)))
+	}
 	// Storage: Security ParachainStatus (r:1 w:0)
 	// Storage: Issue IssueRequests (r:1 w:1)
 	// Storage: Issue IssuePeriod (r:1 w:0)
@@ -85,10 +147,45 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	// Storage: Tokens TotalIssuance (r:1 w:1)
 	// Storage: System Account (r:1 w:1)
 	// Storage: Rewards TotalRewards (r:1 w:1)
+	// Storage: BTCRelay SettledRequests (r:0 w:1)
+	// Estimate: execution time as measured before payments were recorded; recording adds one
+	// append to SettledRequests, whose BlockHeaders existence check hits the header read by the
+	// inclusion proof
 	fn execute_issue() -> Weight {
 		(203_467_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(19 as Weight))
+			.saturating_add(T::DbWeight::get().writes(11 as Weight))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: Issue IssueRequests (r:1 w:1)
+	// Storage: Issue IssuePeriod (r:1 w:0)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: BTCRelay DisableInclusionCheck (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: BTCRelay Chains (r:1 w:0)
+	// Storage: BTCRelay BlockHeaders (r:1 w:0)
+	// Storage: BTCRelay StableBitcoinConfirmations (r:1 w:0)
+	// Storage: BTCRelay StableParachainConfirmations (r:1 w:0)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Rewards Stake (r:1 w:1)
+	// Storage: Rewards TotalStake (r:1 w:1)
+	// Storage: Rewards RewardTally (r:1 w:1)
+	// Storage: Rewards RewardPerToken (r:1 w:1)
+	// Storage: Tokens Accounts (r:1 w:1)
+	// Storage: Tokens TotalIssuance (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Rewards TotalRewards (r:1 w:1)
+	// Storage: BTCRelay SettledRequests (r:0 w:1)
+	// Estimate: execute_issue (203_467_000) with its payment verification replaced by `n` times
+	// verify_and_validate_transaction (66_727_000); each payment also checks that its block is stored
+	// and appends to SettledRequests
+	fn execute_issue_with_payments(n: u32, ) -> Weight {
+		(136_740_000 as Weight)
+			.saturating_add((66_727_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(18 as Weight))
+			.saturating_add(T::DbWeight::get().reads((5 as Weight).saturating_mul(n as Weight)))
 			.saturating_add(T::DbWeight::get().writes(10 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
 	}
 	// Storage: Security ParachainStatus (r:1 w:0)
 	// Storage: Issue IssueRequests (r:1 w:1)
@@ -133,6 +230,65 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(17 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
 	}
+	// Storage: Security Nonce (r:2 w:1)
+	// Storage: System ParentHash (r:1 w:0)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: BTCRelay StartBlockHeight (r:1 w:0)
+	// Storage: BTCRelay StableBitcoinConfirmations (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: Oracle Aggregate (r:1 w:0)
+	// Storage: Fee IssueGriefingCollateral (r:1 w:0)
+	// Storage: Tokens Accounts (r:1 w:1)
+	// Storage: Issue IssueBtcDustValue (r:1 w:0)
+	// Storage: VaultRegistry SecureCollateralThreshold (r:1 w:0)
+	// Storage: Fee IssueFee (r:1 w:0)
+	// Storage: Issue IssuePeriod (r:1 w:0)
+	// Storage: Issue IssueRequests (r:0 w:1)
+	// Storage: VaultRegistry Vaults (r:n w:0)
+	// Storage: Staking Nonce (r:n w:0)
+	// Storage: Staking TotalCurrentStake (r:n w:0)
+	// Estimate: request_issue (500_076_000) plus, per scanned candidate vault, accept_new_issues
+	// (17_480_000), which decodes and checks one vault as the candidate scan does
+	fn request_issue_with_auto_vault(n: u32, ) -> Weight {
+		(500_076_000 as Weight)
+			.saturating_add((17_480_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(16 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Oracle Aggregate (r:1 w:0)
+	// Storage: VaultRegistry SecureCollateralThreshold (r:1 w:0)
+	// Storage: Staking Nonce (r:1 w:0)
+	// Storage: Staking TotalCurrentStake (r:1 w:0)
+	// Storage: Fee IssueGriefingCollateral (r:1 w:0)
+	// Storage: BTCRelay StartBlockHeight (r:1 w:0)
+	// Storage: BTCRelay StableBitcoinConfirmations (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: Tokens Accounts (r:1 w:1)
+	// Storage: Issue IssueBtcDustValue (r:1 w:0)
+	// Storage: Fee IssueFee (r:1 w:0)
+	// Storage: Security Nonce (r:1 w:1)
+	// Storage: System ParentHash (r:1 w:0)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: Issue IssuePeriod (r:1 w:0)
+	// Storage: Issue IssueRequests (r:0 w:1)
+	// Storage: Issue IssueGroups (r:0 w:1)
+	// Estimate: `n` times request_issue (500_076_000) for the requests of the group plus, per
+	// scanned candidate vault (m), accept_new_issues (17_480_000) for decoding and checking it
+	fn request_issue_group(n: u32, m: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((500_076_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((17_480_000 as Weight).saturating_mul(m as Weight))
+			.saturating_add(RocksDbWeight::get().reads(13 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((10 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(m as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(n as Weight)))
+	}
 	// Storage: Security ParachainStatus (r:1 w:0)
 	// Storage: Issue IssueRequests (r:1 w:1)
 	// Storage: Issue IssuePeriod (r:1 w:0)
@@ -152,10 +308,45 @@ impl WeightInfo for () {
 	// Storage: Tokens TotalIssuance (r:1 w:1)
 	// Storage: System Account (r:1 w:1)
 	// Storage: Rewards TotalRewards (r:1 w:1)
+	// Storage: BTCRelay SettledRequests (r:0 w:1)
+	// Estimate: execution time as measured before payments were recorded; recording adds one
+	// append to SettledRequests, whose BlockHeaders existence check hits the header read by the
+	// inclusion proof
 	fn execute_issue() -> Weight {
 		(203_467_000 as Weight)
 			.saturating_add(RocksDbWeight::get().reads(19 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(11 as Weight))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: Issue IssueRequests (r:1 w:1)
+	// Storage: Issue IssuePeriod (r:1 w:0)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: BTCRelay DisableInclusionCheck (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: BTCRelay Chains (r:1 w:0)
+	// Storage: BTCRelay BlockHeaders (r:1 w:0)
+	// Storage: BTCRelay StableBitcoinConfirmations (r:1 w:0)
+	// Storage: BTCRelay StableParachainConfirmations (r:1 w:0)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Rewards Stake (r:1 w:1)
+	// Storage: Rewards TotalStake (r:1 w:1)
+	// Storage: Rewards RewardTally (r:1 w:1)
+	// Storage: Rewards RewardPerToken (r:1 w:1)
+	// Storage: Tokens Accounts (r:1 w:1)
+	// Storage: Tokens TotalIssuance (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Rewards TotalRewards (r:1 w:1)
+	// Storage: BTCRelay SettledRequests (r:0 w:1)
+	// Estimate: execute_issue (203_467_000) with its payment verification replaced by `n` times
+	// verify_and_validate_transaction (66_727_000); each payment also checks that its block is stored
+	// and appends to SettledRequests
+	fn execute_issue_with_payments(n: u32, ) -> Weight {
+		(136_740_000 as Weight)
+			.saturating_add((66_727_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(18 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((5 as Weight).saturating_mul(n as Weight)))
 			.saturating_add(RocksDbWeight::get().writes(10 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
 	}
 	// Storage: Security ParachainStatus (r:1 w:0)
 	// Storage: Issue IssueRequests (r:1 w:1)
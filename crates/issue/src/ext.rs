@@ -3,10 +3,10 @@ use mocktopus::macros::mockable;
 
 #[cfg_attr(test, mockable)]
 pub(crate) mod btc_relay {
-    use bitcoin::types::{MerkleProof, Transaction, Value};
-    use btc_relay::BtcAddress;
+    use bitcoin::types::{H256Le, MerkleProof, Transaction, Value};
+    use btc_relay::{BtcAddress, PaymentVerification, SettledRequest};
     use frame_support::dispatch::DispatchError;
-    use sp_std::convert::TryFrom;
+    use sp_std::{convert::TryFrom, vec::Vec};
 
     pub fn get_and_verify_issue_payment<T: crate::Config, V: TryFrom<Value>>(
         merkle_proof: MerkleProof,
@@ -16,6 +16,22 @@ pub(crate) mod btc_relay {
         <btc_relay::Pallet<T>>::get_and_verify_issue_payment(merkle_proof, transaction, recipient_btc_address)
     }
 
+    pub fn get_and_verify_issue_payments<T: crate::Config, V: TryFrom<Value>>(
+        payments: Vec<(MerkleProof, Transaction)>,
+        recipient_btc_address: BtcAddress,
+    ) -> Result<(BtcAddress, V), DispatchError> {
+        <btc_relay::Pallet<T>>::get_and_verify_issue_payments(payments, recipient_btc_address)
+    }
+
+    pub fn dry_run_issue_payment<T: crate::Config, V: TryFrom<Value> + Clone>(
+        raw_merkle_proof: &[u8],
+        raw_tx: &[u8],
+        recipient_btc_address: BtcAddress,
+        expected_amount: V,
+    ) -> PaymentVerification<V> {
+        <btc_relay::Pallet<T>>::dry_run_issue_payment(raw_merkle_proof, raw_tx, recipient_btc_address, expected_amount)
+    }
+
     pub fn get_best_block_height<T: crate::Config>() -> u32 {
         <btc_relay::Pallet<T>>::get_best_block_height()
     }
@@ -39,6 +55,10 @@ pub(crate) mod btc_relay {
     ) -> Result<bool, DispatchError> {
         <btc_relay::Pallet<T>>::has_request_expired(opentime, btc_open_height, period)
     }
+
+    pub fn record_settled_request<T: crate::Config>(block_hash: H256Le, request: SettledRequest) {
+        <btc_relay::Pallet<T>>::record_settled_request(block_hash, request)
+    }
 }
 
 #[cfg_attr(test, mockable)]
@@ -46,8 +66,9 @@ pub(crate) mod vault_registry {
     use btc_relay::BtcAddress;
     use frame_support::dispatch::{DispatchError, DispatchResult};
     use sp_core::H256;
+    use sp_std::vec::Vec;
     use vault_registry::{
-        types::{CurrencySource, DefaultVault},
+        types::{CurrencyId, CurrencySource, DefaultVault},
         Amount,
     };
 
@@ -69,6 +90,21 @@ pub(crate) mod vault_registry {
         <vault_registry::Pallet<T>>::get_active_vault_from_id(vault_id)
     }
 
+    pub fn get_vaults_for_issue_group<T: crate::Config>(
+        currency_id: CurrencyId<T>,
+        random: H256,
+    ) -> Result<Vec<(T::AccountId, Amount<T>)>, DispatchError> {
+        <vault_registry::Pallet<T>>::get_vaults_for_issue_group(currency_id, random)
+    }
+
+    pub fn select_vault_for_issue<T: crate::Config>(
+        amount: &Amount<T>,
+        currency_id: CurrencyId<T>,
+        random: H256,
+    ) -> Result<T::AccountId, DispatchError> {
+        <vault_registry::Pallet<T>>::select_vault_for_issue(amount, currency_id, random)
+    }
+
     pub fn try_increase_to_be_issued_tokens<T: crate::Config>(
         vault_id: &T::AccountId,
         amount: &Amount<T>,
@@ -27,18 +27,26 @@ mod ext;
 pub mod types;
 
 #[doc(inline)]
-pub use crate::types::{DefaultIssueRequest, IssueRequest, IssueRequestStatus};
+pub use crate::types::{DefaultIssueRequest, IssueGroupStatus, IssueRequest, IssueRequestStatus};
 use types::IssueRequestExt;
 
 use crate::types::{BalanceOf, Collateral, Version, Wrapped};
-use btc_relay::{BtcAddress, BtcPublicKey};
+use btc_relay::{BtcAddress, BtcPublicKey, PaymentVerification, SettledRequest};
 use currency::Amount;
-use frame_support::{dispatch::DispatchError, ensure, traits::Get, transactional};
+use frame_support::{
+    dispatch::{DispatchError, DispatchResult},
+    ensure,
+    traits::Get,
+    transactional,
+};
 use frame_system::{ensure_root, ensure_signed};
 pub use pallet::*;
 use sp_core::H256;
-use sp_std::vec::Vec;
-use vault_registry::{CurrencySource, VaultStatus};
+use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
+use vault_registry::{types::CurrencyId, CurrencySource, VaultStatus, MAX_VAULT_SELECTION_CANDIDATES};
+
+/// The maximum number of vaults an issue group can be split across.
+pub const MAX_ISSUE_GROUP_SIZE: u32 = 10;
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -84,6 +92,8 @@ pub mod pallet {
         ExecuteIssue(H256, T::AccountId, Wrapped<T>, T::AccountId, Wrapped<T>),
         // [issue_id, requester, griefing_collateral]
         CancelIssue(H256, T::AccountId, Collateral<T>),
+        // [group_id, requester, issue_ids]
+        RequestIssueGroup(H256, T::AccountId, Vec<H256>),
     }
 
     #[pallet::error]
@@ -102,6 +112,12 @@ pub mod pallet {
         ArithmeticOverflow,
         InvalidExecutor,
         AmountBelowDustAmount,
+        /// Transaction has already been counted towards this issue request
+        PaymentAlreadyCounted,
+        /// The requested amount cannot be covered by the issuable tokens of at most
+        /// `MAX_ISSUE_GROUP_SIZE` vaults
+        InsufficientVaultCapacity,
+        IssueGroupNotFound,
     }
 
     #[pallet::hooks]
@@ -125,6 +141,11 @@ pub mod pallet {
     #[pallet::storage]
     pub(super) type IssueBtcDustValue<T: Config> = StorageValue<_, Wrapped<T>, ValueQuery>;
 
+    /// Issue requests that were opened together against several vaults, mapping the id of
+    /// the group to the ids of its issue requests.
+    #[pallet::storage]
+    pub(super) type IssueGroups<T: Config> = StorageMap<_, Blake2_128Concat, H256, Vec<H256>, OptionQuery>;
+
     #[pallet::type_value]
     pub(super) fn DefaultForStorageVersion() -> Version {
         Version::V0
@@ -187,6 +208,52 @@ pub mod pallet {
             Ok(().into())
         }
 
+        /// Request the issuance of tokens from a vault selected by the parachain. The vault is
+        /// chosen randomly amongst those that can issue `amount`, weighted by their issuable tokens.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - sender of the transaction
+        /// * `amount` - amount of BTC the user wants to convert to issued tokens. Note that the
+        /// amount of issued tokens received will be less, because a fee is subtracted.
+        /// * `currency_id` - collateral currency of the vault to select
+        /// * `griefing_collateral` - amount of collateral
+        #[pallet::weight(<T as Config>::WeightInfo::request_issue_with_auto_vault(MAX_VAULT_SELECTION_CANDIDATES))]
+        #[transactional]
+        pub fn request_issue_with_auto_vault(
+            origin: OriginFor<T>,
+            #[pallet::compact] amount: Wrapped<T>,
+            currency_id: CurrencyId<T>,
+            #[pallet::compact] griefing_collateral: Collateral<T>,
+        ) -> DispatchResultWithPostInfo {
+            let requester = ensure_signed(origin)?;
+            Self::_request_issue_with_auto_vault(requester, amount, currency_id, griefing_collateral)?;
+            Ok(().into())
+        }
+
+        /// Request the issuance of tokens from several vaults at once, for amounts that exceed
+        /// the issuable tokens of any single vault. Vaults are used in descending order of their
+        /// issuable tokens, and one issue request is opened against each of them.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - sender of the transaction
+        /// * `amount` - total amount of BTC the user wants to convert to issued tokens
+        /// * `currency_id` - collateral currency of the vaults to select
+        /// * `griefing_collateral` - maximum amount of collateral to lock over all requests
+        #[pallet::weight(<T as Config>::WeightInfo::request_issue_group(MAX_ISSUE_GROUP_SIZE, MAX_VAULT_SELECTION_CANDIDATES))]
+        #[transactional]
+        pub fn request_issue_group(
+            origin: OriginFor<T>,
+            #[pallet::compact] amount: Wrapped<T>,
+            currency_id: CurrencyId<T>,
+            #[pallet::compact] griefing_collateral: Collateral<T>,
+        ) -> DispatchResultWithPostInfo {
+            let requester = ensure_signed(origin)?;
+            Self::_request_issue_group(requester, amount, currency_id, griefing_collateral)?;
+            Ok(().into())
+        }
+
         /// Finalize the issuance of tokens
         ///
         /// # Arguments
@@ -209,6 +276,26 @@ pub mod pallet {
             Ok(().into())
         }
 
+        /// Finalize the issuance of tokens using several Bitcoin transactions, whose payments
+        /// to the deposit address are summed
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - sender of the transaction
+        /// * `issue_id` - identifier of issue request as output from request_issue
+        /// * `payments` - raw merkle proof and raw transaction bytes of each payment
+        #[pallet::weight(<T as Config>::WeightInfo::execute_issue_with_payments(payments.len() as u32))]
+        #[transactional]
+        pub fn execute_issue_with_payments(
+            origin: OriginFor<T>,
+            issue_id: H256,
+            payments: Vec<(Vec<u8>, Vec<u8>)>,
+        ) -> DispatchResultWithPostInfo {
+            let executor = ensure_signed(origin)?;
+            Self::_execute_issue_with_payments(executor, issue_id, payments)?;
+            Ok(().into())
+        }
+
         /// Cancel the issuance of tokens if expired
         ///
         /// # Arguments
@@ -244,6 +331,90 @@ pub mod pallet {
 // "Internal" functions, callable by code.
 #[cfg_attr(test, mockable)]
 impl<T: Config> Pallet<T> {
+    /// Requests CBA issuance from a randomly selected vault, returns unique tracking ID.
+    fn _request_issue_with_auto_vault(
+        requester: T::AccountId,
+        amount_requested: Wrapped<T>,
+        currency_id: CurrencyId<T>,
+        griefing_collateral: Collateral<T>,
+    ) -> Result<H256, DispatchError> {
+        let amount = Amount::new(amount_requested, T::GetWrappedCurrencyId::get());
+        let random = ext::security::get_secure_id::<T>(&requester);
+        let vault_id = ext::vault_registry::select_vault_for_issue::<T>(&amount, currency_id, random)?;
+        Self::_request_issue(requester, amount_requested, vault_id, griefing_collateral)
+    }
+
+    /// Requests CBA issuance from as many vaults as needed to cover the requested amount,
+    /// returns the id of the group of issue requests.
+    fn _request_issue_group(
+        requester: T::AccountId,
+        amount_requested: Wrapped<T>,
+        currency_id: CurrencyId<T>,
+        griefing_collateral: Collateral<T>,
+    ) -> Result<H256, DispatchError> {
+        let amount_requested = Amount::new(amount_requested, T::GetWrappedCurrencyId::get());
+        let griefing_collateral = Amount::new(griefing_collateral, T::GetGriefingCollateralCurrencyId::get());
+
+        ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+
+        let dust_value = Self::issue_btc_dust_value();
+        ensure!(amount_requested.ge(&dust_value)?, Error::<T>::AmountBelowDustAmount);
+
+        // reserve the capacity of the vaults with the most issuable tokens first
+        let random = ext::security::get_secure_id::<T>(&requester);
+        let mut remaining = amount_requested;
+        let mut allocations = Vec::new();
+        for (vault_id, issuable_tokens) in ext::vault_registry::get_vaults_for_issue_group::<T>(currency_id, random)? {
+            if remaining.is_zero() || allocations.len() >= MAX_ISSUE_GROUP_SIZE as usize {
+                break;
+            }
+            let mut amount = remaining.min(&issuable_tokens)?;
+            let rest = remaining.checked_sub(&amount)?;
+            if !rest.is_zero() && rest.lt(&dust_value)? {
+                // leave enough for the next vault to open a request above the dust value
+                amount = remaining.saturating_sub(&dust_value)?;
+            }
+            // every request of the group must be above the dust value
+            if amount.lt(&dust_value)? {
+                continue;
+            }
+            remaining = remaining.checked_sub(&amount)?;
+            allocations.push((vault_id, amount));
+        }
+        ensure!(remaining.is_zero(), Error::<T>::InsufficientVaultCapacity);
+
+        // split the griefing collateral according to the amount issued by each vault
+        let mut total_griefing_collateral = Amount::zero(T::GetGriefingCollateralCurrencyId::get());
+        let mut requests = Vec::with_capacity(allocations.len());
+        for (vault_id, amount) in allocations {
+            let amount_collateral = amount.convert_to(T::GetGriefingCollateralCurrencyId::get())?;
+            let vault_griefing_collateral = ext::fee::get_issue_griefing_collateral::<T>(&amount_collateral)?;
+            total_griefing_collateral = total_griefing_collateral.checked_add(&vault_griefing_collateral)?;
+            requests.push((vault_id, amount, vault_griefing_collateral));
+        }
+        ensure!(
+            griefing_collateral.ge(&total_griefing_collateral)?,
+            Error::<T>::InsufficientCollateral
+        );
+
+        let mut issue_ids = Vec::with_capacity(requests.len());
+        for (vault_id, amount, vault_griefing_collateral) in requests {
+            let issue_id = Self::_request_issue(
+                requester.clone(),
+                amount.amount(),
+                vault_id,
+                vault_griefing_collateral.amount(),
+            )?;
+            issue_ids.push(issue_id);
+        }
+
+        let group_id = ext::security::get_secure_id::<T>(&requester);
+        IssueGroups::<T>::insert(group_id, issue_ids.clone());
+        Self::deposit_event(<Event<T>>::RequestIssueGroup(group_id, requester, issue_ids));
+
+        Ok(group_id)
+    }
+
     /// Requests CBA issuance, returns unique tracking ID.
     fn _request_issue(
         requester: T::AccountId,
@@ -333,13 +504,58 @@ impl<T: Config> Pallet<T> {
         raw_merkle_proof: Vec<u8>,
         raw_tx: Vec<u8>,
     ) -> Result<(), DispatchError> {
+        let issue = Self::get_executable_issue_request(&issue_id)?;
+
+        let transaction = ext::btc_relay::parse_transaction::<T>(&raw_tx)?;
+        let merkle_proof = ext::btc_relay::parse_merkle_proof::<T>(&raw_merkle_proof)?;
+        let block_hash = merkle_proof.block_header.hash;
+
+        let (refund_address, amount_transferred) = ext::btc_relay::get_and_verify_issue_payment::<T, Wrapped<T>>(
+            merkle_proof,
+            transaction,
+            issue.btc_address,
+        )?;
+        ext::btc_relay::record_settled_request::<T>(block_hash, SettledRequest::Issue(issue_id));
+        Self::complete_issue(executor, issue_id, issue, refund_address, amount_transferred)
+    }
+
+    /// Completes CBA issuance using the sum of several payments, each transaction being counted
+    /// at most once.
+    fn _execute_issue_with_payments(
+        executor: T::AccountId,
+        issue_id: H256,
+        raw_payments: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Result<(), DispatchError> {
+        let issue = Self::get_executable_issue_request(&issue_id)?;
+
+        let mut payments = Vec::with_capacity(raw_payments.len());
+        let mut block_hashes = Vec::with_capacity(raw_payments.len());
+        let mut tx_ids = BTreeSet::new();
+        for (raw_merkle_proof, raw_tx) in raw_payments {
+            let transaction = ext::btc_relay::parse_transaction::<T>(&raw_tx)?;
+            let merkle_proof = ext::btc_relay::parse_merkle_proof::<T>(&raw_merkle_proof)?;
+            // the request is completed by this call, so transactions only need to be unique within it
+            ensure!(tx_ids.insert(transaction.tx_id()), Error::<T>::PaymentAlreadyCounted);
+            if !block_hashes.contains(&merkle_proof.block_header.hash) {
+                block_hashes.push(merkle_proof.block_header.hash);
+            }
+            payments.push((merkle_proof, transaction));
+        }
+
+        let (refund_address, amount_transferred) =
+            ext::btc_relay::get_and_verify_issue_payments::<T, Wrapped<T>>(payments, issue.btc_address)?;
+        for block_hash in block_hashes {
+            ext::btc_relay::record_settled_request::<T>(block_hash, SettledRequest::Issue(issue_id));
+        }
+        Self::complete_issue(executor, issue_id, issue, refund_address, amount_transferred)
+    }
+
+    /// Fetches an issue request that can still be executed.
+    fn get_executable_issue_request(issue_id: &H256) -> Result<DefaultIssueRequest<T>, DispatchError> {
         // Check that Parachain is RUNNING
         ext::security::ensure_parachain_status_not_shutdown::<T>()?;
 
-        let mut issue = Self::get_issue_request_from_id(&issue_id)?;
-        let mut maybe_refund_id = None;
-        // allow anyone to complete issue request
-        let requester = issue.requester.clone();
+        let issue = Self::get_issue_request_from_id(issue_id)?;
 
         // only executable before the request has expired
         ensure!(
@@ -351,13 +567,22 @@ impl<T: Config> Pallet<T> {
             Error::<T>::CommitPeriodExpired
         );
 
-        let transaction = ext::btc_relay::parse_transaction::<T>(&raw_tx)?;
-        let merkle_proof = ext::btc_relay::parse_merkle_proof::<T>(&raw_merkle_proof)?;
-        let (refund_address, amount_transferred) = ext::btc_relay::get_and_verify_issue_payment::<T, Wrapped<T>>(
-            merkle_proof,
-            transaction,
-            issue.btc_address,
-        )?;
+        Ok(issue)
+    }
+
+    /// Mints the issued tokens for the transferred amount, adjusting the request for under- and
+    /// overpayments.
+    fn complete_issue(
+        executor: T::AccountId,
+        issue_id: H256,
+        mut issue: DefaultIssueRequest<T>,
+        refund_address: BtcAddress,
+        amount_transferred: Wrapped<T>,
+    ) -> Result<(), DispatchError> {
+        let mut maybe_refund_id = None;
+        // allow anyone to complete issue request
+        let requester = issue.requester.clone();
+
         let amount_transferred = Amount::new(amount_transferred, T::GetWrappedCurrencyId::get());
 
         let expected_total_amount = issue.amount().checked_add(&issue.fee())?;
@@ -507,6 +732,63 @@ impl<T: Config> Pallet<T> {
             .collect::<Vec<_>>()
     }
 
+    /// Dry-run the payment verification of `execute_issue`, reporting the payment that was found
+    /// and the first check that failed, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `issue_id` - identifier of the issue request
+    /// * `raw_merkle_proof` - raw merkle proof of the payment
+    /// * `raw_tx` - raw bitcoin transaction of the payment
+    pub fn verify_issue_payment(
+        issue_id: H256,
+        raw_merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+    ) -> Result<PaymentVerification<Wrapped<T>>, DispatchError> {
+        let issue = Self::get_issue_request_from_id(&issue_id)?;
+        let expected_total_amount = issue.amount().checked_add(&issue.fee())?;
+        Ok(ext::btc_relay::dry_run_issue_payment::<T, _>(
+            &raw_merkle_proof,
+            &raw_tx,
+            issue.btc_address,
+            expected_total_amount.amount(),
+        ))
+    }
+
+    /// Get the aggregate status of a group of issue requests
+    ///
+    /// # Arguments
+    ///
+    /// * `group_id` - identifier of the issue group
+    pub fn get_issue_group_status(group_id: H256) -> Result<IssueGroupStatus<Wrapped<T>>, DispatchError> {
+        let issue_ids = IssueGroups::<T>::get(group_id).ok_or(Error::<T>::IssueGroupNotFound)?;
+
+        let mut amount = Amount::zero(T::GetWrappedCurrencyId::get());
+        let mut amount_issued = Amount::zero(T::GetWrappedCurrencyId::get());
+        let (mut pending, mut completed, mut cancelled) = (0, 0, 0);
+        for issue_id in issue_ids.iter() {
+            let request = IssueRequests::<T>::try_get(issue_id).or(Err(Error::<T>::IssueIdNotFound))?;
+            amount = amount.checked_add(&request.amount())?;
+            match request.status {
+                IssueRequestStatus::Pending => pending += 1,
+                IssueRequestStatus::Completed(_) => {
+                    completed += 1;
+                    amount_issued = amount_issued.checked_add(&request.amount())?;
+                }
+                IssueRequestStatus::Cancelled => cancelled += 1,
+            }
+        }
+
+        Ok(IssueGroupStatus {
+            issue_ids,
+            amount: amount.amount(),
+            amount_issued: amount_issued.amount(),
+            pending,
+            completed,
+            cancelled,
+        })
+    }
+
     pub fn get_issue_request_from_id(issue_id: &H256) -> Result<DefaultIssueRequest<T>, DispatchError> {
         let request = IssueRequests::<T>::try_get(issue_id).or(Err(Error::<T>::IssueIdNotFound))?;
 
@@ -3,7 +3,7 @@ use crate::{ext, mock::*, Event};
 use bitcoin::types::{MerkleProof, Transaction};
 use btc_relay::{BtcAddress, BtcPublicKey};
 use currency::Amount;
-use frame_support::{assert_noop, assert_ok, dispatch::DispatchError};
+use frame_support::{assert_err, assert_noop, assert_ok, dispatch::DispatchError};
 use mocktopus::mocking::*;
 use sp_arithmetic::FixedU128;
 use sp_core::{H160, H256};
@@ -150,6 +150,134 @@ fn test_request_issue_succeeds() {
     })
 }
 
+#[test]
+fn test_request_issue_with_auto_vault_succeeds() {
+    run_test(|| {
+        let origin = ALICE;
+        let amount: Balance = 3;
+        let issue_fee = 1;
+        let issue_griefing_collateral = 20;
+
+        ext::vault_registry::select_vault_for_issue::<Test>.mock_safe(move |amount_requested, currency_id, _| {
+            assert_eq!(amount_requested, &wrapped(amount));
+            assert_eq!(currency_id, DEFAULT_TESTING_CURRENCY);
+            MockResult::Return(Ok(BOB))
+        });
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_zero_vault(BOB))));
+        ext::vault_registry::ensure_not_banned::<Test>.mock_safe(|_| MockResult::Return(Ok(())));
+        ext::vault_registry::try_increase_to_be_issued_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::register_deposit_address::<Test>
+            .mock_safe(|_, _| MockResult::Return(Ok(BtcAddress::default())));
+        ext::fee::get_issue_fee::<Test>.mock_safe(move |_| MockResult::Return(Ok(wrapped(issue_fee))));
+        ext::fee::get_issue_griefing_collateral::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(griefing(issue_griefing_collateral))));
+
+        let issue_id =
+            Issue::_request_issue_with_auto_vault(origin, amount, DEFAULT_TESTING_CURRENCY, issue_griefing_collateral)
+                .unwrap();
+
+        let request = Issue::get_issue_request_from_id(&issue_id).unwrap();
+        assert_eq!(request.vault, BOB);
+        assert_eq!(request.requester, origin);
+    })
+}
+
+fn setup_issue_group() {
+    ext::vault_registry::get_vaults_for_issue_group::<Test>.mock_safe(|currency_id, _| {
+        assert_eq!(currency_id, DEFAULT_TESTING_CURRENCY);
+        MockResult::Return(Ok(vec![(BOB, wrapped(6)), (CAROL, wrapped(5))]))
+    });
+    ext::vault_registry::get_active_vault_from_id::<Test>
+        .mock_safe(|vault_id| MockResult::Return(Ok(init_zero_vault(*vault_id))));
+    ext::vault_registry::ensure_not_banned::<Test>.mock_safe(|_| MockResult::Return(Ok(())));
+    ext::vault_registry::try_increase_to_be_issued_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+    ext::vault_registry::register_deposit_address::<Test>
+        .mock_safe(|_, _| MockResult::Return(Ok(BtcAddress::default())));
+    ext::fee::get_issue_fee::<Test>.mock_safe(|_| MockResult::Return(Ok(wrapped(1))));
+    ext::fee::get_issue_griefing_collateral::<Test>.mock_safe(|_| MockResult::Return(Ok(griefing(2))));
+}
+
+#[test]
+fn test_request_issue_group_succeeds() {
+    run_test(|| {
+        setup_issue_group();
+
+        let group_id = Issue::_request_issue_group(ALICE, 10, DEFAULT_TESTING_CURRENCY, 4).unwrap();
+
+        let status = Issue::get_issue_group_status(group_id).unwrap();
+        assert_eq!(status.issue_ids.len(), 2);
+        assert_eq!(status.amount, 8);
+        assert_eq!(status.amount_issued, 0);
+        assert_eq!(status.pending, 2);
+
+        // the vault with the most issuable tokens is used first
+        let first = Issue::get_issue_request_from_id(&status.issue_ids[0]).unwrap();
+        assert_eq!(first.vault, BOB);
+        assert_eq!(first.amount, 5);
+        assert_eq!(first.griefing_collateral, 2);
+        let second = Issue::get_issue_request_from_id(&status.issue_ids[1]).unwrap();
+        assert_eq!(second.vault, CAROL);
+        assert_eq!(second.amount, 3);
+        assert_eq!(second.griefing_collateral, 2);
+
+        assert!(System::events()
+            .iter()
+            .any(|a| a.event == TestEvent::Issue(Event::RequestIssueGroup(group_id, ALICE, status.issue_ids.clone()))));
+    })
+}
+
+#[test]
+fn test_request_issue_group_leaves_no_remainder_below_dust_value() {
+    run_test(|| {
+        setup_issue_group();
+        ext::vault_registry::get_vaults_for_issue_group::<Test>
+            .mock_safe(|_, _| MockResult::Return(Ok(vec![(BOB, wrapped(9)), (CAROL, wrapped(5))])));
+        crate::IssueBtcDustValue::<Test>::set(3);
+
+        assert_noop!(
+            Issue::request_issue_group(Origin::signed(ALICE), 2, DEFAULT_TESTING_CURRENCY, 4),
+            TestError::AmountBelowDustAmount
+        );
+
+        // the first vault could cover all but 1, which is below the dust value
+        let group_id = Issue::_request_issue_group(ALICE, 10, DEFAULT_TESTING_CURRENCY, 4).unwrap();
+
+        let status = Issue::get_issue_group_status(group_id).unwrap();
+        assert_eq!(status.issue_ids.len(), 2);
+        let first = Issue::get_issue_request_from_id(&status.issue_ids[0]).unwrap();
+        assert_eq!(first.vault, BOB);
+        assert_eq!(first.amount + first.fee, 7);
+        let second = Issue::get_issue_request_from_id(&status.issue_ids[1]).unwrap();
+        assert_eq!(second.vault, CAROL);
+        assert_eq!(second.amount + second.fee, 3);
+    })
+}
+
+#[test]
+fn test_request_issue_group_fails_with_insufficient_vault_capacity() {
+    run_test(|| {
+        setup_issue_group();
+
+        assert_noop!(
+            Issue::request_issue_group(Origin::signed(ALICE), 12, DEFAULT_TESTING_CURRENCY, 4),
+            TestError::InsufficientVaultCapacity
+        );
+    })
+}
+
+#[test]
+fn test_request_issue_group_fails_with_insufficient_griefing_collateral() {
+    run_test(|| {
+        setup_issue_group();
+
+        assert_noop!(
+            Issue::request_issue_group(Origin::signed(ALICE), 10, DEFAULT_TESTING_CURRENCY, 3),
+            TestError::InsufficientCollateral
+        );
+    })
+}
+
 #[test]
 fn test_execute_issue_not_found_fails() {
     run_test(|| {
@@ -183,6 +311,10 @@ fn test_execute_issue_succeeds() {
 
         let execute_issue_event = TestEvent::Issue(Event::ExecuteIssue(issue_id, ALICE, 3, BOB, 1));
         assert!(System::events().iter().any(|a| a.event == execute_issue_event));
+        assert_eq!(
+            <btc_relay::Pallet<Test>>::settled_requests(dummy_merkle_proof().block_header.hash),
+            vec![btc_relay::SettledRequest::Issue(issue_id)]
+        );
 
         assert_noop!(cancel_issue(ALICE, &issue_id), TestError::IssueCompleted);
     })
@@ -270,6 +402,65 @@ fn test_execute_issue_refund_succeeds() {
         }
     })
 }
+#[test]
+fn test_execute_issue_with_payments_succeeds() {
+    run_test(|| {
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_zero_vault(BOB))));
+        ext::vault_registry::issue_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::is_vault_liquidated::<Test>.mock_safe(|_| MockResult::Return(Ok(false)));
+        ext::fee::get_issue_fee::<Test>.mock_safe(move |_| MockResult::Return(Ok(wrapped(1))));
+
+        let issue_id = request_issue_ok(ALICE, 3, BOB, 20);
+        <security::Pallet<Test>>::set_active_block_number(5);
+
+        ext::btc_relay::parse_merkle_proof::<Test>.mock_safe(|_| MockResult::Return(Ok(dummy_merkle_proof())));
+        // derive distinct transactions from the raw bytes
+        ext::btc_relay::parse_transaction::<Test>.mock_safe(|raw_tx| {
+            MockResult::Return(Ok(Transaction {
+                version: raw_tx[0] as i32,
+                ..Default::default()
+            }))
+        });
+        ext::btc_relay::get_and_verify_issue_payments::<Test, Balance>.mock_safe(|payments, _| {
+            assert_eq!(payments.len(), 2);
+            MockResult::Return(Ok((BtcAddress::P2SH(H160::zero()), 3)))
+        });
+
+        assert_ok!(Issue::_execute_issue_with_payments(
+            ALICE,
+            issue_id,
+            vec![(vec![0u8; 100], vec![1u8; 100]), (vec![0u8; 100], vec![2u8; 100])],
+        ));
+
+        let execute_issue_event = TestEvent::Issue(Event::ExecuteIssue(issue_id, ALICE, 3, BOB, 1));
+        assert!(System::events().iter().any(|a| a.event == execute_issue_event));
+    })
+}
+
+#[test]
+fn test_execute_issue_with_duplicate_payments_fails() {
+    run_test(|| {
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_zero_vault(BOB))));
+
+        let issue_id = request_issue_ok(ALICE, 3, BOB, 20);
+        <security::Pallet<Test>>::set_active_block_number(5);
+
+        ext::btc_relay::parse_merkle_proof::<Test>.mock_safe(|_| MockResult::Return(Ok(dummy_merkle_proof())));
+        ext::btc_relay::parse_transaction::<Test>.mock_safe(|_| MockResult::Return(Ok(Transaction::default())));
+
+        assert_err!(
+            Issue::_execute_issue_with_payments(
+                ALICE,
+                issue_id,
+                vec![(vec![0u8; 100], vec![0u8; 100]), (vec![0u8; 100], vec![0u8; 100])],
+            ),
+            TestError::PaymentAlreadyCounted
+        );
+    })
+}
+
 #[test]
 fn test_cancel_issue_not_found_fails() {
     run_test(|| {
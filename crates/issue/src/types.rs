@@ -1,7 +1,7 @@
 use codec::{Decode, Encode};
 use currency::Amount;
 use frame_support::traits::Get;
-pub use primitives::issue::{IssueRequest, IssueRequestStatus};
+pub use primitives::issue::{IssueGroupStatus, IssueRequest, IssueRequestStatus};
 
 use crate::Config;
 
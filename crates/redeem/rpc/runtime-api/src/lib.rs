@@ -3,18 +3,28 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::Codec;
+use frame_support::dispatch::DispatchError;
 use sp_std::vec::Vec;
 
 sp_api::decl_runtime_apis! {
-    pub trait RedeemApi<AccountId, H256, RedeemRequest> where
+    pub trait RedeemApi<AccountId, H256, RedeemRequest, RedeemPaymentVerification> where
         AccountId: Codec,
         H256: Codec,
         RedeemRequest: Codec,
+        RedeemPaymentVerification: Codec,
     {
         /// Get all redeem requests for a particular account
         fn get_redeem_requests(account_id: AccountId) -> Vec<(H256, RedeemRequest)>;
 
         /// Get all redeem requests for a particular vault
         fn get_vault_redeem_requests(account_id: AccountId) -> Vec<(H256, RedeemRequest)>;
+
+        /// Dry-run the payment verification of the redeem request, reporting the payment that was
+        /// found and the first check that failed, if any
+        fn verify_redeem_payment(
+            redeem_id: H256,
+            raw_merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+        ) -> Result<RedeemPaymentVerification, DispatchError>;
     }
 }
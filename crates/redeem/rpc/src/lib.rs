@@ -5,14 +5,14 @@ use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
 use jsonrpc_derive::rpc;
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
-use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT, DispatchError};
 use std::sync::Arc;
 
 pub use self::gen_client::Client as RedeemClient;
 pub use module_redeem_rpc_runtime_api::RedeemApi as RedeemRuntimeApi;
 
 #[rpc]
-pub trait RedeemApi<BlockHash, AccountId, H256, RedeemRequest> {
+pub trait RedeemApi<BlockHash, AccountId, H256, RedeemRequest, RedeemPaymentVerification> {
     #[rpc(name = "redeem_getRedeemRequests")]
     fn get_redeem_requests(&self, account_id: AccountId, at: Option<BlockHash>) -> Result<Vec<(H256, RedeemRequest)>>;
 
@@ -22,6 +22,15 @@ pub trait RedeemApi<BlockHash, AccountId, H256, RedeemRequest> {
         account_id: AccountId,
         at: Option<BlockHash>,
     ) -> Result<Vec<(H256, RedeemRequest)>>;
+
+    #[rpc(name = "redeem_verifyRedeemPayment")]
+    fn verify_redeem_payment(
+        &self,
+        redeem_id: H256,
+        raw_merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+        at: Option<BlockHash>,
+    ) -> Result<std::result::Result<RedeemPaymentVerification, DispatchError>>;
 }
 
 /// A struct that implements the [`RedeemApi`].
@@ -52,15 +61,16 @@ impl From<Error> for i64 {
     }
 }
 
-impl<C, Block, AccountId, H256, RedeemRequest> RedeemApi<<Block as BlockT>::Hash, AccountId, H256, RedeemRequest>
-    for Redeem<C, Block>
+impl<C, Block, AccountId, H256, RedeemRequest, RedeemPaymentVerification>
+    RedeemApi<<Block as BlockT>::Hash, AccountId, H256, RedeemRequest, RedeemPaymentVerification> for Redeem<C, Block>
 where
     Block: BlockT,
     C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
-    C::Api: RedeemRuntimeApi<Block, AccountId, H256, RedeemRequest>,
+    C::Api: RedeemRuntimeApi<Block, AccountId, H256, RedeemRequest, RedeemPaymentVerification>,
     AccountId: Codec,
     H256: Codec,
     RedeemRequest: Codec,
+    RedeemPaymentVerification: Codec,
 {
     fn get_redeem_requests(
         &self,
@@ -91,4 +101,22 @@ where
             data: Some(format!("{:?}", e).into()),
         })
     }
+
+    fn verify_redeem_payment(
+        &self,
+        redeem_id: H256,
+        raw_merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<std::result::Result<RedeemPaymentVerification, DispatchError>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.verify_redeem_payment(&at, redeem_id, raw_merkle_proof, raw_tx)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to verify redeem payment.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
 }
@@ -6,7 +6,7 @@ use bitcoin::{
         TransactionOutput,
     },
 };
-use btc_relay::{BtcAddress, BtcPublicKey};
+use btc_relay::{BtcAddress, BtcPublicKey, ACCEPTED_MAX_BATCH_RECIPIENTS};
 use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite};
 use frame_support::{assert_ok, traits::Get};
 use frame_system::RawOrigin;
@@ -15,7 +15,10 @@ use primitives::CurrencyId;
 use sp_core::{H160, H256, U256};
 use sp_runtime::traits::One;
 use sp_std::prelude::*;
-use vault_registry::types::{Vault, Wallet};
+use vault_registry::{
+    types::{Vault, Wallet},
+    MAX_VAULT_SELECTION_CANDIDATES,
+};
 
 // Pallets
 use crate::Pallet as Redeem;
@@ -119,12 +122,12 @@ fn mine_blocks<T: crate::Config>(end_height: u32) {
         .build();
 
     let mut prev_hash = block.header.hash;
-    for _ in 0..end_height {
+    for i in 0..end_height {
         let block = BlockBuilder::new()
             .with_previous_hash(prev_hash)
             .with_version(4)
             .with_coinbase(&BtcAddress::P2SH(H160::zero()), 50, 3)
-            .with_timestamp(1588813835)
+            .with_timestamp(1588814835 + i)
             .add_transaction(transaction.clone())
             .mine(U256::from(2).pow(254.into()))
             .unwrap();
@@ -165,6 +168,69 @@ benchmarks! {
         ));
     }: _(RawOrigin::Signed(origin), amount, btc_address, vault_id.clone())
 
+    request_redeem_with_auto_vault {
+        let n in 1 .. MAX_VAULT_SELECTION_CANDIDATES;
+
+        let origin: T::AccountId = account("Origin", 0, 0);
+        let amount = Redeem::<T>::redeem_btc_dust_value() + 1000u32.into();
+        let btc_address = BtcAddress::P2SH(H160::from([0; 20]));
+
+        initialize_oracle::<T>();
+
+        // every vault is a candidate, so that the selection scans all n of them
+        for i in 0..n {
+            let vault_id: T::AccountId = account("Vault", i, 0);
+            let vault = Vault {
+                wallet: Wallet::new(dummy_public_key()),
+                issued_tokens: amount,
+                id: vault_id.clone(),
+                ..Vault::new(Default::default(), Default::default(), T::GetGriefingCollateralCurrencyId::get())
+            };
+
+            VaultRegistry::<T>::insert_vault(
+                &vault_id,
+                vault
+            );
+        }
+
+        mint_wrapped::<T>(&origin, amount);
+
+        assert_ok!(Oracle::<T>::_set_exchange_rate(DEFAULT_TESTING_CURRENCY,
+            UnsignedFixedPoint::<T>::one()
+        ));
+    }: _(RawOrigin::Signed(origin), amount, btc_address, T::GetGriefingCollateralCurrencyId::get())
+
+    request_batch_redeem {
+        let n in 1 .. ACCEPTED_MAX_BATCH_RECIPIENTS as u32;
+
+        let origin: T::AccountId = account("Origin", 0, 0);
+        let vault_id: T::AccountId = account("Vault", 0, 0);
+        let amount = Redeem::<T>::redeem_btc_dust_value() + 1000u32.into();
+        let outputs: Vec<_> = (0..n)
+            .map(|i| (BtcAddress::P2SH(H160::from_low_u64_be(i as u64)), amount))
+            .collect();
+
+        initialize_oracle::<T>();
+
+        let vault = Vault {
+            wallet: Wallet::new(dummy_public_key()),
+            issued_tokens: amount * n.into(),
+            id: vault_id.clone(),
+            ..Vault::new(Default::default(), Default::default(), T::GetGriefingCollateralCurrencyId::get())
+        };
+
+        VaultRegistry::<T>::insert_vault(
+            &vault_id,
+            vault
+        );
+
+        mint_wrapped::<T>(&origin, amount * n.into());
+
+        assert_ok!(Oracle::<T>::_set_exchange_rate(DEFAULT_TESTING_CURRENCY,
+            UnsignedFixedPoint::<T>::one()
+        ));
+    }: _(RawOrigin::Signed(origin), outputs, vault_id.clone())
+
     liquidation_redeem {
         assert_ok!(Oracle::<T>::_set_exchange_rate(DEFAULT_TESTING_CURRENCY,
             UnsignedFixedPoint::<T>::one()
@@ -255,9 +321,97 @@ benchmarks! {
 
         let block = BlockBuilder::new()
             .with_previous_hash(block_hash)
+            .with_version(4)
+            .with_coinbase(&origin_btc_address, 50, 3)
+            .with_timestamp(1588814835)
+            .add_transaction(transaction.clone())
+            .mine(U256::from(2).pow(254.into())).unwrap();
+
+        let tx_id = transaction.tx_id();
+        let proof = block.merkle_proof(&[tx_id]).unwrap().try_format().unwrap();
+        let raw_tx = transaction.format_with(true);
+
+        let raw_block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
+        let block_header = BtcRelay::<T>::parse_raw_block_header(&raw_block_header).unwrap();
+
+        BtcRelay::<T>::store_block_header(&relayer_id, block_header).unwrap();
+        Security::<T>::set_active_block_number(Security::<T>::active_block_number() +
+BtcRelay::<T>::parachain_confirmations() + 1u32.into());
+
+        assert_ok!(Oracle::<T>::_set_exchange_rate(DEFAULT_TESTING_CURRENCY,
+            UnsignedFixedPoint::<T>::one()
+        ));
+    }: _(RawOrigin::Signed(vault_id), redeem_id, proof, raw_tx)
+
+    execute_partial_redeem {
+        let origin: T::AccountId = account("Origin", 0, 0);
+        let vault_id: T::AccountId = account("Vault", 0, 0);
+        let relayer_id: T::AccountId = account("Relayer", 0, 0);
+
+        initialize_oracle::<T>();
+
+        let origin_btc_address = BtcAddress::P2PKH(H160::zero());
+
+        let redeem_id = H256::zero();
+        let mut redeem_request = RedeemRequest::default();
+        redeem_request.vault = vault_id.clone();
+        redeem_request.btc_address = origin_btc_address;
+        Redeem::<T>::insert_redeem_request(&redeem_id, &redeem_request);
+
+        let vault = Vault {
+            wallet: Wallet::new(dummy_public_key()),
+            id: vault_id.clone(),
+            ..Vault::new(Default::default(), Default::default(), T::GetGriefingCollateralCurrencyId::get())
+        };
+
+        VaultRegistry::<T>::insert_vault(
+            &vault_id,
+            vault
+        );
+
+        let height = 0;
+        let block = BlockBuilder::new()
             .with_version(4)
             .with_coinbase(&origin_btc_address, 50, 3)
             .with_timestamp(1588813835)
+            .mine(U256::from(2).pow(254.into())).unwrap();
+
+        let block_hash = block.header.hash;
+        let raw_block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
+        let block_header = BtcRelay::<T>::parse_raw_block_header(&raw_block_header).unwrap();
+
+        Security::<T>::set_active_block_number(1u32.into());
+        BtcRelay::<T>::initialize(relayer_id.clone(), block_header, height).unwrap();
+
+        // paying the full amount completes the request, which is the most expensive case
+        let value = 0;
+        let transaction = TransactionBuilder::new()
+            .with_version(2)
+            .add_input(
+                TransactionInputBuilder::new()
+                    .with_source(TransactionInputSource::FromOutput(block.transactions[0].hash(), 0))
+                    .with_script(&[
+                        0, 71, 48, 68, 2, 32, 91, 128, 41, 150, 96, 53, 187, 63, 230, 129, 53, 234,
+                        210, 186, 21, 187, 98, 38, 255, 112, 30, 27, 228, 29, 132, 140, 155, 62, 123,
+                        216, 232, 168, 2, 32, 72, 126, 179, 207, 142, 8, 99, 8, 32, 78, 244, 166, 106,
+                        160, 207, 227, 61, 210, 172, 234, 234, 93, 59, 159, 79, 12, 194, 240, 212, 3,
+                        120, 50, 1, 71, 81, 33, 3, 113, 209, 131, 177, 9, 29, 242, 229, 15, 217, 247,
+                        165, 78, 111, 80, 79, 50, 200, 117, 80, 30, 233, 210, 167, 133, 175, 62, 253,
+                        134, 127, 212, 51, 33, 2, 128, 200, 184, 235, 148, 25, 43, 34, 28, 173, 55, 54,
+                        189, 164, 187, 243, 243, 152, 7, 84, 210, 85, 156, 238, 77, 97, 188, 240, 162,
+                        197, 105, 62, 82, 174,
+                    ])
+                    .build(),
+            )
+            .add_output(TransactionOutput::payment(value.into(), &origin_btc_address))
+            .add_output(TransactionOutput::op_return(0, H256::zero().as_bytes()))
+            .build();
+
+        let block = BlockBuilder::new()
+            .with_previous_hash(block_hash)
+            .with_version(4)
+            .with_coinbase(&origin_btc_address, 50, 3)
+            .with_timestamp(1588814835)
             .add_transaction(transaction.clone())
             .mine(U256::from(2).pow(254.into())).unwrap();
 
@@ -277,6 +431,105 @@ BtcRelay::<T>::parachain_confirmations() + 1u32.into());
         ));
     }: _(RawOrigin::Signed(vault_id), redeem_id, proof, raw_tx)
 
+    execute_batch_redeem {
+        let n in 1 .. ACCEPTED_MAX_BATCH_RECIPIENTS as u32;
+
+        let vault_id: T::AccountId = account("Vault", 0, 0);
+        let relayer_id: T::AccountId = account("Relayer", 0, 0);
+
+        initialize_oracle::<T>();
+
+        let batch_id = H256::zero();
+        let mut redeem_ids = vec![];
+        let mut recipients = vec![];
+        for i in 0..n {
+            let redeem_id = H256::from_low_u64_be(i as u64 + 1);
+            let btc_address = BtcAddress::P2PKH(H160::from_low_u64_be(i as u64));
+            let mut redeem_request = RedeemRequest::default();
+            redeem_request.vault = vault_id.clone();
+            redeem_request.btc_address = btc_address;
+            Redeem::<T>::insert_redeem_request(&redeem_id, &redeem_request);
+            redeem_ids.push(redeem_id);
+            recipients.push(btc_address);
+        }
+        RedeemBatches::<T>::insert(batch_id, redeem_ids);
+
+        let vault = Vault {
+            wallet: Wallet::new(dummy_public_key()),
+            id: vault_id.clone(),
+            ..Vault::new(Default::default(), Default::default(), T::GetGriefingCollateralCurrencyId::get())
+        };
+
+        VaultRegistry::<T>::insert_vault(
+            &vault_id,
+            vault
+        );
+
+        let origin_btc_address = BtcAddress::P2PKH(H160::zero());
+        let height = 0;
+        let block = BlockBuilder::new()
+            .with_version(4)
+            .with_coinbase(&origin_btc_address, 50, 3)
+            .with_timestamp(1588813835)
+            .mine(U256::from(2).pow(254.into())).unwrap();
+
+        let block_hash = block.header.hash;
+        let raw_block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
+        let block_header = BtcRelay::<T>::parse_raw_block_header(&raw_block_header).unwrap();
+
+        Security::<T>::set_active_block_number(1u32.into());
+        BtcRelay::<T>::initialize(relayer_id.clone(), block_header, height).unwrap();
+
+        let mut transaction_builder = TransactionBuilder::new();
+        transaction_builder
+            .with_version(2)
+            .add_input(
+                TransactionInputBuilder::new()
+                    .with_source(TransactionInputSource::FromOutput(block.transactions[0].hash(), 0))
+                    .with_script(&[
+                        0, 71, 48, 68, 2, 32, 91, 128, 41, 150, 96, 53, 187, 63, 230, 129, 53, 234,
+                        210, 186, 21, 187, 98, 38, 255, 112, 30, 27, 228, 29, 132, 140, 155, 62, 123,
+                        216, 232, 168, 2, 32, 72, 126, 179, 207, 142, 8, 99, 8, 32, 78, 244, 166, 106,
+                        160, 207, 227, 61, 210, 172, 234, 234, 93, 59, 159, 79, 12, 194, 240, 212, 3,
+                        120, 50, 1, 71, 81, 33, 3, 113, 209, 131, 177, 9, 29, 242, 229, 15, 217, 247,
+                        165, 78, 111, 80, 79, 50, 200, 117, 80, 30, 233, 210, 167, 133, 175, 62, 253,
+                        134, 127, 212, 51, 33, 2, 128, 200, 184, 235, 148, 25, 43, 34, 28, 173, 55, 54,
+                        189, 164, 187, 243, 243, 152, 7, 84, 210, 85, 156, 238, 77, 97, 188, 240, 162,
+                        197, 105, 62, 82, 174,
+                    ])
+                    .build(),
+            );
+        for recipient in recipients.iter() {
+            transaction_builder.add_output(TransactionOutput::payment(0, recipient));
+        }
+        let transaction = transaction_builder
+            .add_output(TransactionOutput::op_return(0, batch_id.as_bytes()))
+            .build();
+
+        let block = BlockBuilder::new()
+            .with_previous_hash(block_hash)
+            .with_version(4)
+            .with_coinbase(&origin_btc_address, 50, 3)
+            .with_timestamp(1588814835)
+            .add_transaction(transaction.clone())
+            .mine(U256::from(2).pow(254.into())).unwrap();
+
+        let tx_id = transaction.tx_id();
+        let proof = block.merkle_proof(&[tx_id]).unwrap().try_format().unwrap();
+        let raw_tx = transaction.format_with(true);
+
+        let raw_block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
+        let block_header = BtcRelay::<T>::parse_raw_block_header(&raw_block_header).unwrap();
+
+        BtcRelay::<T>::store_block_header(&relayer_id, block_header).unwrap();
+        Security::<T>::set_active_block_number(Security::<T>::active_block_number() +
+BtcRelay::<T>::parachain_confirmations() + 1u32.into());
+
+        assert_ok!(Oracle::<T>::_set_exchange_rate(DEFAULT_TESTING_CURRENCY,
+            UnsignedFixedPoint::<T>::one()
+        ));
+    }: _(RawOrigin::Signed(vault_id), batch_id, proof, raw_tx)
+
     cancel_redeem_reimburse {
         let origin: T::AccountId = account("Origin", 0, 0);
         let vault_id: T::AccountId = account("Vault", 0, 0);
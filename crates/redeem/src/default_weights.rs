@@ -35,8 +35,12 @@ use sp_std::marker::PhantomData;
 /// Weight functions needed for redeem.
 pub trait WeightInfo {
 	fn request_redeem() -> Weight;
+	fn request_redeem_with_auto_vault(n: u32, ) -> Weight;
+	fn request_batch_redeem(n: u32, ) -> Weight;
 	fn liquidation_redeem() -> Weight;
 	fn execute_redeem() -> Weight;
+	fn execute_partial_redeem() -> Weight;
+	fn execute_batch_redeem(n: u32, ) -> Weight;
 	fn cancel_redeem_reimburse() -> Weight;
 	fn cancel_redeem_retry() -> Weight;
 	fn set_redeem_period() -> Weight;
@@ -68,6 +72,62 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().writes(4 as Weight))
 	}
 	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: Security Nonce (r:2 w:1)
+	// Storage: System ParentHash (r:1 w:0)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Tokens Accounts (r:1 w:1)
+	// Storage: Fee RedeemFee (r:1 w:0)
+	// Storage: Redeem RedeemTransactionSize (r:1 w:0)
+	// Storage: Oracle Aggregate (r:2 w:0)
+	// Storage: Redeem RedeemBtcDustValue (r:1 w:0)
+	// Storage: VaultRegistry PremiumRedeemThreshold (r:1 w:0)
+	// Storage: Staking Nonce (r:1 w:0)
+	// Storage: Staking TotalCurrentStake (r:1 w:0)
+	// Storage: Fee PremiumRedeemFee (r:1 w:0)
+	// Storage: Redeem RedeemPeriod (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: Redeem RedeemRequests (r:0 w:1)
+	// Storage: VaultRegistry Vaults (r:n w:0)
+	// Estimate: request_redeem (169_144_000) plus, per scanned candidate vault, accept_new_issues
+	// (17_480_000), which decodes and checks one vault as the candidate scan does
+	fn request_redeem_with_auto_vault(n: u32, ) -> Weight {
+		(169_144_000 as Weight)
+			.saturating_add((17_480_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(18 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: Tokens Accounts (r:1 w:1)
+	// Storage: Fee RedeemFee (r:1 w:0)
+	// Storage: Redeem RedeemTransactionSize (r:1 w:0)
+	// Storage: Oracle Aggregate (r:2 w:0)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Redeem RedeemBtcDustValue (r:1 w:0)
+	// Storage: Security Nonce (r:1 w:1)
+	// Storage: System ParentHash (r:1 w:0)
+	// Storage: VaultRegistry PremiumRedeemThreshold (r:1 w:0)
+	// Storage: Staking Nonce (r:1 w:0)
+	// Storage: Staking TotalCurrentStake (r:1 w:0)
+	// Storage: Fee PremiumRedeemFee (r:1 w:0)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: Redeem RedeemPeriod (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: Redeem RedeemRequests (r:0 w:1)
+	// Storage: Redeem RedeemBatches (r:0 w:1)
+	// Storage: Redeem BatchedRedeemRequests (r:0 w:1)
+	// Estimate: `n` times request_redeem (169_144_000); the batch adds one RedeemBatches write and
+	// a BatchedRedeemRequests write per request
+	fn request_batch_redeem(n: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((169_144_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((13 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes((5 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
 	// Storage: Tokens Accounts (r:3 w:3)
 	// Storage: Tokens TotalIssuance (r:1 w:1)
 	// Storage: VaultRegistry LiquidationVault (r:1 w:1)
@@ -93,10 +153,67 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	// Storage: Rewards Stake (r:1 w:1)
 	// Storage: Rewards RewardTally (r:1 w:1)
 	// Storage: Rewards RewardPerToken (r:1 w:0)
+	// Storage: Redeem BatchedRedeemRequests (r:1 w:0)
+	// Storage: BTCRelay SettledRequests (r:0 w:1)
+	// Estimate: execution time as measured before batches and settled requests; the storage
+	// accesses add the BatchedRedeemRequests read and one append to SettledRequests, whose
+	// BlockHeaders existence check hits the header read by the inclusion proof
 	fn execute_redeem() -> Weight {
 		(163_039_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(14 as Weight))
-			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads(15 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: Redeem RedeemRequests (r:1 w:1)
+	// Storage: Redeem RedeemPaymentTransactions (r:1 w:1)
+	// Storage: BTCRelay DisableInclusionCheck (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: BTCRelay Chains (r:1 w:0)
+	// Storage: BTCRelay BlockHeaders (r:1 w:0)
+	// Storage: BTCRelay StableBitcoinConfirmations (r:1 w:0)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: BTCRelay StableParachainConfirmations (r:1 w:0)
+	// Storage: Redeem PartialRedeemPayments (r:1 w:1)
+	// Storage: Rewards TotalStake (r:1 w:1)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Rewards Stake (r:1 w:1)
+	// Storage: Rewards RewardTally (r:1 w:1)
+	// Storage: Rewards RewardPerToken (r:1 w:0)
+	// Storage: Redeem BatchedRedeemRequests (r:1 w:0)
+	// Storage: BTCRelay SettledRequests (r:0 w:1)
+	// Estimate: execute_redeem (163_039_000), which verifies one payment as well; the storage
+	// accesses add the PartialRedeemPayments and RedeemPaymentTransactions reads and writes
+	fn execute_partial_redeem() -> Weight {
+		(163_039_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(17 as Weight))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: Redeem RedeemBatches (r:1 w:0)
+	// Storage: Redeem RedeemRequests (r:1 w:1)
+	// Storage: BTCRelay DisableInclusionCheck (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: BTCRelay Chains (r:1 w:0)
+	// Storage: BTCRelay BlockHeaders (r:1 w:0)
+	// Storage: BTCRelay StableBitcoinConfirmations (r:1 w:0)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: BTCRelay StableParachainConfirmations (r:1 w:0)
+	// Storage: Redeem PartialRedeemPayments (r:1 w:0)
+	// Storage: Rewards TotalStake (r:1 w:1)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Rewards Stake (r:1 w:1)
+	// Storage: Rewards RewardTally (r:1 w:1)
+	// Storage: Rewards RewardPerToken (r:1 w:0)
+	// Storage: BTCRelay SettledRequests (r:0 w:1)
+	// Estimate: execute_redeem (163_039_000) split into one verify_and_validate_transaction
+	// (66_727_000) for the shared payment and 96_312_000 per request for settling it; each request
+	// also checks that the payment block is stored and appends to SettledRequests
+	fn execute_batch_redeem(n: u32, ) -> Weight {
+		(66_727_000 as Weight)
+			.saturating_add((96_312_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(12 as Weight))
+			.saturating_add(T::DbWeight::get().reads((6 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes((6 as Weight).saturating_mul(n as Weight)))
 	}
 	// Storage: Security ParachainStatus (r:1 w:0)
 	// Storage: Redeem RedeemRequests (r:1 w:1)
@@ -168,6 +285,62 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
 	}
 	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: Security Nonce (r:2 w:1)
+	// Storage: System ParentHash (r:1 w:0)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Tokens Accounts (r:1 w:1)
+	// Storage: Fee RedeemFee (r:1 w:0)
+	// Storage: Redeem RedeemTransactionSize (r:1 w:0)
+	// Storage: Oracle Aggregate (r:2 w:0)
+	// Storage: Redeem RedeemBtcDustValue (r:1 w:0)
+	// Storage: VaultRegistry PremiumRedeemThreshold (r:1 w:0)
+	// Storage: Staking Nonce (r:1 w:0)
+	// Storage: Staking TotalCurrentStake (r:1 w:0)
+	// Storage: Fee PremiumRedeemFee (r:1 w:0)
+	// Storage: Redeem RedeemPeriod (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: Redeem RedeemRequests (r:0 w:1)
+	// Storage: VaultRegistry Vaults (r:n w:0)
+	// Estimate: request_redeem (169_144_000) plus, per scanned candidate vault, accept_new_issues
+	// (17_480_000), which decodes and checks one vault as the candidate scan does
+	fn request_redeem_with_auto_vault(n: u32, ) -> Weight {
+		(169_144_000 as Weight)
+			.saturating_add((17_480_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(18 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: Tokens Accounts (r:1 w:1)
+	// Storage: Fee RedeemFee (r:1 w:0)
+	// Storage: Redeem RedeemTransactionSize (r:1 w:0)
+	// Storage: Oracle Aggregate (r:2 w:0)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Redeem RedeemBtcDustValue (r:1 w:0)
+	// Storage: Security Nonce (r:1 w:1)
+	// Storage: System ParentHash (r:1 w:0)
+	// Storage: VaultRegistry PremiumRedeemThreshold (r:1 w:0)
+	// Storage: Staking Nonce (r:1 w:0)
+	// Storage: Staking TotalCurrentStake (r:1 w:0)
+	// Storage: Fee PremiumRedeemFee (r:1 w:0)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: Redeem RedeemPeriod (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: Redeem RedeemRequests (r:0 w:1)
+	// Storage: Redeem RedeemBatches (r:0 w:1)
+	// Storage: Redeem BatchedRedeemRequests (r:0 w:1)
+	// Estimate: `n` times request_redeem (169_144_000); the batch adds one RedeemBatches write and
+	// a BatchedRedeemRequests write per request
+	fn request_batch_redeem(n: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((169_144_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((13 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((5 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
 	// Storage: Tokens Accounts (r:3 w:3)
 	// Storage: Tokens TotalIssuance (r:1 w:1)
 	// Storage: VaultRegistry LiquidationVault (r:1 w:1)
@@ -193,10 +366,67 @@ impl WeightInfo for () {
 	// Storage: Rewards Stake (r:1 w:1)
 	// Storage: Rewards RewardTally (r:1 w:1)
 	// Storage: Rewards RewardPerToken (r:1 w:0)
+	// Storage: Redeem BatchedRedeemRequests (r:1 w:0)
+	// Storage: BTCRelay SettledRequests (r:0 w:1)
+	// Estimate: execution time as measured before batches and settled requests; the storage
+	// accesses add the BatchedRedeemRequests read and one append to SettledRequests, whose
+	// BlockHeaders existence check hits the header read by the inclusion proof
 	fn execute_redeem() -> Weight {
 		(163_039_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(14 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(15 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: Redeem RedeemRequests (r:1 w:1)
+	// Storage: Redeem RedeemPaymentTransactions (r:1 w:1)
+	// Storage: BTCRelay DisableInclusionCheck (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: BTCRelay Chains (r:1 w:0)
+	// Storage: BTCRelay BlockHeaders (r:1 w:0)
+	// Storage: BTCRelay StableBitcoinConfirmations (r:1 w:0)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: BTCRelay StableParachainConfirmations (r:1 w:0)
+	// Storage: Redeem PartialRedeemPayments (r:1 w:1)
+	// Storage: Rewards TotalStake (r:1 w:1)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Rewards Stake (r:1 w:1)
+	// Storage: Rewards RewardTally (r:1 w:1)
+	// Storage: Rewards RewardPerToken (r:1 w:0)
+	// Storage: Redeem BatchedRedeemRequests (r:1 w:0)
+	// Storage: BTCRelay SettledRequests (r:0 w:1)
+	// Estimate: execute_redeem (163_039_000), which verifies one payment as well; the storage
+	// accesses add the PartialRedeemPayments and RedeemPaymentTransactions reads and writes
+	fn execute_partial_redeem() -> Weight {
+		(163_039_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(17 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: Redeem RedeemBatches (r:1 w:0)
+	// Storage: Redeem RedeemRequests (r:1 w:1)
+	// Storage: BTCRelay DisableInclusionCheck (r:1 w:0)
+	// Storage: BTCRelay BestBlockHeight (r:1 w:0)
+	// Storage: BTCRelay Chains (r:1 w:0)
+	// Storage: BTCRelay BlockHeaders (r:1 w:0)
+	// Storage: BTCRelay StableBitcoinConfirmations (r:1 w:0)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: BTCRelay StableParachainConfirmations (r:1 w:0)
+	// Storage: Redeem PartialRedeemPayments (r:1 w:0)
+	// Storage: Rewards TotalStake (r:1 w:1)
+	// Storage: VaultRegistry Vaults (r:1 w:1)
+	// Storage: Rewards Stake (r:1 w:1)
+	// Storage: Rewards RewardTally (r:1 w:1)
+	// Storage: Rewards RewardPerToken (r:1 w:0)
+	// Storage: BTCRelay SettledRequests (r:0 w:1)
+	// Estimate: execute_redeem (163_039_000) split into one verify_and_validate_transaction
+	// (66_727_000) for the shared payment and 96_312_000 per request for settling it; each request
+	// also checks that the payment block is stored and appends to SettledRequests
+	fn execute_batch_redeem(n: u32, ) -> Weight {
+		(66_727_000 as Weight)
+			.saturating_add((96_312_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(12 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((6 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((6 as Weight).saturating_mul(n as Weight)))
 	}
 	// Storage: Security ParachainStatus (r:1 w:0)
 	// Storage: Redeem RedeemRequests (r:1 w:1)
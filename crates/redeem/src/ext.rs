@@ -3,11 +3,48 @@ use mocktopus::macros::mockable;
 
 #[cfg_attr(test, mockable)]
 pub(crate) mod btc_relay {
-    use bitcoin::types::{MerkleProof, Transaction, Value};
-    use btc_relay::BtcAddress;
+    use bitcoin::types::{H256Le, MerkleProof, Transaction, Value};
+    use btc_relay::{BtcAddress, PaymentVerification, SettledRequest};
     use frame_support::dispatch::DispatchError;
     use sp_core::H256;
-    use sp_std::convert::TryInto;
+    use sp_std::{
+        convert::{TryFrom, TryInto},
+        vec::Vec,
+    };
+
+    pub fn dry_run_op_return_payment<T: crate::Config, V: TryFrom<Value> + TryInto<Value> + Clone>(
+        raw_merkle_proof: &[u8],
+        raw_tx: &[u8],
+        recipient_btc_address: BtcAddress,
+        expected_amount: V,
+        op_return_id: H256,
+    ) -> PaymentVerification<V> {
+        <btc_relay::Pallet<T>>::dry_run_op_return_payment(
+            raw_merkle_proof,
+            raw_tx,
+            recipient_btc_address,
+            expected_amount,
+            op_return_id,
+        )
+    }
+
+    pub fn dry_run_op_return_part_payment<T: crate::Config, V: TryFrom<Value> + TryInto<Value> + Clone>(
+        raw_merkle_proof: &[u8],
+        raw_tx: &[u8],
+        recipient_btc_address: BtcAddress,
+        expected_amount: V,
+        request_amount: V,
+        op_return_id: H256,
+    ) -> PaymentVerification<V> {
+        <btc_relay::Pallet<T>>::dry_run_op_return_part_payment(
+            raw_merkle_proof,
+            raw_tx,
+            recipient_btc_address,
+            expected_amount,
+            request_amount,
+            op_return_id,
+        )
+    }
 
     pub fn verify_and_validate_op_return_transaction<T: crate::Config, V: TryInto<Value>>(
         merkle_proof: MerkleProof,
@@ -25,6 +62,36 @@ pub(crate) mod btc_relay {
         )
     }
 
+    pub fn verify_and_validate_batch_op_return_transaction<T: crate::Config, V: TryInto<Value>>(
+        merkle_proof: MerkleProof,
+        transaction: Transaction,
+        expected_payments: Vec<(BtcAddress, V)>,
+        op_return_id: H256,
+    ) -> Result<(), DispatchError> {
+        <btc_relay::Pallet<T>>::verify_and_validate_batch_op_return_transaction(
+            merkle_proof,
+            transaction,
+            expected_payments,
+            op_return_id,
+        )
+    }
+
+    pub fn get_and_verify_op_return_payment<T: crate::Config, V: TryFrom<Value> + TryInto<Value>>(
+        merkle_proof: MerkleProof,
+        transaction: Transaction,
+        recipient_btc_address: BtcAddress,
+        request_amount: V,
+        op_return_id: H256,
+    ) -> Result<V, DispatchError> {
+        <btc_relay::Pallet<T>>::get_and_verify_op_return_payment(
+            merkle_proof,
+            transaction,
+            recipient_btc_address,
+            request_amount,
+            op_return_id,
+        )
+    }
+
     pub fn get_best_block_height<T: crate::Config>() -> u32 {
         <btc_relay::Pallet<T>>::get_best_block_height()
     }
@@ -44,12 +111,17 @@ pub(crate) mod btc_relay {
     ) -> Result<bool, DispatchError> {
         <btc_relay::Pallet<T>>::has_request_expired(opentime, btc_open_height, period)
     }
+
+    pub fn record_settled_request<T: crate::Config>(block_hash: H256Le, request: SettledRequest) {
+        <btc_relay::Pallet<T>>::record_settled_request(block_hash, request)
+    }
 }
 
 #[cfg_attr(test, mockable)]
 pub(crate) mod vault_registry {
     use currency::Amount;
     use frame_support::dispatch::{DispatchError, DispatchResult};
+    use sp_core::H256;
     use vault_registry::types::{CurrencyId, CurrencySource, DefaultVault};
 
     pub fn get_liquidated_collateral<T: crate::Config>(vault_id: &T::AccountId) -> Result<Amount<T>, DispatchError> {
@@ -76,6 +148,14 @@ pub(crate) mod vault_registry {
         <vault_registry::Pallet<T>>::get_vault_from_id(vault_id)
     }
 
+    pub fn select_vault_for_redeem<T: crate::Config>(
+        amount: &Amount<T>,
+        currency_id: CurrencyId<T>,
+        random: H256,
+    ) -> Result<T::AccountId, DispatchError> {
+        <vault_registry::Pallet<T>>::select_vault_for_redeem(amount, currency_id, random)
+    }
+
     pub fn try_increase_to_be_redeemed_tokens<T: crate::Config>(
         vault_id: &T::AccountId,
         amount: &Amount<T>,
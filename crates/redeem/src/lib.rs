@@ -27,10 +27,11 @@ mod ext;
 pub mod types;
 
 #[doc(inline)]
-pub use crate::types::{DefaultRedeemRequest, RedeemRequest, RedeemRequestStatus};
+pub use crate::types::{DefaultRedeemRequest, RedeemPaymentVerification, RedeemRequest, RedeemRequestStatus};
 
 use crate::types::{BalanceOf, Collateral, RedeemRequestExt, Version, Wrapped};
-use btc_relay::BtcAddress;
+use bitcoin::types::H256Le;
+use btc_relay::{BtcAddress, SettledRequest, ACCEPTED_MAX_BATCH_RECIPIENTS};
 use currency::Amount;
 use frame_support::{
     dispatch::{DispatchError, DispatchResult},
@@ -43,7 +44,7 @@ use oracle::OracleKey;
 use sp_core::H256;
 use sp_runtime::FixedPointNumber;
 use sp_std::{convert::TryInto, vec::Vec};
-use vault_registry::{types::CurrencyId, CurrencySource};
+use vault_registry::{types::CurrencyId, CurrencySource, MAX_VAULT_SELECTION_CANDIDATES};
 
 pub use pallet::*;
 
@@ -80,10 +81,16 @@ pub mod pallet {
             BtcAddress,    // user btc_address
             Wrapped<T>,    // transfer_fee_btc
         ),
+        // [batch_id, redeemer, vault_id, redeem_ids]
+        RequestBatchRedeem(H256, T::AccountId, T::AccountId, Vec<H256>),
         // [redeemer, amount_wrapped]
         LiquidationRedeem(T::AccountId, Wrapped<T>),
         // [redeem_id, redeemer, amount_wrapped, fee_wrapped, vault, transfer_fee_btc]
         ExecuteRedeem(H256, T::AccountId, Wrapped<T>, Wrapped<T>, T::AccountId, Wrapped<T>),
+        // [batch_id]
+        ExecuteBatchRedeem(H256),
+        // [redeem_id, redeemer, vault_id, amount_paid, total_paid]
+        PartialRedeemPayment(H256, T::AccountId, T::AccountId, Wrapped<T>, Wrapped<T>),
         // [redeem_id, redeemer, vault_id, slashing_amount_in_collateral, status]
         CancelRedeem(H256, T::AccountId, T::AccountId, Collateral<T>, RedeemRequestStatus),
         // [vault_id, redeem_id, amount_minted]
@@ -105,6 +112,15 @@ pub mod pallet {
         ArithmeticOverflow,
         ArithmeticUnderflow,
         AmountBelowDustAmount,
+        /// Partial payments exceed the amount of the redeem request
+        PaymentExceedsRedeemAmount,
+        /// Transaction has already been counted towards this redeem request
+        PaymentAlreadyCounted,
+        /// Batch has no outputs, too many outputs, or several outputs to the same address
+        InvalidRedeemBatch,
+        RedeemBatchNotFound,
+        /// Batched redeem requests can only be executed together with their batch
+        RedeemRequestInBatch,
     }
 
     /// The time difference in number of blocks between a redeem request is created and required completion time by a
@@ -131,6 +147,28 @@ pub mod pallet {
     #[pallet::getter(fn redeem_transaction_size)]
     pub(super) type RedeemTransactionSize<T: Config> = StorageValue<_, u32, ValueQuery>;
 
+    /// The amount of BTC that has been paid towards a redeem request by partial payments. This
+    /// part of the request is settled when the payment is made, so it is excluded when the request
+    /// is executed or cancelled.
+    #[pallet::storage]
+    #[pallet::getter(fn partial_redeem_payment)]
+    pub(super) type PartialRedeemPayments<T: Config> = StorageMap<_, Blake2_128Concat, H256, Wrapped<T>, ValueQuery>;
+
+    /// Bitcoin transactions that have been counted as partial payments of a redeem request.
+    #[pallet::storage]
+    pub(super) type RedeemPaymentTransactions<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, H256, Blake2_128Concat, H256Le, Option<()>, ValueQuery>;
+
+    /// Batched redeem requests, mapping a batch id to the redeem requests that the vault pays
+    /// together in a single transaction carrying the batch id in its OP_RETURN output.
+    #[pallet::storage]
+    pub(super) type RedeemBatches<T: Config> = StorageMap<_, Blake2_128Concat, H256, Vec<H256>, OptionQuery>;
+
+    /// Mapping of batched redeem requests to their batch id. Batched requests are only executed
+    /// together, but each of them can be cancelled on its own.
+    #[pallet::storage]
+    pub(super) type BatchedRedeemRequests<T: Config> = StorageMap<_, Blake2_128Concat, H256, H256, OptionQuery>;
+
     #[pallet::type_value]
     pub(super) fn DefaultForStorageVersion() -> Version {
         Version::V0
@@ -199,6 +237,49 @@ pub mod pallet {
             Ok(().into())
         }
 
+        /// Initializes a request to burn issued tokens against a Vault selected by the parachain.
+        /// The vault is chosen randomly amongst those that can redeem `amount_wrapped`, weighted by
+        /// their redeemable tokens.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - sender of the transaction
+        /// * `amount` - amount of issued tokens
+        /// * `btc_address` - the address to receive BTC
+        /// * `currency_id` - collateral currency of the vault to select
+        #[pallet::weight(<T as Config>::WeightInfo::request_redeem_with_auto_vault(MAX_VAULT_SELECTION_CANDIDATES))]
+        #[transactional]
+        pub fn request_redeem_with_auto_vault(
+            origin: OriginFor<T>,
+            #[pallet::compact] amount_wrapped: Wrapped<T>,
+            btc_address: BtcAddress,
+            currency_id: CurrencyId<T>,
+        ) -> DispatchResultWithPostInfo {
+            let redeemer = ensure_signed(origin)?;
+            Self::_request_redeem_with_auto_vault(redeemer, amount_wrapped, btc_address, currency_id)?;
+            Ok(().into())
+        }
+
+        /// Initializes a batch of redeem requests against a single Vault, which pays all of them in one
+        /// Bitcoin transaction. The Bitcoin inclusion fee is charged only once for the whole batch.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - sender of the transaction
+        /// * `outputs` - the addresses to receive BTC, with the amount of issued tokens redeemed for each
+        /// * `vault_id` - address of the vault
+        #[pallet::weight(<T as Config>::WeightInfo::request_batch_redeem(outputs.len() as u32))]
+        #[transactional]
+        pub fn request_batch_redeem(
+            origin: OriginFor<T>,
+            outputs: Vec<(BtcAddress, Wrapped<T>)>,
+            vault_id: T::AccountId,
+        ) -> DispatchResultWithPostInfo {
+            let redeemer = ensure_signed(origin)?;
+            Self::_request_batch_redeem(redeemer, outputs, vault_id)?;
+            Ok(().into())
+        }
+
         /// When a Vault is liquidated, its collateral is slashed up to 150% of the liquidated BTC value.
         /// To re-establish the physical 1:1 peg, the bridge allows users to burn issued tokens in return for
         /// collateral at a premium rate.
@@ -244,6 +325,51 @@ pub mod pallet {
             Ok(().into())
         }
 
+        /// A Vault calls this function to pay a redeem request in parts, using several transactions
+        /// that each carry the redeem id in their OP_RETURN output. Each payment is settled right
+        /// away, and the redeem request is completed once the full amount has been paid.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - anyone executing this redeem request
+        /// * `redeem_id` - identifier of redeem request as output from request_redeem
+        /// * `merkle_proof` - raw bytes
+        /// * `raw_tx` - raw bytes
+        #[pallet::weight(<T as Config>::WeightInfo::execute_partial_redeem())]
+        #[transactional]
+        pub fn execute_partial_redeem(
+            origin: OriginFor<T>,
+            redeem_id: H256,
+            merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            let _ = ensure_signed(origin)?;
+            Self::_execute_partial_redeem(redeem_id, merkle_proof, raw_tx)?;
+            Ok(().into())
+        }
+
+        /// A Vault calls this function after paying all the redeem requests of a batch in a single
+        /// transaction, which carries the batch id in its OP_RETURN output.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - anyone executing this batch
+        /// * `batch_id` - identifier of the batch as output from request_batch_redeem
+        /// * `merkle_proof` - raw bytes
+        /// * `raw_tx` - raw bytes
+        #[pallet::weight(<T as Config>::WeightInfo::execute_batch_redeem(ACCEPTED_MAX_BATCH_RECIPIENTS as u32))]
+        #[transactional]
+        pub fn execute_batch_redeem(
+            origin: OriginFor<T>,
+            batch_id: H256,
+            merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            let _ = ensure_signed(origin)?;
+            Self::_execute_batch_redeem(batch_id, merkle_proof, raw_tx)?;
+            Ok(().into())
+        }
+
         /// If a redeem request is not completed on time, the redeem request can be cancelled.
         /// The user that initially requested the redeem process calls this function to obtain
         /// the Vault’s collateral as compensation for not refunding the BTC back to their address.
@@ -313,6 +439,72 @@ impl<T: Config> Pallet<T> {
 
         ext::security::ensure_parachain_status_running::<T>()?;
 
+        Self::create_redeem_request(redeemer, amount_wrapped, btc_address, vault_id, true)
+    }
+
+    fn _request_redeem_with_auto_vault(
+        redeemer: T::AccountId,
+        amount_wrapped: Wrapped<T>,
+        btc_address: BtcAddress,
+        currency_id: CurrencyId<T>,
+    ) -> Result<H256, DispatchError> {
+        let amount_wrapped = Amount::new(amount_wrapped, T::GetWrappedCurrencyId::get());
+
+        ext::security::ensure_parachain_status_running::<T>()?;
+
+        let random = ext::security::get_secure_id::<T>(&redeemer);
+        let vault_id = ext::vault_registry::select_vault_for_redeem::<T>(&amount_wrapped, currency_id, random)?;
+
+        Self::create_redeem_request(redeemer, amount_wrapped, btc_address, vault_id, true)
+    }
+
+    fn _request_batch_redeem(
+        redeemer: T::AccountId,
+        outputs: Vec<(BtcAddress, Wrapped<T>)>,
+        vault_id: T::AccountId,
+    ) -> Result<H256, DispatchError> {
+        ext::security::ensure_parachain_status_running::<T>()?;
+
+        // the outputs must fit in a single batch payment, which pays each address once
+        ensure!(
+            !outputs.is_empty() && outputs.len() <= ACCEPTED_MAX_BATCH_RECIPIENTS,
+            Error::<T>::InvalidRedeemBatch
+        );
+        for (i, (btc_address, _)) in outputs.iter().enumerate() {
+            ensure!(
+                outputs[..i].iter().all(|(other, _)| other != btc_address),
+                Error::<T>::InvalidRedeemBatch
+            );
+        }
+
+        let mut redeem_ids = Vec::with_capacity(outputs.len());
+        for (i, (btc_address, amount_wrapped)) in outputs.into_iter().enumerate() {
+            let amount_wrapped = Amount::new(amount_wrapped, T::GetWrappedCurrencyId::get());
+            // the inclusion fee of the batch payment is charged to the first request only
+            let redeem_id =
+                Self::create_redeem_request(redeemer.clone(), amount_wrapped, btc_address, vault_id.clone(), i == 0)?;
+            redeem_ids.push(redeem_id);
+        }
+
+        let batch_id = ext::security::get_secure_id::<T>(&redeemer);
+        for redeem_id in redeem_ids.iter() {
+            BatchedRedeemRequests::<T>::insert(redeem_id, batch_id);
+        }
+        RedeemBatches::<T>::insert(batch_id, redeem_ids.clone());
+
+        Self::deposit_event(<Event<T>>::RequestBatchRedeem(batch_id, redeemer, vault_id, redeem_ids));
+
+        Ok(batch_id)
+    }
+
+    /// Opens a redeem request, charging the Bitcoin inclusion fee only if `charge_inclusion_fee` is set.
+    fn create_redeem_request(
+        redeemer: T::AccountId,
+        amount_wrapped: Amount<T>,
+        btc_address: BtcAddress,
+        vault_id: T::AccountId,
+        charge_inclusion_fee: bool,
+    ) -> Result<H256, DispatchError> {
         let redeemer_balance = ext::treasury::get_balance::<T>(&redeemer);
         ensure!(
             amount_wrapped.le(&redeemer_balance)?,
@@ -324,7 +516,11 @@ impl<T: Config> Pallet<T> {
         } else {
             ext::fee::get_redeem_fee::<T>(&amount_wrapped)?
         };
-        let inclusion_fee = Self::get_current_inclusion_fee()?;
+        let inclusion_fee = if charge_inclusion_fee {
+            Self::get_current_inclusion_fee()?
+        } else {
+            Amount::zero(T::GetWrappedCurrencyId::get())
+        };
 
         let vault_to_be_burned_tokens = amount_wrapped.checked_sub(&fee_wrapped)?;
 
@@ -430,10 +626,12 @@ impl<T: Config> Pallet<T> {
         ext::security::ensure_parachain_status_not_shutdown::<T>()?;
 
         let redeem = Self::get_open_redeem_request_from_id(&redeem_id)?;
+        Self::ensure_not_batched(&redeem_id)?;
 
         // check the transaction inclusion and validity
         let transaction = ext::btc_relay::parse_transaction::<T>(&raw_tx)?;
         let merkle_proof = ext::btc_relay::parse_merkle_proof::<T>(&raw_merkle_proof)?;
+        let block_hash = merkle_proof.block_header.hash;
         ext::btc_relay::verify_and_validate_op_return_transaction::<T, _>(
             merkle_proof,
             transaction,
@@ -441,9 +639,112 @@ impl<T: Config> Pallet<T> {
             redeem.amount_btc,
             redeem_id,
         )?;
+        ext::btc_relay::record_settled_request::<T>(block_hash, SettledRequest::Redeem(redeem_id));
+
+        Self::complete_redeem(redeem_id, redeem)
+    }
 
-        // burn amount (without parachain fee, but including transfer fee)
-        let burn_amount = redeem.amount_btc().checked_add(&redeem.transfer_fee_btc())?;
+    fn _execute_batch_redeem(batch_id: H256, raw_merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> DispatchResult {
+        ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+
+        let redeem_ids = RedeemBatches::<T>::get(batch_id).ok_or(Error::<T>::RedeemBatchNotFound)?;
+        let mut redeems = Vec::with_capacity(redeem_ids.len());
+        let mut expected_payments = Vec::with_capacity(redeem_ids.len());
+        for redeem_id in redeem_ids {
+            let redeem = RedeemRequests::<T>::try_get(redeem_id).or(Err(Error::<T>::RedeemIdNotFound))?;
+            // the payment covers the whole batch, including requests that were cancelled since
+            expected_payments.push((redeem.btc_address, redeem.amount_btc));
+            match redeem.status {
+                RedeemRequestStatus::Pending => redeems.push((redeem_id, redeem)),
+                RedeemRequestStatus::Completed => return Err(Error::<T>::RedeemCompleted.into()),
+                RedeemRequestStatus::Reimbursed(_) | RedeemRequestStatus::Retried => {}
+            }
+        }
+        ensure!(!redeems.is_empty(), Error::<T>::RedeemCancelled);
+
+        // check the transaction inclusion and that it pays every request of the batch
+        let transaction = ext::btc_relay::parse_transaction::<T>(&raw_tx)?;
+        let merkle_proof = ext::btc_relay::parse_merkle_proof::<T>(&raw_merkle_proof)?;
+        let block_hash = merkle_proof.block_header.hash;
+        ext::btc_relay::verify_and_validate_batch_op_return_transaction::<T, _>(
+            merkle_proof,
+            transaction,
+            expected_payments,
+            batch_id,
+        )?;
+
+        for (redeem_id, redeem) in redeems {
+            ext::btc_relay::record_settled_request::<T>(block_hash, SettledRequest::Redeem(redeem_id));
+            Self::complete_redeem(redeem_id, redeem)?;
+        }
+
+        Self::deposit_event(<Event<T>>::ExecuteBatchRedeem(batch_id));
+        Ok(())
+    }
+
+    fn _execute_partial_redeem(redeem_id: H256, raw_merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> DispatchResult {
+        ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+
+        let redeem = Self::get_open_redeem_request_from_id(&redeem_id)?;
+        Self::ensure_not_batched(&redeem_id)?;
+
+        // check the transaction inclusion and that it pays the redeemer
+        let transaction = ext::btc_relay::parse_transaction::<T>(&raw_tx)?;
+        let merkle_proof = ext::btc_relay::parse_merkle_proof::<T>(&raw_merkle_proof)?;
+        ensure!(
+            RedeemPaymentTransactions::<T>::get(redeem_id, transaction.tx_id()).is_none(),
+            Error::<T>::PaymentAlreadyCounted
+        );
+        RedeemPaymentTransactions::<T>::insert(redeem_id, transaction.tx_id(), Some(()));
+        let block_hash = merkle_proof.block_header.hash;
+        // the confirmations depend on the request amount rather than on the amount of this part
+        let request_amount = redeem.amount_btc().checked_add(&redeem.transfer_fee_btc())?;
+        let amount_paid = ext::btc_relay::get_and_verify_op_return_payment::<T, Wrapped<T>>(
+            merkle_proof,
+            transaction,
+            redeem.btc_address,
+            request_amount.amount(),
+            redeem_id,
+        )?;
+        ext::btc_relay::record_settled_request::<T>(block_hash, SettledRequest::Redeem(redeem_id));
+        let amount_paid = Amount::new(amount_paid, T::GetWrappedCurrencyId::get());
+
+        let total_paid = Self::get_partial_payment(&redeem_id).checked_add(&amount_paid)?;
+        ensure!(
+            total_paid.le(&redeem.amount_btc())?,
+            Error::<T>::PaymentExceedsRedeemAmount
+        );
+
+        if total_paid.eq(&redeem.amount_btc())? {
+            return Self::complete_redeem(redeem_id, redeem);
+        }
+
+        // settle the paid part right away, so that it is excluded from cancellation slashing
+        amount_paid.burn_from(&redeem.redeemer)?;
+        let currency_id = ext::vault_registry::get_collateral_currency::<T>(&redeem.vault)?;
+        ext::vault_registry::redeem_tokens::<T>(
+            &redeem.vault,
+            &amount_paid,
+            &Amount::zero(currency_id),
+            &redeem.redeemer,
+        )?;
+        <PartialRedeemPayments<T>>::insert(redeem_id, total_paid.amount());
+
+        Self::deposit_event(<Event<T>>::PartialRedeemPayment(
+            redeem_id,
+            redeem.redeemer,
+            redeem.vault,
+            amount_paid.amount(),
+            total_paid.amount(),
+        ));
+        Ok(())
+    }
+
+    /// Completes the redeem request, settling the part of it that has not been settled by partial
+    /// payments.
+    fn complete_redeem(redeem_id: H256, redeem: DefaultRedeemRequest<T>) -> DispatchResult {
+        // burn amount (without parachain fee, but including transfer fee), excluding partial payments
+        let burn_amount = Self::get_unpaid_amount(&redeem_id, &redeem)?;
         burn_amount.burn_from(&redeem.redeemer)?;
 
         // send fees to pool
@@ -486,7 +787,7 @@ impl<T: Config> Pallet<T> {
         let vault_to_be_redeemed_tokens = Amount::new(vault.to_be_redeemed_tokens, T::GetWrappedCurrencyId::get());
         let vault_id = redeem.vault.clone();
 
-        let vault_to_be_burned_tokens = redeem.amount_btc().checked_add(&redeem.transfer_fee_btc())?;
+        let vault_to_be_burned_tokens = Self::get_unpaid_amount(&redeem_id, &redeem)?;
 
         let amount_wrapped_in_collateral = vault_to_be_burned_tokens.convert_to(vault.currency_id)?;
 
@@ -558,10 +859,7 @@ impl<T: Config> Pallet<T> {
             }
         } else {
             // unlock user's issued tokens, including fee
-            let total_wrapped: Amount<T> = redeem
-                .amount_btc()
-                .checked_add(&redeem.fee())?
-                .checked_add(&redeem.transfer_fee_btc())?;
+            let total_wrapped: Amount<T> = vault_to_be_burned_tokens.checked_add(&redeem.fee())?;
             total_wrapped.unlock_on(&redeemer)?;
             ext::vault_registry::decrease_to_be_redeemed_tokens::<T>(&vault_id, &vault_to_be_burned_tokens)?;
             Self::set_redeem_status(redeem_id, RedeemRequestStatus::Retried)
@@ -589,7 +887,7 @@ impl<T: Config> Pallet<T> {
 
         ensure!(redeem.vault == vault_id, Error::<T>::UnauthorizedUser);
 
-        let reimbursed_amount = redeem.amount_btc().checked_add(&redeem.transfer_fee_btc())?;
+        let reimbursed_amount = Self::get_unpaid_amount(&redeem_id, &redeem)?;
 
         ext::vault_registry::try_increase_to_be_issued_tokens::<T>(&vault_id, &reimbursed_amount)?;
         ext::vault_registry::issue_tokens::<T>(&vault_id, &reimbursed_amount)?;
@@ -616,6 +914,20 @@ impl<T: Config> Pallet<T> {
         <RedeemRequests<T>>::insert(key, value)
     }
 
+    /// Get the amount that has been paid towards a redeem request by partial payments.
+    fn get_partial_payment(redeem_id: &H256) -> Amount<T> {
+        Amount::new(Self::partial_redeem_payment(redeem_id), T::GetWrappedCurrencyId::get())
+    }
+
+    /// Get the amount of a redeem request, including the transfer fee, that has not yet been
+    /// settled by partial payments.
+    fn get_unpaid_amount(redeem_id: &H256, redeem: &DefaultRedeemRequest<T>) -> Result<Amount<T>, DispatchError> {
+        redeem
+            .amount_btc()
+            .checked_add(&redeem.transfer_fee_btc())?
+            .checked_sub(&Self::get_partial_payment(redeem_id))
+    }
+
     fn set_redeem_status(id: H256, status: RedeemRequestStatus) -> RedeemRequestStatus {
         <RedeemRequests<T>>::mutate(id, |request| {
             request.status = status.clone();
@@ -662,6 +974,61 @@ impl<T: Config> Pallet<T> {
             .collect::<Vec<_>>()
     }
 
+    /// Dry-run the payment verification of `execute_redeem` and of `execute_partial_redeem`,
+    /// reporting the payment that was found and the first check that failed, if any, along with
+    /// the amount already paid by partial payments. Batched requests are only paid and executed
+    /// as part of their batch, so they fail with `RedeemRequestInBatch`.
+    ///
+    /// # Arguments
+    ///
+    /// * `redeem_id` - identifier of the redeem request
+    /// * `raw_merkle_proof` - raw merkle proof of the payment
+    /// * `raw_tx` - raw bitcoin transaction of the payment
+    pub fn verify_redeem_payment(
+        redeem_id: H256,
+        raw_merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+    ) -> Result<RedeemPaymentVerification<Wrapped<T>>, DispatchError> {
+        let redeem = Self::get_open_redeem_request_from_id(&redeem_id)?;
+        Self::ensure_not_batched(&redeem_id)?;
+        let payment = ext::btc_relay::dry_run_op_return_payment::<T, _>(
+            &raw_merkle_proof,
+            &raw_tx,
+            redeem.btc_address,
+            redeem.amount_btc,
+            redeem_id,
+        );
+
+        let amount_paid = Self::get_partial_payment(&redeem_id);
+        let amount_left = redeem.amount_btc().checked_sub(&amount_paid)?;
+        let request_amount = redeem.amount_btc().checked_add(&redeem.transfer_fee_btc())?;
+        let mut partial_payment = ext::btc_relay::dry_run_op_return_part_payment::<T, _>(
+            &raw_merkle_proof,
+            &raw_tx,
+            redeem.btc_address,
+            amount_left.amount(),
+            request_amount.amount(),
+            redeem_id,
+        );
+        // the checks of `execute_partial_redeem` that are not part of the payment verification
+        let is_counted = ext::btc_relay::parse_transaction::<T>(&raw_tx)
+            .map(|transaction| Self::is_partial_payment_transaction(&redeem_id, transaction.tx_id()))
+            .unwrap_or(false);
+        if is_counted {
+            partial_payment.error = Some(Error::<T>::PaymentAlreadyCounted.into());
+        } else if partial_payment.error.is_none()
+            && matches!(partial_payment.amount_found, Some(amount) if amount > amount_left.amount())
+        {
+            partial_payment.error = Some(Error::<T>::PaymentExceedsRedeemAmount.into());
+        }
+
+        Ok(RedeemPaymentVerification {
+            payment,
+            partial_payment,
+            amount_paid: amount_paid.amount(),
+        })
+    }
+
     /// Fetch a pre-existing redeem request or throw. Completed or cancelled
     /// requests are not returned.
     ///
@@ -681,6 +1048,59 @@ impl<T: Config> Pallet<T> {
         }
     }
 
+    /// Fetch the payments that a batched redeem expects, provided that at least one of its
+    /// requests is pending or completed. Cancelled requests are included, since the vault may
+    /// have paid the batch before they were cancelled.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_id` - 256-bit identifier of the batch
+    pub fn get_open_or_completed_redeem_batch_payments(
+        batch_id: &H256,
+    ) -> Result<Vec<(BtcAddress, Wrapped<T>)>, DispatchError> {
+        let redeem_ids = RedeemBatches::<T>::get(batch_id).ok_or(Error::<T>::RedeemBatchNotFound)?;
+        let mut payments = Vec::with_capacity(redeem_ids.len());
+        let mut is_cancelled = true;
+        for redeem_id in redeem_ids {
+            let redeem = RedeemRequests::<T>::try_get(redeem_id).or(Err(Error::<T>::RedeemIdNotFound))?;
+            is_cancelled &= matches!(
+                redeem.status,
+                RedeemRequestStatus::Reimbursed(_) | RedeemRequestStatus::Retried
+            );
+            payments.push((redeem.btc_address, redeem.amount_btc));
+        }
+        ensure!(!is_cancelled, Error::<T>::RedeemCancelled);
+        Ok(payments)
+    }
+
+    /// Checks if any transaction has been counted as a partial payment of the redeem request.
+    ///
+    /// # Arguments
+    ///
+    /// * `redeem_id` - 256-bit identifier of the redeem request
+    pub fn has_partial_payments(redeem_id: &H256) -> bool {
+        RedeemPaymentTransactions::<T>::iter_prefix(redeem_id).next().is_some()
+    }
+
+    /// Checks if the transaction has been counted as a partial payment of the redeem request.
+    ///
+    /// # Arguments
+    ///
+    /// * `redeem_id` - 256-bit identifier of the redeem request
+    /// * `tx_id` - id of the Bitcoin transaction
+    pub fn is_partial_payment_transaction(redeem_id: &H256, tx_id: H256Le) -> bool {
+        RedeemPaymentTransactions::<T>::contains_key(redeem_id, tx_id)
+    }
+
+    /// Ensures that the redeem request is not part of a batch, which is executed as a whole.
+    fn ensure_not_batched(redeem_id: &H256) -> DispatchResult {
+        ensure!(
+            !BatchedRedeemRequests::<T>::contains_key(redeem_id),
+            Error::<T>::RedeemRequestInBatch
+        );
+        Ok(())
+    }
+
     /// Fetch a pre-existing open or completed redeem request or throw.
     /// Cancelled requests are not returned.
     ///
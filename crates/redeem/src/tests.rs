@@ -2,7 +2,7 @@ use crate::{ext, mock::*};
 
 use crate::types::{Collateral, RedeemRequest, RedeemRequestStatus, Wrapped};
 use bitcoin::types::{MerkleProof, Transaction};
-use btc_relay::{BtcAddress, BtcPublicKey};
+use btc_relay::{BtcAddress, BtcPublicKey, PaymentVerification};
 use currency::Amount;
 use frame_support::{assert_err, assert_noop, assert_ok, dispatch::DispatchError};
 use mocktopus::mocking::*;
@@ -528,6 +528,428 @@ fn test_execute_redeem_succeeds() {
     })
 }
 
+fn inject_partially_payable_redeem_request(transfer_fee_btc: Balance) {
+    inject_redeem_request(
+        H256([0u8; 32]),
+        RedeemRequest {
+            period: 0,
+            vault: BOB,
+            opentime: 40,
+            fee: 0,
+            amount_btc: 100,
+            premium: 0,
+            redeemer: ALICE,
+            btc_address: BtcAddress::random(),
+            btc_height: 0,
+            status: RedeemRequestStatus::Pending,
+            transfer_fee_btc,
+        },
+    );
+    ext::btc_relay::parse_merkle_proof::<Test>.mock_safe(|_| MockResult::Return(Ok(dummy_merkle_proof())));
+    // derive distinct transactions from the raw bytes
+    ext::btc_relay::parse_transaction::<Test>.mock_safe(|raw_tx| {
+        MockResult::Return(Ok(Transaction {
+            version: raw_tx[0] as i32,
+            ..Default::default()
+        }))
+    });
+    ext::vault_registry::get_collateral_currency::<Test>
+        .mock_safe(|_| MockResult::Return(Ok(DEFAULT_TESTING_CURRENCY)));
+}
+
+#[test]
+fn test_execute_partial_redeem_succeeds() {
+    run_test(|| {
+        Security::<Test>::set_active_block_number(40);
+        let btc_fee = Redeem::get_current_inclusion_fee().unwrap();
+        inject_partially_payable_redeem_request(btc_fee.amount());
+
+        // the confirmations are looked up for the amount of the request, not of the payment
+        let request_amount = 100 + btc_fee.amount();
+        ext::btc_relay::get_and_verify_op_return_payment::<Test, Balance>.mock_safe(move |_, _, _, amount, _| {
+            assert_eq!(amount, request_amount);
+            MockResult::Return(Ok(40))
+        });
+        Amount::<Test>::burn_from.mock_safe(|amount_wrapped, _| {
+            assert_eq!(amount_wrapped, &wrapped(40));
+            MockResult::Return(Ok(()))
+        });
+        ext::vault_registry::redeem_tokens::<Test>.mock_safe(|vault, amount_wrapped, premium, _| {
+            assert_eq!(vault, &BOB);
+            assert_eq!(amount_wrapped, &wrapped(40));
+            assert_eq!(premium, &collateral(0));
+            MockResult::Return(Ok(()))
+        });
+
+        assert_ok!(Redeem::execute_partial_redeem(
+            Origin::signed(BOB),
+            H256([0u8; 32]),
+            Vec::default(),
+            vec![1u8]
+        ));
+        assert_emitted!(Event::PartialRedeemPayment(H256([0; 32]), ALICE, BOB, 40, 40));
+        assert_eq!(Redeem::partial_redeem_payment(H256([0u8; 32])), 40);
+        assert_ok!(Redeem::get_open_redeem_request_from_id(&H256([0u8; 32])));
+
+        // the remaining payment completes the request
+        ext::btc_relay::get_and_verify_op_return_payment::<Test, Balance>
+            .mock_safe(|_, _, _, _, _| MockResult::Return(Ok(60)));
+        Amount::<Test>::burn_from.mock_safe(move |amount_wrapped, _| {
+            assert_eq!(amount_wrapped, &(wrapped(60) + btc_fee));
+            MockResult::Return(Ok(()))
+        });
+        ext::vault_registry::redeem_tokens::<Test>.mock_safe(move |_, amount_wrapped, _, _| {
+            assert_eq!(amount_wrapped, &(wrapped(60) + btc_fee));
+            MockResult::Return(Ok(()))
+        });
+
+        assert_ok!(Redeem::execute_partial_redeem(
+            Origin::signed(BOB),
+            H256([0u8; 32]),
+            Vec::default(),
+            vec![2u8]
+        ));
+        assert_emitted!(Event::ExecuteRedeem(
+            H256([0; 32]),
+            ALICE,
+            100,
+            0,
+            BOB,
+            btc_fee.amount(),
+        ));
+        assert_err!(
+            Redeem::get_open_redeem_request_from_id(&H256([0u8; 32])),
+            TestError::RedeemCompleted,
+        );
+    })
+}
+
+#[test]
+fn test_execute_partial_redeem_fails_with_payment_exceeding_amount() {
+    run_test(|| {
+        Security::<Test>::set_active_block_number(40);
+        inject_partially_payable_redeem_request(0);
+
+        ext::btc_relay::get_and_verify_op_return_payment::<Test, Balance>
+            .mock_safe(|_, _, _, _, _| MockResult::Return(Ok(101)));
+
+        assert_noop!(
+            Redeem::execute_partial_redeem(Origin::signed(BOB), H256([0u8; 32]), Vec::default(), vec![1u8]),
+            TestError::PaymentExceedsRedeemAmount
+        );
+    })
+}
+
+#[test]
+fn test_execute_partial_redeem_fails_with_reused_transaction() {
+    run_test(|| {
+        Security::<Test>::set_active_block_number(40);
+        inject_partially_payable_redeem_request(0);
+
+        ext::btc_relay::get_and_verify_op_return_payment::<Test, Balance>
+            .mock_safe(|_, _, _, _, _| MockResult::Return(Ok(40)));
+        Amount::<Test>::burn_from.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::redeem_tokens::<Test>.mock_safe(|_, _, _, _, _| MockResult::Return(Ok(())));
+
+        assert_ok!(Redeem::execute_partial_redeem(
+            Origin::signed(BOB),
+            H256([0u8; 32]),
+            Vec::default(),
+            vec![1u8]
+        ));
+        assert_noop!(
+            Redeem::execute_partial_redeem(Origin::signed(BOB), H256([0u8; 32]), Vec::default(), vec![1u8]),
+            TestError::PaymentAlreadyCounted
+        );
+    })
+}
+
+#[test]
+fn test_verify_redeem_payment_reports_partial_payments() {
+    run_test(|| {
+        Security::<Test>::set_active_block_number(40);
+        inject_partially_payable_redeem_request(0);
+
+        ext::btc_relay::get_and_verify_op_return_payment::<Test, Balance>
+            .mock_safe(|_, _, _, _, _| MockResult::Return(Ok(40)));
+        Amount::<Test>::burn_from.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::redeem_tokens::<Test>.mock_safe(|_, _, _, _, _| MockResult::Return(Ok(())));
+        assert_ok!(Redeem::execute_partial_redeem(
+            Origin::signed(BOB),
+            H256([0u8; 32]),
+            Vec::default(),
+            vec![1u8]
+        ));
+
+        // the payment does not pay the full amount, but pays what is left of it
+        ext::btc_relay::dry_run_op_return_payment::<Test, Balance>.mock_safe(|_, _, _, expected_amount, _| {
+            MockResult::Return(PaymentVerification {
+                amount_found: Some(60),
+                expected_amount,
+                refund_address: None,
+                error: Some(TestError::InvalidPaymentAmount.into()),
+            })
+        });
+        ext::btc_relay::dry_run_op_return_part_payment::<Test, Balance>.mock_safe(
+            |_, raw_tx, _, expected_amount, request_amount, _| {
+                assert_eq!(request_amount, 100);
+                MockResult::Return(PaymentVerification {
+                    amount_found: Some(raw_tx[0] as Balance * 10),
+                    expected_amount,
+                    refund_address: None,
+                    error: None,
+                })
+            },
+        );
+
+        let verification = Redeem::verify_redeem_payment(H256([0u8; 32]), Vec::default(), vec![6u8]).unwrap();
+        assert_eq!(verification.amount_paid, 40);
+        assert_eq!(verification.payment.error, Some(TestError::InvalidPaymentAmount.into()));
+        assert_eq!(
+            verification.partial_payment,
+            PaymentVerification {
+                amount_found: Some(60),
+                expected_amount: 60,
+                refund_address: None,
+                error: None,
+            }
+        );
+
+        let verification = Redeem::verify_redeem_payment(H256([0u8; 32]), Vec::default(), vec![7u8]).unwrap();
+        assert_eq!(
+            verification.partial_payment.error,
+            Some(TestError::PaymentExceedsRedeemAmount.into())
+        );
+
+        let verification = Redeem::verify_redeem_payment(H256([0u8; 32]), Vec::default(), vec![1u8]).unwrap();
+        assert_eq!(
+            verification.partial_payment.error,
+            Some(TestError::PaymentAlreadyCounted.into())
+        );
+    })
+}
+
+#[test]
+fn test_request_redeem_with_auto_vault_succeeds() {
+    run_test(|| {
+        convert_to.mock_safe(|_, x| MockResult::Return(btcdot_parity(x)));
+        <vault_registry::Pallet<Test>>::insert_vault(&BOB, default_vault());
+
+        let amount = 90;
+        ext::vault_registry::select_vault_for_redeem::<Test>.mock_safe(move |amount_wrapped, currency_id, _| {
+            assert_eq!(amount_wrapped, &wrapped(amount));
+            assert_eq!(currency_id, DEFAULT_TESTING_CURRENCY);
+            MockResult::Return(Ok(BOB))
+        });
+        ext::vault_registry::try_increase_to_be_redeemed_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::is_vault_below_premium_threshold::<Test>.mock_safe(move |_| MockResult::Return(Ok(false)));
+        ext::fee::get_redeem_fee::<Test>.mock_safe(move |_| MockResult::Return(Ok(wrapped(0))));
+
+        assert_ok!(Redeem::request_redeem_with_auto_vault(
+            Origin::signed(ALICE),
+            amount,
+            BtcAddress::P2PKH(H160::zero()),
+            DEFAULT_TESTING_CURRENCY
+        ));
+
+        let redeem_id = System::events()
+            .into_iter()
+            .find_map(|record| match record.event {
+                TestEvent::Redeem(Event::RequestRedeem(redeem_id, ..)) => Some(redeem_id),
+                _ => None,
+            })
+            .unwrap();
+        let redeem = Redeem::get_open_redeem_request_from_id(&redeem_id).unwrap();
+        assert_eq!(redeem.vault, BOB);
+        assert_eq!(redeem.redeemer, ALICE);
+    })
+}
+
+#[test]
+fn test_request_batch_redeem_succeeds() {
+    run_test(|| {
+        convert_to.mock_safe(|_, x| MockResult::Return(btcdot_parity(x)));
+        <vault_registry::Pallet<Test>>::insert_vault(&BOB, default_vault());
+
+        ext::vault_registry::try_increase_to_be_redeemed_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::is_vault_below_premium_threshold::<Test>.mock_safe(move |_| MockResult::Return(Ok(false)));
+        ext::fee::get_redeem_fee::<Test>.mock_safe(move |_| MockResult::Return(Ok(wrapped(0))));
+        let btc_fee = Redeem::get_current_inclusion_fee().unwrap();
+
+        let first_address = BtcAddress::P2PKH(H160::zero());
+        let second_address = BtcAddress::P2PKH(H160::repeat_byte(1));
+        let batch_id =
+            Redeem::_request_batch_redeem(ALICE, vec![(first_address, 90), (second_address, 50)], BOB).unwrap();
+
+        let redeem_ids = crate::RedeemBatches::<Test>::get(batch_id).unwrap();
+        assert_eq!(redeem_ids.len(), 2);
+
+        // the inclusion fee is only charged once
+        let first = Redeem::get_open_redeem_request_from_id(&redeem_ids[0]).unwrap();
+        assert_eq!(first.btc_address, first_address);
+        assert_eq!(first.amount_btc, 90 - btc_fee.amount());
+        assert_eq!(first.transfer_fee_btc, btc_fee.amount());
+        let second = Redeem::get_open_redeem_request_from_id(&redeem_ids[1]).unwrap();
+        assert_eq!(second.btc_address, second_address);
+        assert_eq!(second.amount_btc, 50);
+        assert_eq!(second.transfer_fee_btc, 0);
+
+        assert_emitted!(Event::RequestBatchRedeem(batch_id, ALICE, BOB, redeem_ids));
+    })
+}
+
+#[test]
+fn test_request_batch_redeem_fails_with_duplicate_address() {
+    run_test(|| {
+        assert_noop!(
+            Redeem::request_batch_redeem(
+                Origin::signed(ALICE),
+                vec![
+                    (BtcAddress::P2PKH(H160::zero()), 90),
+                    (BtcAddress::P2PKH(H160::zero()), 50)
+                ],
+                BOB
+            ),
+            TestError::InvalidRedeemBatch
+        );
+        assert_noop!(
+            Redeem::request_batch_redeem(Origin::signed(ALICE), vec![], BOB),
+            TestError::InvalidRedeemBatch
+        );
+    })
+}
+
+#[test]
+fn test_execute_batch_redeem_succeeds() {
+    run_test(|| {
+        Security::<Test>::set_active_block_number(40);
+        let first_address = BtcAddress::P2PKH(H160::zero());
+        let second_address = BtcAddress::P2PKH(H160::repeat_byte(1));
+        for (i, (btc_address, amount_btc)) in vec![(first_address, 100), (second_address, 50)].into_iter().enumerate() {
+            inject_redeem_request(
+                H256::repeat_byte(i as u8 + 1),
+                RedeemRequest {
+                    period: 0,
+                    vault: BOB,
+                    opentime: 40,
+                    fee: 0,
+                    amount_btc,
+                    premium: 0,
+                    redeemer: ALICE,
+                    btc_address,
+                    btc_height: 0,
+                    status: RedeemRequestStatus::Pending,
+                    transfer_fee_btc: 0,
+                },
+            );
+        }
+        let batch_id = H256::zero();
+        crate::RedeemBatches::<Test>::insert(batch_id, vec![H256::repeat_byte(1), H256::repeat_byte(2)]);
+
+        ext::btc_relay::parse_merkle_proof::<Test>.mock_safe(|_| MockResult::Return(Ok(dummy_merkle_proof())));
+        ext::btc_relay::parse_transaction::<Test>.mock_safe(|_| MockResult::Return(Ok(Transaction::default())));
+        ext::btc_relay::verify_and_validate_batch_op_return_transaction::<Test, Balance>.mock_safe(
+            move |_, _, expected_payments, op_return_id| {
+                assert_eq!(expected_payments, vec![(first_address, 100), (second_address, 50)]);
+                assert_eq!(op_return_id, H256::zero());
+                MockResult::Return(Ok(()))
+            },
+        );
+        Amount::<Test>::burn_from.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::redeem_tokens::<Test>.mock_safe(|_, _, _, _, _| MockResult::Return(Ok(())));
+        ext::vault_registry::get_collateral_currency::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(DEFAULT_TESTING_CURRENCY)));
+
+        assert_ok!(Redeem::execute_batch_redeem(
+            Origin::signed(BOB),
+            batch_id,
+            Vec::default(),
+            Vec::default()
+        ));
+        assert_emitted!(Event::ExecuteRedeem(H256::repeat_byte(1), ALICE, 100, 0, BOB, 0));
+        assert_emitted!(Event::ExecuteRedeem(H256::repeat_byte(2), ALICE, 50, 0, BOB, 0));
+        assert_emitted!(Event::ExecuteBatchRedeem(batch_id));
+    })
+}
+
+#[test]
+fn test_execute_batch_redeem_skips_cancelled_requests() {
+    run_test(|| {
+        Security::<Test>::set_active_block_number(40);
+        let first_address = BtcAddress::P2PKH(H160::zero());
+        let second_address = BtcAddress::P2PKH(H160::repeat_byte(1));
+        let statuses = vec![RedeemRequestStatus::Reimbursed(false), RedeemRequestStatus::Pending];
+        for (i, ((btc_address, amount_btc), status)) in vec![(first_address, 100), (second_address, 50)]
+            .into_iter()
+            .zip(statuses)
+            .enumerate()
+        {
+            inject_redeem_request(
+                H256::repeat_byte(i as u8 + 1),
+                RedeemRequest {
+                    period: 0,
+                    vault: BOB,
+                    opentime: 40,
+                    fee: 0,
+                    amount_btc,
+                    premium: 0,
+                    redeemer: ALICE,
+                    btc_address,
+                    btc_height: 0,
+                    status,
+                    transfer_fee_btc: 0,
+                },
+            );
+        }
+        let batch_id = H256::zero();
+        crate::RedeemBatches::<Test>::insert(batch_id, vec![H256::repeat_byte(1), H256::repeat_byte(2)]);
+        crate::BatchedRedeemRequests::<Test>::insert(H256::repeat_byte(2), batch_id);
+
+        ext::btc_relay::parse_merkle_proof::<Test>.mock_safe(|_| MockResult::Return(Ok(dummy_merkle_proof())));
+        ext::btc_relay::parse_transaction::<Test>.mock_safe(|_| MockResult::Return(Ok(Transaction::default())));
+        ext::btc_relay::verify_and_validate_batch_op_return_transaction::<Test, Balance>.mock_safe(
+            move |_, _, expected_payments, _| {
+                // the payment still covers the cancelled request
+                assert_eq!(expected_payments, vec![(first_address, 100), (second_address, 50)]);
+                MockResult::Return(Ok(()))
+            },
+        );
+        Amount::<Test>::burn_from.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::redeem_tokens::<Test>.mock_safe(|_, _, _, _, _| MockResult::Return(Ok(())));
+        ext::vault_registry::get_collateral_currency::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(DEFAULT_TESTING_CURRENCY)));
+
+        // batched requests cannot be executed on their own
+        assert_noop!(
+            Redeem::execute_redeem(
+                Origin::signed(BOB),
+                H256::repeat_byte(2),
+                Vec::default(),
+                Vec::default()
+            ),
+            TestError::RedeemRequestInBatch
+        );
+        // neither can their payment be verified on its own
+        assert_err!(
+            Redeem::verify_redeem_payment(H256::repeat_byte(2), Vec::default(), Vec::default()),
+            TestError::RedeemRequestInBatch
+        );
+
+        assert_ok!(Redeem::execute_batch_redeem(
+            Origin::signed(BOB),
+            batch_id,
+            Vec::default(),
+            Vec::default()
+        ));
+        assert_emitted!(Event::ExecuteRedeem(H256::repeat_byte(1), ALICE, 100, 0, BOB, 0), 0);
+        assert_emitted!(Event::ExecuteRedeem(H256::repeat_byte(2), ALICE, 50, 0, BOB, 0));
+        assert_eq!(
+            Redeem::get_open_or_completed_redeem_batch_payments(&batch_id),
+            Ok(vec![(first_address, 100), (second_address, 50)])
+        );
+    })
+}
+
 #[test]
 fn test_cancel_redeem_fails_with_redeem_id_not_found() {
     run_test(|| {
@@ -647,6 +1069,47 @@ fn test_cancel_redeem_succeeds() {
     })
 }
 
+#[test]
+fn test_cancel_redeem_excludes_partial_payments() {
+    run_test(|| {
+        Security::<Test>::set_active_block_number(40);
+        let btc_fee = Redeem::get_current_inclusion_fee().unwrap();
+        inject_partially_payable_redeem_request(btc_fee.amount());
+
+        ext::btc_relay::get_and_verify_op_return_payment::<Test, Balance>
+            .mock_safe(|_, _, _, _, _| MockResult::Return(Ok(40)));
+        Amount::<Test>::burn_from.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::redeem_tokens::<Test>.mock_safe(|_, _, _, _, _| MockResult::Return(Ok(())));
+        assert_ok!(Redeem::execute_partial_redeem(
+            Origin::signed(BOB),
+            H256([0u8; 32]),
+            Vec::default(),
+            vec![1u8]
+        ));
+
+        ext::btc_relay::has_request_expired::<Test>.mock_safe(|_, _, _| MockResult::Return(Ok(true)));
+        ext::vault_registry::ban_vault::<Test>.mock_safe(|_| MockResult::Return(Ok(())));
+        ext::vault_registry::transfer_funds_saturated::<Test>
+            .mock_safe(move |_, _, amount| MockResult::Return(Ok(amount.clone())));
+        ext::vault_registry::get_vault_from_id::<Test>.mock_safe(|_| {
+            MockResult::Return(Ok(vault_registry::types::Vault {
+                status: VaultStatus::Active(true),
+                ..vault_registry::types::Vault::new(Default::default(), Default::default(), DEFAULT_TESTING_CURRENCY)
+            }))
+        });
+        Amount::<Test>::unlock_on.mock_safe(move |amount, _| {
+            assert_eq!(amount, &(wrapped(60) + btc_fee));
+            MockResult::Return(Ok(()))
+        });
+        ext::vault_registry::decrease_to_be_redeemed_tokens::<Test>.mock_safe(move |_, amount| {
+            assert_eq!(amount, &(wrapped(60) + btc_fee));
+            MockResult::Return(Ok(()))
+        });
+
+        assert_ok!(Redeem::cancel_redeem(Origin::signed(ALICE), H256([0u8; 32]), false));
+    })
+}
+
 #[test]
 fn test_mint_tokens_for_reimbursed_redeem() {
     // PRECONDITION: The vault MUST NOT be banned.
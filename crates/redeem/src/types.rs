@@ -1,3 +1,4 @@
+pub use btc_relay::PaymentVerification;
 pub use primitives::redeem::{RedeemRequest, RedeemRequestStatus};
 use sp_runtime::DispatchError;
 
@@ -28,6 +29,19 @@ pub(crate) type Wrapped<T> = BalanceOf<T>;
 pub type DefaultRedeemRequest<T> =
     RedeemRequest<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber, BalanceOf<T>>;
 
+/// Outcome of a dry run of the payment verification of a redeem request
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct RedeemPaymentVerification<Balance> {
+    /// the payment verified as the full payment of `execute_redeem`
+    pub payment: PaymentVerification<Balance>,
+    /// the payment verified as a part payment of `execute_partial_redeem`, expecting the amount
+    /// that is left to be paid
+    pub partial_payment: PaymentVerification<Balance>,
+    /// the amount that has already been paid by partial payments
+    pub amount_paid: Balance,
+}
+
 pub trait RedeemRequestExt<T: Config> {
     fn amount_btc(&self) -> Amount<T>;
     fn fee(&self) -> Amount<T>;
@@ -96,7 +96,7 @@ benchmarks! {
             .with_previous_hash(block_hash)
             .with_version(4)
             .with_coinbase(&origin_btc_address, 50, 3)
-            .with_timestamp(1588813835)
+            .with_timestamp(1588814835)
             .add_transaction(transaction.clone())
             .mine(U256::from(2).pow(254.into())).unwrap();
 
@@ -2,12 +2,13 @@ use super::*;
 use crate::types::BalanceOf;
 use bitcoin::{
     formatter::{Formattable, TryFormattable},
+    parser::parse_transaction,
     types::{
         BlockBuilder, H256Le, RawBlockHeader, TransactionBuilder, TransactionInputBuilder, TransactionInputSource,
         TransactionOutput,
     },
 };
-use btc_relay::{BtcAddress, BtcPublicKey, Pallet as BtcRelay};
+use btc_relay::{BtcAddress, BtcPublicKey, Pallet as BtcRelay, MEDIAN_TIME_SPAN};
 use currency::Amount;
 use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite};
 use frame_support::{assert_ok, traits::Get};
@@ -36,6 +37,40 @@ fn dummy_public_key() -> BtcPublicKey {
     ])
 }
 
+/// Transaction funding a P2WPKH output of the vault
+const FUNDING_TX: [u8; 148] = [
+    2, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0,
+    0, 0, 0, 0, 255, 255, 255, 255, 3, 160, 134, 1, 0, 0, 0, 0, 0, 22, 0, 20, 252, 114, 80, 162, 17, 222, 221, 199, 14,
+    229, 162, 115, 141, 229, 240, 120, 23, 53, 28, 239, 64, 13, 3, 0, 0, 0, 0, 0, 25, 118, 169, 20, 252, 114, 80, 162,
+    17, 222, 221, 199, 14, 229, 162, 115, 141, 229, 240, 120, 23, 53, 28, 239, 136, 172, 224, 147, 4, 0, 0, 0, 0, 0,
+    23, 169, 20, 236, 143, 61, 156, 39, 99, 160, 153, 122, 70, 91, 150, 141, 153, 219, 71, 232, 46, 105, 210, 135, 0,
+    0, 0, 0,
+];
+
+/// Signed transaction spending all outputs of `FUNDING_TX` to a non-vault address
+const THEFT_TX: [u8; 515] = [
+    2, 0, 0, 0, 0, 1, 3, 182, 68, 121, 65, 109, 166, 248, 132, 181, 213, 12, 44, 68, 78, 172, 230, 127, 216, 96, 185,
+    156, 18, 188, 156, 57, 242, 250, 110, 69, 28, 151, 158, 0, 0, 0, 0, 0, 255, 255, 255, 255, 182, 68, 121, 65, 109,
+    166, 248, 132, 181, 213, 12, 44, 68, 78, 172, 230, 127, 216, 96, 185, 156, 18, 188, 156, 57, 242, 250, 110, 69, 28,
+    151, 158, 1, 0, 0, 0, 106, 71, 48, 68, 2, 32, 122, 255, 182, 205, 13, 57, 248, 234, 197, 149, 131, 147, 166, 225,
+    221, 189, 253, 134, 179, 178, 146, 52, 195, 187, 13, 185, 67, 157, 135, 10, 116, 121, 2, 32, 49, 167, 140, 86, 55,
+    128, 61, 80, 134, 250, 216, 162, 232, 91, 226, 83, 137, 15, 155, 26, 117, 148, 200, 51, 148, 101, 99, 156, 21, 195,
+    3, 119, 1, 33, 3, 79, 53, 91, 220, 183, 204, 10, 247, 40, 239, 60, 206, 185, 97, 93, 144, 104, 75, 181, 178, 202,
+    95, 133, 154, 176, 240, 183, 4, 7, 88, 113, 170, 255, 255, 255, 255, 182, 68, 121, 65, 109, 166, 248, 132, 181,
+    213, 12, 44, 68, 78, 172, 230, 127, 216, 96, 185, 156, 18, 188, 156, 57, 242, 250, 110, 69, 28, 151, 158, 2, 0, 0,
+    0, 23, 22, 0, 20, 252, 114, 80, 162, 17, 222, 221, 199, 14, 229, 162, 115, 141, 229, 240, 120, 23, 53, 28, 239,
+    255, 255, 255, 255, 1, 176, 0, 9, 0, 0, 0, 0, 0, 25, 118, 169, 20, 34, 34, 34, 34, 34, 34, 34, 34, 34, 34, 34, 34,
+    34, 34, 34, 34, 34, 34, 34, 34, 136, 172, 2, 72, 48, 69, 2, 33, 0, 183, 84, 26, 165, 22, 51, 125, 146, 90, 170,
+    167, 54, 37, 218, 160, 119, 44, 85, 248, 207, 205, 116, 35, 103, 204, 20, 78, 126, 14, 40, 117, 35, 2, 32, 124, 83,
+    151, 133, 104, 58, 48, 56, 176, 238, 133, 158, 89, 65, 133, 235, 40, 95, 254, 198, 80, 124, 94, 47, 172, 212, 52,
+    76, 239, 255, 249, 36, 1, 33, 3, 79, 53, 91, 220, 183, 204, 10, 247, 40, 239, 60, 206, 185, 97, 93, 144, 104, 75,
+    181, 178, 202, 95, 133, 154, 176, 240, 183, 4, 7, 88, 113, 170, 0, 2, 72, 48, 69, 2, 33, 0, 195, 184, 56, 236, 235,
+    236, 205, 159, 218, 39, 33, 191, 116, 180, 63, 47, 128, 133, 35, 84, 44, 75, 255, 135, 231, 53, 112, 235, 20, 114,
+    8, 52, 2, 32, 109, 210, 38, 145, 31, 158, 253, 160, 68, 186, 89, 35, 131, 47, 115, 2, 68, 141, 99, 242, 124, 244,
+    1, 62, 1, 136, 104, 37, 138, 250, 8, 53, 1, 33, 3, 79, 53, 91, 220, 183, 204, 10, 247, 40, 239, 60, 206, 185, 97,
+    93, 144, 104, 75, 181, 178, 202, 95, 133, 154, 176, 240, 183, 4, 7, 88, 113, 170, 0, 0, 0, 0,
+];
+
 fn mint_collateral<T: crate::Config>(account_id: &T::AccountId, amount: BalanceOf<T>) {
     <orml_tokens::Pallet<T>>::deposit(DEFAULT_TESTING_CURRENCY, account_id, amount).unwrap();
 }
@@ -76,11 +111,24 @@ benchmarks! {
 
         BtcRelay::<T>::initialize(origin.clone(), block_header, height).unwrap();
 
+        // store enough predecessors to compute the full median time past
+        let mut prev_hash = init_block_hash;
+        for i in 0..MEDIAN_TIME_SPAN as u32 {
+            let block = BlockBuilder::new()
+                .with_previous_hash(prev_hash)
+                .with_version(4)
+                .with_coinbase(&address, 50, 3)
+                .with_timestamp(1588814835 + i)
+                .mine(U256::from(2).pow(254.into())).unwrap();
+            prev_hash = block.header.hash;
+            BtcRelay::<T>::store_block_header(&origin, block.header).unwrap();
+        }
+
         let block = BlockBuilder::new()
-            .with_previous_hash(init_block_hash)
+            .with_previous_hash(prev_hash)
             .with_version(4)
             .with_coinbase(&address, 50, 3)
-            .with_timestamp(1588814835)
+            .with_timestamp(1588815835)
             .mine(U256::from(2).pow(254.into())).unwrap();
 
         let raw_block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap())
@@ -88,6 +136,57 @@ benchmarks! {
 
     }: _(RawOrigin::Signed(origin), raw_block_header)
 
+    store_block_headers {
+        let h in 1 .. MAX_BLOCK_HEADERS_PER_BATCH;
+
+        let origin: T::AccountId = account("Origin", 0, 0);
+
+        let address = BtcAddress::P2PKH(H160::from([0; 20]));
+        let height = 0;
+
+        let init_block = BlockBuilder::new()
+            .with_version(4)
+            .with_coinbase(&address, 50, 3)
+            .with_timestamp(1588813835)
+            .mine(U256::from(2).pow(254.into())).unwrap();
+
+        let mut prev_hash = init_block.header.hash;
+        let raw_block_header = RawBlockHeader::from_bytes(&init_block.header.try_format().unwrap())
+            .expect("could not serialize block header");
+        let block_header = BtcRelay::<T>::parse_raw_block_header(&raw_block_header).unwrap();
+
+        BtcRelay::<T>::initialize(origin.clone(), block_header, height).unwrap();
+
+        // store enough predecessors to compute the full median time past
+        for i in 0..MEDIAN_TIME_SPAN as u32 {
+            let block = BlockBuilder::new()
+                .with_previous_hash(prev_hash)
+                .with_version(4)
+                .with_coinbase(&address, 50, 3)
+                .with_timestamp(1588814835 + i)
+                .mine(U256::from(2).pow(254.into())).unwrap();
+            prev_hash = block.header.hash;
+            BtcRelay::<T>::store_block_header(&origin, block.header).unwrap();
+        }
+
+        let mut raw_block_headers = vec![];
+        for i in 0..h {
+            let block = BlockBuilder::new()
+                .with_previous_hash(prev_hash)
+                .with_version(4)
+                .with_coinbase(&address, 50, 3)
+                .with_timestamp(1588815835 + i)
+                .mine(U256::from(2).pow(254.into())).unwrap();
+            prev_hash = block.header.hash;
+
+            raw_block_headers.push(
+                RawBlockHeader::from_bytes(&block.header.try_format().unwrap())
+                    .expect("could not serialize block header")
+            );
+        }
+
+    }: _(RawOrigin::Signed(origin), raw_block_headers)
+
     report_vault_theft {
         let origin: T::AccountId = account("Origin", 0, 0);
         let relayer_id: T::AccountId = account("Relayer", 0, 0);
@@ -156,7 +255,7 @@ benchmarks! {
             .with_previous_hash(block_hash)
             .with_version(4)
             .with_coinbase(&address, 50, 3)
-            .with_timestamp(1588813835)
+            .with_timestamp(1588814835)
             .add_transaction(transaction.clone())
             .mine(U256::from(2).pow(254.into())).unwrap();
 
@@ -174,6 +273,69 @@ benchmarks! {
             <T as currency::Config>::UnsignedFixedPoint::one()
         ).unwrap();
     }: _(RawOrigin::Signed(origin), vault_id, proof, raw_tx)
+
+    report_vault_theft_with_signature_proof {
+        let origin: T::AccountId = account("Origin", 0, 0);
+        let relayer_id: T::AccountId = account("Relayer", 0, 0);
+
+        let vault_address = BtcAddress::P2WPKHv0(H160::from_slice(&[
+            252, 114, 80, 162, 17, 222, 221, 199, 14, 229, 162, 115, 141, 229, 240, 120, 23, 53,
+            28, 239,
+        ]));
+
+        let address = BtcAddress::P2PKH(H160([0; 20]));
+
+        let vault_id: T::AccountId = account("Vault", 0, 0);
+        let mut vault = Vault {
+            wallet: Wallet::new(dummy_public_key()),
+            id: vault_id.clone(),
+            ..Vault::new(Default::default(), Default::default(), T::GetGriefingCollateralCurrencyId::get())
+        };
+        vault.wallet.add_btc_address(vault_address);
+        VaultRegistry::<T>::insert_vault(
+            &vault_id,
+            vault
+        );
+
+        mint_collateral::<T>(&vault_id, 1000u32.into());
+        assert_ok!(VaultRegistry::<T>::try_deposit_collateral(&vault_id, &Amount::new(1000u32.into(), T::GetGriefingCollateralCurrencyId::get())));
+
+        let height = 0;
+        let block = BlockBuilder::new()
+            .with_version(4)
+            .with_coinbase(&address, 50, 3)
+            .with_timestamp(1588813835)
+            .mine(U256::from(2).pow(254.into())).unwrap();
+
+        let block_hash = block.header.hash;
+        let raw_block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
+        let block_header = BtcRelay::<T>::parse_raw_block_header(&raw_block_header).unwrap();
+
+        Security::<T>::set_active_block_number(1u32.into());
+        BtcRelay::<T>::initialize(relayer_id.clone(), block_header, height).unwrap();
+
+        let transaction = parse_transaction(&THEFT_TX).unwrap();
+        let block = BlockBuilder::new()
+            .with_previous_hash(block_hash)
+            .with_version(4)
+            .with_coinbase(&address, 50, 3)
+            .with_timestamp(1588814835)
+            .add_transaction(transaction.clone())
+            .mine(U256::from(2).pow(254.into())).unwrap();
+
+        let tx_id = transaction.tx_id();
+        let proof = block.merkle_proof(&[tx_id]).unwrap().try_format().unwrap();
+
+        let raw_block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
+        let block_header = BtcRelay::<T>::parse_raw_block_header(&raw_block_header).unwrap();
+
+        BtcRelay::<T>::store_block_header(&relayer_id, block_header).unwrap();
+        Security::<T>::set_active_block_number(Security::<T>::active_block_number() + BtcRelay::<T>::parachain_confirmations() + 1u32.into());
+
+        Oracle::<T>::_set_exchange_rate(DEFAULT_TESTING_CURRENCY,
+            <T as currency::Config>::UnsignedFixedPoint::one()
+        ).unwrap();
+    }: _(RawOrigin::Signed(origin), vault_id, proof, THEFT_TX.to_vec(), 0, FUNDING_TX.to_vec())
 }
 
 impl_benchmark_test_suite!(Relay, crate::mock::ExtBuilder::build_with(|_| {}), crate::mock::Test);
@@ -36,7 +36,9 @@ use sp_std::marker::PhantomData;
 pub trait WeightInfo {
 	fn initialize() -> Weight;
 	fn store_block_header() -> Weight;
+	fn store_block_headers(h: u32, ) -> Weight;
 	fn report_vault_theft() -> Weight;
+	fn report_vault_theft_with_signature_proof() -> Weight;
 }
 
 /// Weights for relay using the Substrate node and recommended hardware.
@@ -58,17 +60,40 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().writes(8 as Weight))
 	}
 	// Storage: Security ParachainStatus (r:1 w:0)
-	// Storage: BTCRelay BlockHeaders (r:2 w:1)
+	// Storage: BTCRelay BlockHeaders (r:12 w:1)
 	// Storage: BTCRelay ChainsIndex (r:1 w:1)
 	// Storage: BTCRelay DisableDifficultyCheck (r:1 w:0)
 	// Storage: BTCRelay ChainsHashes (r:1 w:1)
 	// Storage: Security ActiveBlockCount (r:1 w:0)
 	// Storage: BTCRelay BestBlock (r:0 w:1)
 	// Storage: BTCRelay BestBlockHeight (r:0 w:1)
+	// Storage: BTCRelay BitcoinNetwork (r:1 w:0)
+	// Storage: BTCRelay TestnetPeriodTargets (r:1 w:1)
+	// Estimate: execution time as measured before the header checks grew; the storage accesses add
+	// 10 BlockHeaders reads for the median time past of the 11 preceding headers, a BitcoinNetwork
+	// read and a TestnetPeriodTargets read and write
 	fn store_block_header() -> Weight {
 		(68_306_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(7 as Weight))
-			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads(19 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: BTCRelay BlockHeaders (r:12 w:1)
+	// Storage: BTCRelay ChainsIndex (r:1 w:1)
+	// Storage: BTCRelay DisableDifficultyCheck (r:1 w:0)
+	// Storage: BTCRelay ChainsHashes (r:1 w:1)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: BTCRelay BestBlock (r:0 w:1)
+	// Storage: BTCRelay BestBlockHeight (r:0 w:1)
+	// Storage: BTCRelay BitcoinNetwork (r:1 w:0)
+	// Storage: BTCRelay TestnetPeriodTargets (r:1 w:1)
+	// Estimate: `h` times store_block_header (68_306_000), which reads ParachainStatus once per
+	// call rather than once per header
+	fn store_block_headers(h: u32, ) -> Weight {
+		(68_306_000 as Weight).saturating_mul(h as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((18 as Weight).saturating_mul(h as Weight)))
+			.saturating_add(T::DbWeight::get().writes((6 as Weight).saturating_mul(h as Weight)))
 	}
 	// Storage: Security ParachainStatus (r:1 w:0)
 	// Storage: Relay TheftReports (r:1 w:1)
@@ -106,6 +131,14 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(33 as Weight))
 			.saturating_add(T::DbWeight::get().writes(15 as Weight))
 	}
+	// Storage: as report_vault_theft
+	// Estimate: report_vault_theft (478_713_000) plus 100_000_000 for parsing the spent
+	// transaction, computing its sighash and verifying one signature with libsecp256k1 in the runtime
+	fn report_vault_theft_with_signature_proof() -> Weight {
+		(578_713_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(33 as Weight))
+			.saturating_add(T::DbWeight::get().writes(15 as Weight))
+	}
 }
 
 // For backwards compatibility and tests
@@ -126,17 +159,40 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
 	}
 	// Storage: Security ParachainStatus (r:1 w:0)
-	// Storage: BTCRelay BlockHeaders (r:2 w:1)
+	// Storage: BTCRelay BlockHeaders (r:12 w:1)
 	// Storage: BTCRelay ChainsIndex (r:1 w:1)
 	// Storage: BTCRelay DisableDifficultyCheck (r:1 w:0)
 	// Storage: BTCRelay ChainsHashes (r:1 w:1)
 	// Storage: Security ActiveBlockCount (r:1 w:0)
 	// Storage: BTCRelay BestBlock (r:0 w:1)
 	// Storage: BTCRelay BestBlockHeight (r:0 w:1)
+	// Storage: BTCRelay BitcoinNetwork (r:1 w:0)
+	// Storage: BTCRelay TestnetPeriodTargets (r:1 w:1)
+	// Estimate: execution time as measured before the header checks grew; the storage accesses add
+	// 10 BlockHeaders reads for the median time past of the 11 preceding headers, a BitcoinNetwork
+	// read and a TestnetPeriodTargets read and write
 	fn store_block_header() -> Weight {
 		(68_306_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(19 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	// Storage: Security ParachainStatus (r:1 w:0)
+	// Storage: BTCRelay BlockHeaders (r:12 w:1)
+	// Storage: BTCRelay ChainsIndex (r:1 w:1)
+	// Storage: BTCRelay DisableDifficultyCheck (r:1 w:0)
+	// Storage: BTCRelay ChainsHashes (r:1 w:1)
+	// Storage: Security ActiveBlockCount (r:1 w:0)
+	// Storage: BTCRelay BestBlock (r:0 w:1)
+	// Storage: BTCRelay BestBlockHeight (r:0 w:1)
+	// Storage: BTCRelay BitcoinNetwork (r:1 w:0)
+	// Storage: BTCRelay TestnetPeriodTargets (r:1 w:1)
+	// Estimate: `h` times store_block_header (68_306_000), which reads ParachainStatus once per
+	// call rather than once per header
+	fn store_block_headers(h: u32, ) -> Weight {
+		(68_306_000 as Weight).saturating_mul(h as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((18 as Weight).saturating_mul(h as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((6 as Weight).saturating_mul(h as Weight)))
 	}
 	// Storage: Security ParachainStatus (r:1 w:0)
 	// Storage: Relay TheftReports (r:1 w:1)
@@ -174,5 +230,13 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(33 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(15 as Weight))
 	}
+	// Storage: as report_vault_theft
+	// Estimate: report_vault_theft (478_713_000) plus 100_000_000 for parsing the spent
+	// transaction, computing its sighash and verifying one signature with libsecp256k1 in the runtime
+	fn report_vault_theft_with_signature_proof() -> Weight {
+		(578_713_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(33 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(15 as Weight))
+	}
 }
 
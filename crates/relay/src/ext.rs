@@ -47,6 +47,7 @@ pub(crate) mod btc_relay {
     use bitcoin::types::{BlockHeader, H256Le, MerkleProof, RawBlockHeader};
     use frame_support::dispatch::DispatchResult;
     use sp_runtime::DispatchError;
+    use sp_std::vec::Vec;
 
     pub fn initialize<T: crate::Config>(
         relayer: T::AccountId,
@@ -60,6 +61,13 @@ pub(crate) mod btc_relay {
         <btc_relay::Pallet<T>>::store_block_header(relayer, block_header)
     }
 
+    pub fn store_block_headers<T: crate::Config>(
+        relayer: &T::AccountId,
+        block_headers: Vec<BlockHeader>,
+    ) -> DispatchResult {
+        <btc_relay::Pallet<T>>::store_block_headers(relayer, block_headers)
+    }
+
     pub(crate) fn verify_transaction_inclusion<T: crate::Config>(
         tx_id: H256Le,
         merkle_proof: MerkleProof,
@@ -80,15 +88,37 @@ pub(crate) mod btc_relay {
 
 #[cfg_attr(test, mockable)]
 pub(crate) mod redeem {
+    use crate::types::Wrapped;
+    use bitcoin::types::H256Le;
+    use btc_relay::BtcAddress;
     use frame_support::dispatch::DispatchError;
     use redeem::types::DefaultRedeemRequest;
     use sp_core::H256;
+    use sp_std::vec::Vec;
 
     pub(crate) fn get_open_or_completed_redeem_request_from_id<T: crate::Config>(
         id: &H256,
     ) -> Result<DefaultRedeemRequest<T>, DispatchError> {
         <redeem::Pallet<T>>::get_open_or_completed_redeem_request_from_id(id)
     }
+
+    pub(crate) fn get_open_or_completed_redeem_batch_payments<T: crate::Config>(
+        batch_id: &H256,
+    ) -> Result<Vec<(BtcAddress, Wrapped<T>)>, DispatchError> {
+        <redeem::Pallet<T>>::get_open_or_completed_redeem_batch_payments(batch_id)
+    }
+
+    pub(crate) fn has_partial_payments<T: crate::Config>(redeem_id: &H256) -> bool {
+        <redeem::Pallet<T>>::has_partial_payments(redeem_id)
+    }
+
+    pub(crate) fn is_partial_payment_transaction<T: crate::Config>(redeem_id: &H256, tx_id: H256Le) -> bool {
+        <redeem::Pallet<T>>::is_partial_payment_transaction(redeem_id, tx_id)
+    }
+
+    pub(crate) fn get_partial_payment<T: crate::Config>(redeem_id: &H256) -> Wrapped<T> {
+        <redeem::Pallet<T>>::partial_redeem_payment(redeem_id)
+    }
 }
 
 #[cfg_attr(test, mockable)]
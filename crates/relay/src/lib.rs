@@ -29,11 +29,14 @@ use mocktopus::macros::mockable;
 pub use security;
 
 use crate::types::Wrapped;
-use bitcoin::{parser::parse_transaction, types::*};
+use bitcoin::{interpreter::verify_input, parser::parse_transaction, types::*};
 
 use btc_relay::{types::OpReturnPaymentData, BtcAddress};
 use frame_support::{dispatch::DispatchResult, ensure, transactional, weights::Pays};
-use frame_system::ensure_signed;
+use frame_system::{ensure_root, ensure_signed};
+use redeem::types::{DefaultRedeemRequest, RedeemRequestStatus};
+use sp_core::H256;
+use sp_runtime::traits::Saturating;
 use sp_std::{
     convert::{TryFrom, TryInto},
     vec::Vec,
@@ -42,6 +45,9 @@ use vault_registry::Wallet;
 
 pub use pallet::*;
 
+/// Maximum number of block headers that can be submitted in one `store_block_headers` call.
+pub const MAX_BLOCK_HEADERS_PER_BATCH: u32 = 100;
+
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
@@ -98,6 +104,12 @@ pub mod pallet {
         DuplicateTransaction,
         /// Expected duplicate OP_RETURN ids
         ExpectedDuplicate,
+        /// Theft reports must prove that the vault signed the transaction
+        SignatureProofRequired,
+        /// Input does not spend an output of the given transaction
+        InvalidSignatureProof,
+        /// More than `MAX_BLOCK_HEADERS_PER_BATCH` block headers submitted at once
+        TooManyBlockHeaders,
     }
 
     /// Mapping of Bitcoin transaction identifiers (SHA256 hashes) to account
@@ -107,6 +119,12 @@ pub mod pallet {
     pub(super) type TheftReports<T: Config> =
         StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, H256Le, Option<()>, ValueQuery>;
 
+    /// If set, theft reports must prove that the Vault signed an input of the
+    /// reported transaction, see `report_vault_theft_with_signature_proof`.
+    #[pallet::storage]
+    #[pallet::getter(fn require_signature_proof)]
+    pub(super) type RequireSignatureProof<T: Config> = StorageValue<_, bool, ValueQuery>;
+
     #[pallet::hooks]
     impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
 
@@ -202,6 +220,43 @@ pub mod pallet {
             Ok(Pays::No.into())
         }
 
+        /// Stores a contiguous run of new block headers, where each header extends the previous
+        /// one. Either all headers are stored or, if any of them is invalid, none are.
+        ///
+        /// # Arguments
+        ///
+        /// * `raw_block_headers` - 80 byte raw Bitcoin block headers, ordered by height. At most
+        /// `MAX_BLOCK_HEADERS_PER_BATCH` headers can be submitted at once.
+        ///
+        /// # <weight>
+        /// Key: H (number of headers)
+        /// - The weight of `store_block_header` for each of the H headers.
+        ///
+        /// Total Complexity: O(H * (C + P))
+        /// # </weight>
+        #[pallet::weight(<T as Config>::WeightInfo::store_block_headers(raw_block_headers.len() as u32))]
+        #[transactional]
+        pub fn store_block_headers(
+            origin: OriginFor<T>,
+            raw_block_headers: Vec<RawBlockHeader>,
+        ) -> DispatchResultWithPostInfo {
+            ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            let relayer = ensure_signed(origin)?;
+            ensure!(
+                raw_block_headers.len() <= MAX_BLOCK_HEADERS_PER_BATCH as usize,
+                Error::<T>::TooManyBlockHeaders
+            );
+
+            let block_headers = raw_block_headers
+                .iter()
+                .map(ext::btc_relay::parse_raw_block_header::<T>)
+                .collect::<Result<Vec<_>, _>>()?;
+            ext::btc_relay::store_block_headers::<T>(&relayer, block_headers)?;
+
+            // don't take tx fees on success
+            Ok(Pays::No.into())
+        }
+
         /// Report misbehavior by a Vault, providing a fraud proof (malicious Bitcoin transaction
         /// and the corresponding transaction inclusion proof). This fully slashes the Vault.
         ///
@@ -221,6 +276,7 @@ pub mod pallet {
         ) -> DispatchResultWithPostInfo {
             ext::security::ensure_parachain_status_not_shutdown::<T>()?;
             let reporter_id = ensure_signed(origin)?;
+            ensure!(!Self::require_signature_proof(), Error::<T>::SignatureProofRequired);
 
             let merkle_proof = ext::btc_relay::parse_merkle_proof::<T>(&raw_merkle_proof)?;
             let transaction = parse_transaction(raw_tx.as_slice()).map_err(|_| Error::<T>::InvalidTransaction)?;
@@ -235,18 +291,78 @@ pub mod pallet {
             ext::btc_relay::verify_transaction_inclusion::<T>(tx_id, merkle_proof)?;
             Self::_is_parsed_transaction_invalid(&vault_id, transaction)?;
 
-            ext::vault_registry::liquidate_theft_vault::<T>(&vault_id, reporter_id)?;
+            Self::liquidate_and_record_theft(vault_id, reporter_id, tx_id)?;
+
+            // don't take tx fees on success
+            Ok(Pays::No.into())
+        }
+
+        /// Report misbehavior by a Vault, providing a fraud proof (malicious Bitcoin transaction
+        /// and the corresponding transaction inclusion proof) together with the transaction whose
+        /// output is spent by the input at `input_index`. The signature of that input must be
+        /// valid for a key of the Vault. This fully slashes the Vault.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin`: Any signed user.
+        /// * `vault_id`: The account of the vault to check.
+        /// * `raw_merkle_proof`: The proof of tx inclusion.
+        /// * `raw_tx`: The raw Bitcoin transaction.
+        /// * `input_index`: The input of `raw_tx` signed by the vault.
+        /// * `raw_spent_tx`: The raw Bitcoin transaction containing the output spent by the input.
+        #[pallet::weight(<T as Config>::WeightInfo::report_vault_theft_with_signature_proof())]
+        #[transactional]
+        pub fn report_vault_theft_with_signature_proof(
+            origin: OriginFor<T>,
+            vault_id: T::AccountId,
+            raw_merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+            input_index: u32,
+            raw_spent_tx: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            let reporter_id = ensure_signed(origin)?;
+
+            let merkle_proof = ext::btc_relay::parse_merkle_proof::<T>(&raw_merkle_proof)?;
+            let transaction = parse_transaction(raw_tx.as_slice()).map_err(|_| Error::<T>::InvalidTransaction)?;
+            let spent_transaction =
+                parse_transaction(raw_spent_tx.as_slice()).map_err(|_| Error::<T>::InvalidTransaction)?;
+            let tx_id = transaction.tx_id();
 
-            <TheftReports<T>>::mutate(&vault_id, &tx_id, |inner| {
-                let _ = inner.insert(());
-            });
+            // throw if already reported
+            ensure!(
+                !<TheftReports<T>>::contains_key(&vault_id, &tx_id),
+                Error::<T>::VaultAlreadyReported,
+            );
+
+            ext::btc_relay::verify_transaction_inclusion::<T>(tx_id, merkle_proof)?;
 
-            Self::deposit_event(<Event<T>>::VaultTheft(vault_id, tx_id));
+            let vault = ext::vault_registry::get_active_vault_from_id::<T>(&vault_id)?;
+            Self::verify_signed_input_from_wallet(&transaction, input_index, &spent_transaction, &vault.wallet)?;
+            Self::_is_unauthorized_transaction(transaction, &vault.wallet)?;
+
+            Self::liquidate_and_record_theft(vault_id, reporter_id, tx_id)?;
 
             // don't take tx fees on success
             Ok(Pays::No.into())
         }
 
+        /// Sets whether theft reports must prove that the Vault signed the transaction.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `required` - whether `report_vault_theft_with_signature_proof` must be used
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn set_require_signature_proof(origin: OriginFor<T>, required: bool) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            <RequireSignatureProof<T>>::put(required);
+            Ok(().into())
+        }
+
         /// Report Vault double payment, providing two fraud proofs (malicious Bitcoin transactions
         /// and the corresponding transaction inclusion proofs). This fully slashes the Vault.
         ///
@@ -304,6 +420,12 @@ pub mod pallet {
                     // verify that the OP_RETURN matches, amounts are not relevant as Vaults
                     // might transfer any amount in the theft transaction
                     ensure!(left.op_return == right.op_return, Error::<T>::ExpectedDuplicate);
+                    // redeem requests may be paid in several partial payments, which are checked
+                    // by `report_vault_theft` instead
+                    ensure!(
+                        !ext::redeem::has_partial_payments::<T>(&left.op_return),
+                        Error::<T>::ValidRedeemTransaction
+                    );
 
                     ext::vault_registry::liquidate_theft_vault::<T>(&vault_id, reporter_id)?;
 
@@ -337,14 +459,63 @@ impl<T: Config> Pallet<T> {
             .map(|input| input.extract_address())
             .collect();
 
-        // TODO: can a vault steal funds if it registers a P2WPKH-P2SH since we
-        // would extract the `P2WPKHv0`?
+        // NOTE: this does not check any signatures, so an input may be attributed to the vault
+        // without the vault's key having signed it, see `verify_signed_input_from_wallet`
         input_addresses.into_iter().any(|address_result| match address_result {
             Ok(address) => wallet.has_btc_address(&address),
             _ => false,
         })
     }
 
+    /// Liquidates the vault for theft and records the report.
+    fn liquidate_and_record_theft(vault_id: T::AccountId, reporter_id: T::AccountId, tx_id: H256Le) -> DispatchResult {
+        ext::vault_registry::liquidate_theft_vault::<T>(&vault_id, reporter_id)?;
+
+        <TheftReports<T>>::mutate(&vault_id, &tx_id, |inner| {
+            let _ = inner.insert(());
+        });
+
+        Self::deposit_event(<Event<T>>::VaultTheft(vault_id, tx_id));
+        Ok(())
+    }
+
+    /// Checks that the input at `input_index` spends an output of `spent_transaction` which
+    /// pays to one of the vault's addresses, and that it is signed by the corresponding key.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - the tx
+    /// * `input_index` - index of the input signed by the vault
+    /// * `spent_transaction` - the tx containing the output spent by the input
+    /// * `wallet` - vault btc addresses
+    pub(crate) fn verify_signed_input_from_wallet(
+        transaction: &Transaction,
+        input_index: u32,
+        spent_transaction: &Transaction,
+        wallet: &Wallet,
+    ) -> DispatchResult {
+        let input_index = input_index as usize;
+        let input = transaction
+            .inputs
+            .get(input_index)
+            .ok_or(Error::<T>::InvalidSignatureProof)?;
+        let spent_output = match input.source {
+            TransactionInputSource::FromOutput(hash, index) if hash == spent_transaction.tx_id() => spent_transaction
+                .outputs
+                .get(index as usize)
+                .ok_or(Error::<T>::InvalidSignatureProof)?,
+            _ => return Err(Error::<T>::InvalidSignatureProof.into()),
+        };
+
+        ensure!(
+            matches!(spent_output.extract_address(), Ok(address) if wallet.has_btc_address(&address)),
+            Error::<T>::VaultNoInputToTransaction
+        );
+
+        verify_input(transaction, input_index, spent_output).map_err(btc_relay::Error::<T>::from)?;
+        Ok(())
+    }
+
     /// Checks if the vault is doing a valid merge transaction to move funds between
     /// addresses.
     ///
@@ -385,6 +556,103 @@ impl<T: Config> Pallet<T> {
         }
     }
 
+    /// Checks if the transaction pays at most the unpaid part of the requested amount to the
+    /// requested address, with any change going back to the vault.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_value` - amount of btc as specified in the request, including the transfer fee
+    /// * `paid_value` - amount of btc already paid towards the request
+    /// * `request_address` - recipient btc address
+    /// * `payment_data` - all payment data extracted from tx
+    /// * `wallet` - vault btc addresses
+    pub(crate) fn is_valid_partial_request_transaction(
+        request_value: Wrapped<T>,
+        paid_value: Wrapped<T>,
+        request_address: BtcAddress,
+        payment_data: &OpReturnPaymentData<T>,
+        wallet: &Wallet,
+    ) -> bool {
+        let unpaid_value = match TryInto::<u64>::try_into(request_value.saturating_sub(paid_value)) {
+            Ok(value) => value as i64,
+            Err(_) => return false,
+        };
+
+        match payment_data.amount_paid_to(request_address) {
+            Some(value) if value <= unpaid_value => {}
+            _ => return false,
+        }
+
+        match payment_data.return_to_self_address(request_address) {
+            None => true,
+            Some(return_to_self) => wallet.has_btc_address(&return_to_self),
+        }
+    }
+
+    /// Checks if the vault is sending a valid payment for a redeem request. A completed request
+    /// without partial payments must have been paid exactly. Otherwise the transaction must have
+    /// been counted as a partial payment or, while the request is open, keep the total paid
+    /// within the requested amount including the transfer fee.
+    ///
+    /// # Arguments
+    ///
+    /// * `redeem_id` - id of the redeem request
+    /// * `request` - the redeem request
+    /// * `tx_id` - id of the transaction
+    /// * `payment_data` - all payment data extracted from tx
+    /// * `wallet` - vault btc addresses
+    pub(crate) fn is_valid_redeem_transaction(
+        redeem_id: &H256,
+        request: &DefaultRedeemRequest<T>,
+        tx_id: H256Le,
+        payment_data: &OpReturnPaymentData<T>,
+        wallet: &Wallet,
+    ) -> bool {
+        if ext::redeem::is_partial_payment_transaction::<T>(redeem_id, tx_id) {
+            return true;
+        }
+
+        match request.status {
+            RedeemRequestStatus::Pending => Self::is_valid_partial_request_transaction(
+                request.amount_btc.saturating_add(request.transfer_fee_btc),
+                ext::redeem::get_partial_payment::<T>(redeem_id),
+                request.btc_address,
+                payment_data,
+                wallet,
+            ),
+            _ if ext::redeem::has_partial_payments::<T>(redeem_id) => false,
+            _ => Self::is_valid_request_transaction(request.amount_btc, request.btc_address, payment_data, wallet),
+        }
+    }
+
+    /// Checks if the transaction pays exactly the requested amounts to the requested addresses,
+    /// with any change going back to the vault.
+    ///
+    /// # Arguments
+    ///
+    /// * `requests` - btc addresses and amounts as specified in the batched requests
+    /// * `payment_data` - all payment data extracted from tx
+    /// * `wallet` - vault btc addresses
+    pub(crate) fn is_valid_batch_request_transaction(
+        requests: Vec<(BtcAddress, Wrapped<T>)>,
+        payment_data: &OpReturnPaymentData<T>,
+        wallet: &Wallet,
+    ) -> bool {
+        let mut expected_payments = Vec::with_capacity(requests.len());
+        for (address, value) in requests {
+            match TryInto::<u64>::try_into(value) {
+                Ok(value) => expected_payments.push((address, value as i64)),
+                Err(_) => return false,
+            }
+        }
+
+        match payment_data.ensure_valid_batch_payment_to(&expected_payments, payment_data.op_return) {
+            Ok(None) => true,
+            Ok(Some(return_to_self)) if wallet.has_btc_address(&return_to_self) => true,
+            _ => false,
+        }
+    }
+
     /// Check if a vault transaction is invalid. Returns `Ok` if invalid or `Err` otherwise.
     /// This method should be callable over RPC for a staked-relayer client to check validity.
     ///
@@ -409,6 +677,17 @@ impl<T: Config> Pallet<T> {
             Error::<T>::VaultNoInputToTransaction
         );
 
+        Self::_is_unauthorized_transaction(tx, &vault.wallet)
+    }
+
+    /// Check if a transaction spending the vault's funds is not a valid merge, redeem, replace
+    /// or refund transaction. Returns `Ok` if invalid or `Err` otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - the tx
+    /// * `wallet` - vault btc addresses
+    pub(crate) fn _is_unauthorized_transaction(tx: Transaction, wallet: &Wallet) -> DispatchResult {
         // Vaults are required to move funds for redeem and replace operations.
         // Each transaction MUST feature at least two or three outputs as follows:
         // * recipient: the recipient of the redeem / replace
@@ -416,15 +695,27 @@ impl<T: Config> Pallet<T> {
         // * vault: any "spare change" the vault is transferring
 
         ensure!(
-            !Self::is_valid_merge_transaction(&tx, &vault.wallet),
+            !Self::is_valid_merge_transaction(&tx, wallet),
             Error::<T>::ValidMergeTransaction
         );
 
+        // batched redeem requests, which pay several recipients in one transaction
+        if let Ok(payment_data) = OpReturnPaymentData::<T>::try_from_batch(tx.clone()) {
+            if let Ok(payments) = ext::redeem::get_open_or_completed_redeem_batch_payments::<T>(&payment_data.op_return)
+            {
+                ensure!(
+                    !Self::is_valid_batch_request_transaction(payments, &payment_data, wallet),
+                    Error::<T>::ValidRedeemTransaction
+                );
+            }
+        }
+
+        let tx_id = tx.tx_id();
         if let Ok(payment_data) = OpReturnPaymentData::<T>::try_from(tx) {
-            // redeem requests
+            // redeem requests, which may be paid in several partial payments
             if let Ok(req) = ext::redeem::get_open_or_completed_redeem_request_from_id::<T>(&payment_data.op_return) {
                 ensure!(
-                    !Self::is_valid_request_transaction(req.amount_btc, req.btc_address, &payment_data, &vault.wallet),
+                    !Self::is_valid_redeem_transaction(&payment_data.op_return, &req, tx_id, &payment_data, wallet),
                     Error::<T>::ValidRedeemTransaction
                 );
             };
@@ -432,7 +723,7 @@ impl<T: Config> Pallet<T> {
             // replace requests
             if let Ok(req) = ext::replace::get_open_or_completed_replace_request::<T>(&payment_data.op_return) {
                 ensure!(
-                    !Self::is_valid_request_transaction(req.amount, req.btc_address, &payment_data, &vault.wallet),
+                    !Self::is_valid_request_transaction(req.amount, req.btc_address, &payment_data, wallet),
                     Error::<T>::ValidReplaceTransaction
                 );
             };
@@ -440,12 +731,7 @@ impl<T: Config> Pallet<T> {
             // refund requests
             if let Ok(req) = ext::refund::get_open_or_completed_refund_request_from_id::<T>(&payment_data.op_return) {
                 ensure!(
-                    !Self::is_valid_request_transaction(
-                        req.amount_wrapped,
-                        req.btc_address,
-                        &payment_data,
-                        &vault.wallet
-                    ),
+                    !Self::is_valid_request_transaction(req.amount_wrapped, req.btc_address, &payment_data, wallet),
                     Error::<T>::ValidRefundTransaction
                 );
             };
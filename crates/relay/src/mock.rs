@@ -225,6 +225,7 @@ impl btc_relay::Config for Test {
     type Event = TestEvent;
     type ParachainBlocksPerBitcoinBlock = ParachainBlocksPerBitcoinBlock;
     type WeightInfo = ();
+    type ChainParams = btc_relay::BitcoinChainParams;
 }
 
 impl redeem::Config for Test {
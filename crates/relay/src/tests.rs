@@ -2,13 +2,18 @@ extern crate hex;
 use crate::{ext, mock::*};
 use bitcoin::{
     formatter::Formattable,
+    parser::parse_transaction,
     types::{
-        H256Le, MerkleProof, Transaction, TransactionBuilder, TransactionInputBuilder, TransactionInputSource,
-        TransactionOutput,
+        BlockHeader, H256Le, MerkleProof, RawBlockHeader, Transaction, TransactionBuilder, TransactionInputBuilder,
+        TransactionInputSource, TransactionOutput,
     },
 };
 use btc_relay::{BtcAddress, BtcPublicKey, OpReturnPaymentData};
-use frame_support::{assert_err, assert_ok};
+use frame_support::{
+    assert_err, assert_ok,
+    dispatch::{DispatchError, DispatchResultWithPostInfo},
+    weights::Pays,
+};
 use mocktopus::mocking::*;
 use redeem::types::{RedeemRequest, RedeemRequestStatus};
 use replace::types::{ReplaceRequest, ReplaceRequestStatus};
@@ -148,6 +153,37 @@ fn test_report_vault_theft_succeeds() {
     })
 }
 
+#[test]
+fn test_store_block_headers_succeeds() {
+    run_test(|| {
+        ext::btc_relay::parse_raw_block_header::<Test>.mock_safe(|_| MockResult::Return(Ok(BlockHeader::default())));
+        ext::btc_relay::store_block_headers::<Test>.mock_safe(|relayer, block_headers| {
+            assert_eq!(relayer, &ALICE);
+            assert_eq!(block_headers.len(), 3);
+            MockResult::Return(Ok(()))
+        });
+
+        let result = Relay::store_block_headers(Origin::signed(ALICE), vec![RawBlockHeader::default(); 3]);
+        assert_ok!(result);
+        assert_eq!(result.unwrap().pays_fee, Pays::No);
+    })
+}
+
+#[test]
+fn test_store_block_headers_fails_with_too_many_headers() {
+    run_test(|| {
+        ext::btc_relay::store_block_headers::<Test>.mock_safe(|_, _| panic!("should not store block headers"));
+
+        assert_err!(
+            Relay::store_block_headers(
+                Origin::signed(ALICE),
+                vec![RawBlockHeader::default(); crate::MAX_BLOCK_HEADERS_PER_BATCH as usize + 1]
+            ),
+            TestError::TooManyBlockHeaders
+        );
+    })
+}
+
 fn build_dummy_transaction_from_input_with_output_and_op_return(
     prev_hash: H256Le,
     public_key: &BtcPublicKey,
@@ -352,6 +388,127 @@ fn test_is_valid_request_transaction_succeeds() {
     })
 }
 
+#[test]
+fn test_is_valid_partial_request_transaction_succeeds() {
+    run_test(|| {
+        let recipient_address = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+
+        let vault_address = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
+
+        let mut wallet = Wallet::new(dummy_public_key());
+        wallet.add_btc_address(vault_address);
+
+        let transaction =
+            build_dummy2_transaction_with(vec![(40, recipient_address), (50, vault_address)], H256::zero());
+        let payment_data = OpReturnPaymentData::try_from(transaction).unwrap();
+
+        assert_eq!(
+            Relay::is_valid_partial_request_transaction(100, 0, recipient_address, &payment_data, &wallet),
+            true
+        );
+        assert_eq!(
+            Relay::is_valid_partial_request_transaction(100, 60, recipient_address, &payment_data, &wallet),
+            true
+        );
+        // the running total would exceed the requested amount
+        assert_eq!(
+            Relay::is_valid_partial_request_transaction(100, 61, recipient_address, &payment_data, &wallet),
+            false
+        );
+        assert_eq!(
+            Relay::is_valid_partial_request_transaction(30, 0, recipient_address, &payment_data, &wallet),
+            false
+        );
+        assert_eq!(
+            Relay::is_valid_partial_request_transaction(
+                100,
+                0,
+                recipient_address,
+                &payment_data,
+                &Wallet::new(dummy_public_key())
+            ),
+            false
+        );
+    })
+}
+
+fn redeem_request_with_status(
+    btc_address: BtcAddress,
+    status: RedeemRequestStatus,
+) -> RedeemRequest<AccountId, BlockNumber, Balance> {
+    RedeemRequest {
+        period: 0,
+        vault: BOB,
+        opentime: 0,
+        fee: 0,
+        amount_btc: 90,
+        premium: 0,
+        redeemer: ALICE,
+        btc_address,
+        btc_height: 0,
+        status,
+        transfer_fee_btc: 10,
+    }
+}
+
+#[test]
+fn test_is_valid_redeem_transaction_with_partial_payments() {
+    run_test(|| {
+        let recipient_address = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        let vault_address = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
+        let mut wallet = Wallet::new(dummy_public_key());
+        wallet.add_btc_address(vault_address);
+
+        let transaction =
+            build_dummy2_transaction_with(vec![(40, recipient_address), (50, vault_address)], H256::zero());
+        let tx_id = transaction.tx_id();
+        let payment_data = OpReturnPaymentData::try_from(transaction).unwrap();
+
+        ext::redeem::has_partial_payments::<Test>.mock_safe(|_| MockResult::Return(true));
+        ext::redeem::is_partial_payment_transaction::<Test>.mock_safe(|_, _| MockResult::Return(false));
+        ext::redeem::get_partial_payment::<Test>.mock_safe(|_| MockResult::Return(60));
+
+        // the running total stays within the amount including the transfer fee
+        let open_request = redeem_request_with_status(recipient_address, RedeemRequestStatus::Pending);
+        assert!(Relay::is_valid_redeem_transaction(
+            &H256::zero(),
+            &open_request,
+            tx_id,
+            &payment_data,
+            &wallet
+        ));
+
+        ext::redeem::get_partial_payment::<Test>.mock_safe(|_| MockResult::Return(61));
+        assert!(!Relay::is_valid_redeem_transaction(
+            &H256::zero(),
+            &open_request,
+            tx_id,
+            &payment_data,
+            &wallet
+        ));
+
+        // completed requests only accept the counted partial payments
+        let completed_request = redeem_request_with_status(recipient_address, RedeemRequestStatus::Completed);
+        assert!(!Relay::is_valid_redeem_transaction(
+            &H256::zero(),
+            &completed_request,
+            tx_id,
+            &payment_data,
+            &wallet
+        ));
+
+        ext::redeem::is_partial_payment_transaction::<Test>
+            .mock_safe(move |_, counted_tx_id| MockResult::Return(counted_tx_id == tx_id));
+        assert!(Relay::is_valid_redeem_transaction(
+            &H256::zero(),
+            &completed_request,
+            tx_id,
+            &payment_data,
+            &wallet
+        ));
+    })
+}
+
 #[test]
 fn test_is_transaction_invalid_fails_with_valid_merge_transaction() {
     run_test(|| {
@@ -674,6 +831,47 @@ fn should_report_double_payment() {
     })
 }
 
+#[test]
+fn should_not_report_double_payment_with_partial_redeem_payments() {
+    run_test(|| {
+        let public_key = dummy_public_key();
+        let input_address = BtcAddress::P2PKH(public_key.to_hash());
+        let output_address = BtcAddress::P2PKH(H160::random());
+        let left_tx = build_dummy_transaction_from_input_with_output_and_op_return(
+            H256Le::from_bytes_le(&vec![1u8; 32]),
+            &public_key,
+            output_address,
+            &[1; 32],
+        );
+        let right_tx = build_dummy_transaction_from_input_with_output_and_op_return(
+            H256Le::from_bytes_le(&vec![2u8; 32]),
+            &public_key,
+            output_address,
+            &[1; 32],
+        );
+
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(input_address)))));
+        ext::btc_relay::parse_merkle_proof::<Test>.mock_safe(|_| MockResult::Return(Ok(dummy_merkle_proof())));
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(|_, _| panic!("honest vault liquidated"));
+        ext::redeem::has_partial_payments::<Test>.mock_safe(|redeem_id| {
+            assert_eq!(redeem_id, &H256::from([1; 32]));
+            MockResult::Return(true)
+        });
+
+        assert_err!(
+            Relay::report_vault_double_payment(
+                Origin::signed(ALICE),
+                CAROL,
+                (vec![0u8; 32], vec![1u8; 32]),
+                (left_tx.format(), right_tx.format()),
+            ),
+            TestError::ValidRedeemTransaction
+        );
+    })
+}
+
 #[test]
 fn should_not_report_double_payment_with_vault_no_input() {
     run_test(|| {
@@ -710,3 +908,158 @@ fn should_not_report_double_payment_with_vault_no_input() {
         );
     })
 }
+
+// all three outputs pay to the key 034f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871aa:
+// P2WPKH, P2PKH and P2SH-P2WPKH
+const FUNDING_TX: &str = "020000000101010101010101010101010101010101010101010101010101010101010101010000000000ffffffff03a086010000000000160014fc7250a211deddc70ee5a2738de5f07817351cef400d0300000000001976a914fc7250a211deddc70ee5a2738de5f07817351cef88ace09304000000000017a914ec8f3d9c2763a0997a465b968d99db47e82e69d28700000000";
+
+// spends all outputs of `FUNDING_TX` to a non-vault address
+const THEFT_TX: &str = "02000000000103b64479416da6f884b5d50c2c444eace67fd860b99c12bc9c39f2fa6e451c979e0000000000ffffffffb64479416da6f884b5d50c2c444eace67fd860b99c12bc9c39f2fa6e451c979e010000006a47304402207affb6cd0d39f8eac5958393a6e1ddbdfd86b3b29234c3bb0db9439d870a7479022031a78c5637803d5086fad8a2e85be253890f9b1a7594c8339465639c15c303770121034f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871aaffffffffb64479416da6f884b5d50c2c444eace67fd860b99c12bc9c39f2fa6e451c979e0200000017160014fc7250a211deddc70ee5a2738de5f07817351cefffffffff01b0000900000000001976a914222222222222222222222222222222222222222288ac02483045022100b7541aa516337d925aaaa73625daa0772c55f8cfcd742367cc144e7e0e28752302207c539785683a3038b0ee859e594185eb285ffec6507c5e2facd4344ceffff9240121034f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871aa0002483045022100c3b838ecebeccd9fda2721bf74b43f2f808523542c4bff87e73570eb1472083402206dd226911f9efda044ba5923832f7302448d63f27cf4013e018868258afa08350121034f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871aa00000000";
+
+fn signing_vault_addresses() -> Vec<BtcAddress> {
+    let key_hash = H160::from_slice(&hex::decode("fc7250a211deddc70ee5a2738de5f07817351cef").unwrap());
+    vec![
+        BtcAddress::P2WPKHv0(key_hash),
+        BtcAddress::P2PKH(key_hash),
+        BtcAddress::P2SH(H160::from_slice(
+            &hex::decode("ec8f3d9c2763a0997a465b968d99db47e82e69d2").unwrap(),
+        )),
+    ]
+}
+
+fn report_vault_theft_with_signature_proof(
+    input_index: u32,
+    raw_tx: Vec<u8>,
+    raw_spent_tx: Vec<u8>,
+) -> DispatchResultWithPostInfo {
+    Relay::report_vault_theft_with_signature_proof(
+        Origin::signed(ALICE),
+        CAROL,
+        vec![0u8; 32],
+        raw_tx,
+        input_index,
+        raw_spent_tx,
+    )
+}
+
+fn mock_signature_proof_report(vault_address: BtcAddress) {
+    ext::vault_registry::get_active_vault_from_id::<Test>
+        .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(vault_address)))));
+    ext::btc_relay::parse_merkle_proof::<Test>.mock_safe(|_| MockResult::Return(Ok(dummy_merkle_proof())));
+    ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+    ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+}
+
+#[test]
+fn test_report_vault_theft_fails_when_signature_proof_required() {
+    run_test(|| {
+        assert_err!(
+            Relay::set_require_signature_proof(Origin::signed(ALICE), true),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(Relay::set_require_signature_proof(Origin::root(), true));
+
+        assert_err!(
+            Relay::report_vault_theft(
+                Origin::signed(ALICE),
+                CAROL,
+                vec![0u8; 32],
+                hex::decode(THEFT_TX).unwrap()
+            ),
+            TestError::SignatureProofRequired
+        );
+    })
+}
+
+#[test]
+fn test_report_vault_theft_with_signature_proof_succeeds() {
+    for (input_index, vault_address) in signing_vault_addresses().into_iter().enumerate() {
+        run_test(|| {
+            mock_signature_proof_report(vault_address);
+            assert_ok!(Relay::set_require_signature_proof(Origin::root(), true));
+
+            assert_ok!(report_vault_theft_with_signature_proof(
+                input_index as u32,
+                hex::decode(THEFT_TX).unwrap(),
+                hex::decode(FUNDING_TX).unwrap()
+            ));
+            assert!(System::events()
+                .iter()
+                .any(|a| matches!(a.event, TestEvent::Relay(Event::VaultTheft(id, _)) if id == CAROL)));
+        })
+    }
+}
+
+#[test]
+fn test_report_vault_theft_with_signature_proof_fails_with_wrong_spent_transaction() {
+    run_test(|| {
+        mock_signature_proof_report(signing_vault_addresses()[0]);
+
+        // the input does not spend an output of the reported transaction itself
+        assert_err!(
+            report_vault_theft_with_signature_proof(0, hex::decode(THEFT_TX).unwrap(), hex::decode(THEFT_TX).unwrap()),
+            TestError::InvalidSignatureProof
+        );
+        assert_err!(
+            report_vault_theft_with_signature_proof(
+                3,
+                hex::decode(THEFT_TX).unwrap(),
+                hex::decode(FUNDING_TX).unwrap()
+            ),
+            TestError::InvalidSignatureProof
+        );
+    })
+}
+
+#[test]
+fn test_report_vault_theft_with_signature_proof_fails_with_non_vault_input() {
+    run_test(|| {
+        // the P2PKH output is spent by the second input
+        mock_signature_proof_report(signing_vault_addresses()[1]);
+
+        assert_err!(
+            report_vault_theft_with_signature_proof(
+                0,
+                hex::decode(THEFT_TX).unwrap(),
+                hex::decode(FUNDING_TX).unwrap()
+            ),
+            TestError::VaultNoInputToTransaction
+        );
+    })
+}
+
+#[test]
+fn test_report_vault_theft_with_signature_proof_fails_with_invalid_signature() {
+    run_test(|| {
+        mock_signature_proof_report(signing_vault_addresses()[1]);
+
+        let mut transaction = parse_transaction(&hex::decode(THEFT_TX).unwrap()).unwrap();
+        transaction.outputs[0].value += 1;
+
+        assert_err!(
+            report_vault_theft_with_signature_proof(1, transaction.format(), hex::decode(FUNDING_TX).unwrap()),
+            btc_relay::Error::<Test>::ScriptVerificationFailed
+        );
+    })
+}
+
+#[test]
+fn test_report_vault_theft_with_signature_proof_detects_spoofed_input() {
+    run_test(|| {
+        // an input whose witness merely ends with the vault's public key is attributed to the
+        // vault's P2WPKH address by `has_input_from_wallet`, even though it carries no signature
+        let vault_address = signing_vault_addresses()[0];
+        let funding_tx = parse_transaction(&hex::decode(FUNDING_TX).unwrap()).unwrap();
+        let mut transaction = parse_transaction(&hex::decode(THEFT_TX).unwrap()).unwrap();
+        transaction.inputs.truncate(1);
+        transaction.inputs[0].witness =
+            vec![hex::decode("034f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871aa").unwrap()];
+        assert_eq!(transaction.inputs[0].extract_address(), Ok(vault_address));
+
+        mock_signature_proof_report(vault_address);
+        assert_err!(
+            report_vault_theft_with_signature_proof(0, transaction.format(), funding_tx.format()),
+            btc_relay::Error::<Test>::InvalidScript
+        );
+    })
+}
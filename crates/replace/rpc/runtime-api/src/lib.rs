@@ -3,18 +3,28 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::Codec;
+use frame_support::dispatch::DispatchError;
 use sp_std::vec::Vec;
 
 sp_api::decl_runtime_apis! {
-    pub trait ReplaceApi<AccountId, H256, ReplaceRequest> where
+    pub trait ReplaceApi<AccountId, H256, ReplaceRequest, PaymentVerification> where
         AccountId: Codec,
         H256: Codec,
         ReplaceRequest: Codec,
+        PaymentVerification: Codec,
     {
         /// Get all replace requests from a particular vault
         fn get_old_vault_replace_requests(account_id: AccountId) -> Vec<(H256, ReplaceRequest)>;
 
         /// Get all replace requests to a particular vault
         fn get_new_vault_replace_requests(account_id: AccountId) -> Vec<(H256, ReplaceRequest)>;
+
+        /// Dry-run the payment verification of the replace request, reporting the payment that was
+        /// found and the first check that failed, if any
+        fn verify_replace_payment(
+            replace_id: H256,
+            raw_merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+        ) -> Result<PaymentVerification, DispatchError>;
     }
 }
@@ -5,14 +5,14 @@ use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
 use jsonrpc_derive::rpc;
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
-use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT, DispatchError};
 use std::sync::Arc;
 
 pub use self::gen_client::Client as ReplaceClient;
 pub use module_replace_rpc_runtime_api::ReplaceApi as ReplaceRuntimeApi;
 
 #[rpc]
-pub trait ReplaceApi<BlockHash, AccountId, H256, ReplaceRequest> {
+pub trait ReplaceApi<BlockHash, AccountId, H256, ReplaceRequest, PaymentVerification> {
     #[rpc(name = "replace_getOldVaultReplaceRequests")]
     fn get_old_vault_replace_requests(
         &self,
@@ -26,6 +26,15 @@ pub trait ReplaceApi<BlockHash, AccountId, H256, ReplaceRequest> {
         account_id: AccountId,
         at: Option<BlockHash>,
     ) -> Result<Vec<(H256, ReplaceRequest)>>;
+
+    #[rpc(name = "replace_verifyReplacePayment")]
+    fn verify_replace_payment(
+        &self,
+        replace_id: H256,
+        raw_merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+        at: Option<BlockHash>,
+    ) -> Result<std::result::Result<PaymentVerification, DispatchError>>;
 }
 
 /// A struct that implements the [`ReplaceApi`].
@@ -56,15 +65,16 @@ impl From<Error> for i64 {
     }
 }
 
-impl<C, Block, AccountId, H256, ReplaceRequest> ReplaceApi<<Block as BlockT>::Hash, AccountId, H256, ReplaceRequest>
-    for Replace<C, Block>
+impl<C, Block, AccountId, H256, ReplaceRequest, PaymentVerification>
+    ReplaceApi<<Block as BlockT>::Hash, AccountId, H256, ReplaceRequest, PaymentVerification> for Replace<C, Block>
 where
     Block: BlockT,
     C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
-    C::Api: ReplaceRuntimeApi<Block, AccountId, H256, ReplaceRequest>,
+    C::Api: ReplaceRuntimeApi<Block, AccountId, H256, ReplaceRequest, PaymentVerification>,
     AccountId: Codec,
     H256: Codec,
     ReplaceRequest: Codec,
+    PaymentVerification: Codec,
 {
     fn get_old_vault_replace_requests(
         &self,
@@ -97,4 +107,22 @@ where
                 data: Some(format!("{:?}", e).into()),
             })
     }
+
+    fn verify_replace_payment(
+        &self,
+        replace_id: H256,
+        raw_merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<std::result::Result<PaymentVerification, DispatchError>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.verify_replace_payment(&at, replace_id, raw_merkle_proof, raw_tx)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to verify replace payment.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
 }
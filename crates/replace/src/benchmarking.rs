@@ -89,12 +89,12 @@ fn mine_blocks<T: crate::Config>(end_height: u32) {
         .build();
 
     let mut prev_hash = block.header.hash;
-    for _ in 0..end_height {
+    for i in 0..end_height {
         let block = BlockBuilder::new()
             .with_previous_hash(prev_hash)
             .with_version(4)
             .with_coinbase(&BtcAddress::P2SH(H160::zero()), 50, 3)
-            .with_timestamp(1588813835)
+            .with_timestamp(1588814835 + i)
             .add_transaction(transaction.clone())
             .mine(U256::from(2).pow(254.into()))
             .unwrap();
@@ -260,7 +260,7 @@ benchmarks! {
             .with_previous_hash(block_hash)
             .with_version(4)
             .with_coinbase(&new_vault_btc_address, 50, 3)
-            .with_timestamp(1588813835)
+            .with_timestamp(1588814835)
             .add_transaction(transaction.clone())
             .mine(U256::from(2).pow(254.into())).unwrap();
 
@@ -3,11 +3,27 @@ use mocktopus::macros::mockable;
 
 #[cfg_attr(test, mockable)]
 pub(crate) mod btc_relay {
-    use bitcoin::types::{MerkleProof, Transaction, Value};
-    use btc_relay::BtcAddress;
+    use bitcoin::types::{H256Le, MerkleProof, Transaction, Value};
+    use btc_relay::{BtcAddress, PaymentVerification, SettledRequest};
     use frame_support::dispatch::DispatchError;
     use sp_core::H256;
-    use sp_std::convert::TryInto;
+    use sp_std::convert::{TryFrom, TryInto};
+
+    pub fn dry_run_op_return_payment<T: crate::Config, V: TryFrom<Value> + TryInto<Value> + Clone>(
+        raw_merkle_proof: &[u8],
+        raw_tx: &[u8],
+        recipient_btc_address: BtcAddress,
+        expected_amount: V,
+        op_return_id: H256,
+    ) -> PaymentVerification<V> {
+        <btc_relay::Pallet<T>>::dry_run_op_return_payment(
+            raw_merkle_proof,
+            raw_tx,
+            recipient_btc_address,
+            expected_amount,
+            op_return_id,
+        )
+    }
 
     pub fn verify_and_validate_op_return_transaction<T: crate::Config, V: TryInto<Value>>(
         merkle_proof: MerkleProof,
@@ -44,6 +60,10 @@ pub(crate) mod btc_relay {
     ) -> Result<bool, DispatchError> {
         <btc_relay::Pallet<T>>::has_request_expired(opentime, btc_open_height, period)
     }
+
+    pub fn record_settled_request<T: crate::Config>(block_hash: H256Le, request: SettledRequest) {
+        <btc_relay::Pallet<T>>::record_settled_request(block_hash, request)
+    }
 }
 
 #[cfg_attr(test, mockable)]
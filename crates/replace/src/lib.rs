@@ -15,7 +15,7 @@ pub use default_weights::WeightInfo;
 extern crate mocktopus;
 
 use crate::types::ReplaceRequestExt;
-use btc_relay::BtcAddress;
+use btc_relay::{BtcAddress, PaymentVerification, SettledRequest};
 use frame_support::{
     dispatch::{DispatchError, DispatchResult},
     ensure,
@@ -461,6 +461,7 @@ impl<T: Config> Pallet<T> {
         // check the transaction inclusion and validity
         let transaction = ext::btc_relay::parse_transaction::<T>(&raw_tx)?;
         let merkle_proof = ext::btc_relay::parse_merkle_proof::<T>(&raw_merkle_proof)?;
+        let block_hash = merkle_proof.block_header.hash;
         ext::btc_relay::verify_and_validate_op_return_transaction::<T, _>(
             merkle_proof,
             transaction,
@@ -468,6 +469,7 @@ impl<T: Config> Pallet<T> {
             replace.amount,
             replace_id,
         )?;
+        ext::btc_relay::record_settled_request::<T>(block_hash, SettledRequest::Replace(replace_id));
 
         // decrease old-vault's issued & to-be-redeemed tokens, and
         // change new-vault's to-be-issued tokens to issued tokens
@@ -561,6 +563,29 @@ impl<T: Config> Pallet<T> {
             .collect::<Vec<_>>()
     }
 
+    /// Dry-run the payment verification of `execute_replace`, reporting the payment that was found
+    /// and the first check that failed, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `replace_id` - identifier of the replace request
+    /// * `raw_merkle_proof` - raw merkle proof of the payment
+    /// * `raw_tx` - raw bitcoin transaction of the payment
+    pub fn verify_replace_payment(
+        replace_id: H256,
+        raw_merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+    ) -> Result<PaymentVerification<Wrapped<T>>, DispatchError> {
+        let replace = Self::get_open_replace_request(&replace_id)?;
+        Ok(ext::btc_relay::dry_run_op_return_payment::<T, _>(
+            &raw_merkle_proof,
+            &raw_tx,
+            replace.btc_address,
+            replace.amount,
+            replace_id,
+        ))
+    }
+
     /// Get a replace request by id. Completed or cancelled requests are not returned.
     pub fn get_open_replace_request(replace_id: &H256) -> Result<DefaultReplaceRequest<T>, DispatchError> {
         let request = ReplaceRequests::<T>::try_get(replace_id).or(Err(Error::<T>::ReplaceIdNotFound))?;
@@ -90,6 +90,9 @@ benchmarks! {
     adjust_liquidation_collateral_threshold {
     }: _(RawOrigin::Root, T::GetGriefingCollateralCurrencyId::get(), UnsignedFixedPoint::<T>::one())
 
+    adjust_liquidation_penalty {
+    }: _(RawOrigin::Root, T::GetGriefingCollateralCurrencyId::get(), UnsignedFixedPoint::<T>::one())
+
     report_undercollateralized_vault {
         let origin: T::AccountId = account("Origin", 0, 0);
         let vault_id: T::AccountId = account("Vault", 0, 0);
@@ -44,6 +44,7 @@ pub trait WeightInfo {
 	fn adjust_secure_collateral_threshold() -> Weight;
 	fn adjust_premium_redeem_threshold() -> Weight;
 	fn adjust_liquidation_collateral_threshold() -> Weight;
+	fn adjust_liquidation_penalty() -> Weight;
 	fn report_undercollateralized_vault() -> Weight;
 }
 
@@ -151,6 +152,13 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 		(4_392_000 as Weight)
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
+	// Storage: VaultRegistry LiquidationPenalty (r:0 w:1)
+	// Estimate: adjust_liquidation_collateral_threshold (4_392_000), which is the same root setter
+	// of a single value
+	fn adjust_liquidation_penalty() -> Weight {
+		(4_392_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
 	// Storage: VaultRegistry Vaults (r:1 w:1)
 	// Storage: VaultRegistry LiquidationCollateralThreshold (r:1 w:0)
 	// Storage: Staking Nonce (r:1 w:0)
@@ -158,6 +166,7 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	// Storage: Security ParachainStatus (r:1 w:0)
 	// Storage: Oracle Aggregate (r:1 w:0)
 	// Storage: VaultRegistry SecureCollateralThreshold (r:1 w:0)
+	// Storage: VaultRegistry LiquidationPenalty (r:1 w:0)
 	// Storage: Staking Stake (r:1 w:1)
 	// Storage: Staking SlashPerToken (r:1 w:0)
 	// Storage: Staking SlashTally (r:1 w:1)
@@ -174,9 +183,12 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	// Storage: Rewards TotalStake (r:1 w:1)
 	// Storage: Rewards RewardTally (r:1 w:1)
 	// Storage: Rewards RewardPerToken (r:1 w:0)
+	// Storage: VaultRegistry LiquidationPenalty (r:1 w:0)
+	// Estimate: execution time as measured for a full liquidation; partially liquidating moves
+	// less collateral through the same storage items and adds the LiquidationPenalty read
 	fn report_undercollateralized_vault() -> Weight {
 		(412_444_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(25 as Weight))
+			.saturating_add(T::DbWeight::get().reads(26 as Weight))
 			.saturating_add(T::DbWeight::get().writes(14 as Weight))
 	}
 }
@@ -284,6 +296,13 @@ impl WeightInfo for () {
 		(4_392_000 as Weight)
 			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
 	}
+	// Storage: VaultRegistry LiquidationPenalty (r:0 w:1)
+	// Estimate: adjust_liquidation_collateral_threshold (4_392_000), which is the same root setter
+	// of a single value
+	fn adjust_liquidation_penalty() -> Weight {
+		(4_392_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
 	// Storage: VaultRegistry Vaults (r:1 w:1)
 	// Storage: VaultRegistry LiquidationCollateralThreshold (r:1 w:0)
 	// Storage: Staking Nonce (r:1 w:0)
@@ -291,6 +310,7 @@ impl WeightInfo for () {
 	// Storage: Security ParachainStatus (r:1 w:0)
 	// Storage: Oracle Aggregate (r:1 w:0)
 	// Storage: VaultRegistry SecureCollateralThreshold (r:1 w:0)
+	// Storage: VaultRegistry LiquidationPenalty (r:1 w:0)
 	// Storage: Staking Stake (r:1 w:1)
 	// Storage: Staking SlashPerToken (r:1 w:0)
 	// Storage: Staking SlashTally (r:1 w:1)
@@ -307,9 +327,12 @@ impl WeightInfo for () {
 	// Storage: Rewards TotalStake (r:1 w:1)
 	// Storage: Rewards RewardTally (r:1 w:1)
 	// Storage: Rewards RewardPerToken (r:1 w:0)
+	// Storage: VaultRegistry LiquidationPenalty (r:1 w:0)
+	// Estimate: execution time as measured for a full liquidation; partially liquidating moves
+	// less collateral through the same storage items and adds the LiquidationPenalty read
 	fn report_undercollateralized_vault() -> Weight {
 		(412_444_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(25 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(26 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(14 as Weight))
 	}
 }
@@ -33,15 +33,20 @@ use crate::types::{
 };
 
 #[doc(inline)]
-pub use crate::types::{BtcPublicKey, CurrencySource, DefaultVault, SystemVault, Vault, VaultStatus, Wallet};
+pub use crate::types::{
+    BtcPublicKey, CollateralizationLevel, CurrencySource, DefaultVault, SystemVault, Vault, VaultStatus, Wallet,
+};
 use bitcoin::types::Value;
 use codec::FullCodec;
 pub use currency::Amount;
 use frame_support::{
     dispatch::{DispatchError, DispatchResult},
     ensure,
+    storage::StoragePrefixedMap,
     traits::Get,
-    transactional, PalletId,
+    transactional,
+    weights::Weight,
+    PalletId,
 };
 use frame_system::{
     ensure_signed,
@@ -64,6 +69,12 @@ use sp_std::{
 // value taken from https://github.com/substrate-developer-hub/recipes/blob/master/pallets/ocw-demo/src/lib.rs
 pub const UNSIGNED_TXS_PRIORITY: u64 = 100;
 
+/// The maximum number of vaults whose collateralization is checked against the thresholds in a single block.
+pub const MAX_THRESHOLD_CHECKS_PER_BLOCK: u32 = 10;
+
+/// The maximum number of vaults scanned when selecting a vault for an issue or redeem request.
+pub const MAX_VAULT_SELECTION_CANDIDATES: u32 = 100;
+
 pub use pallet::*;
 
 #[frame_support::pallet]
@@ -126,6 +137,10 @@ pub mod pallet {
 
     #[pallet::hooks]
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(_n: T::BlockNumber) -> Weight {
+            Self::check_vault_thresholds(MAX_THRESHOLD_CHECKS_PER_BLOCK)
+        }
+
         fn offchain_worker(n: T::BlockNumber) {
             log::info!("Off-chain worker started on block {:?}", n);
             Self::_offchain_worker();
@@ -309,7 +324,7 @@ pub mod pallet {
             let liquidation_threshold =
                 Self::liquidation_collateral_threshold(vault.currency_id).ok_or(Error::<T>::ThresholdNotSet)?;
             if Self::is_vault_below_liquidation_threshold(&vault, liquidation_threshold)? {
-                Self::liquidate_vault(&vault_id)?;
+                Self::partially_liquidate_vault(&vault_id)?;
                 Ok(().into())
             } else {
                 log::info!("Not liquidating; vault not below liquidation threshold");
@@ -384,6 +399,23 @@ pub mod pallet {
             Self::set_liquidation_collateral_threshold(currency_id, threshold);
             Ok(())
         }
+
+        /// Changes the liquidation penalty for a currency (only executable by the Root account)
+        ///
+        /// # Arguments
+        /// * `currency_id` - the currency to change
+        /// * `penalty` - the new liquidation penalty
+        #[pallet::weight(<T as Config>::WeightInfo::adjust_liquidation_penalty())]
+        #[transactional]
+        pub fn adjust_liquidation_penalty(
+            origin: OriginFor<T>,
+            currency_id: CurrencyId<T>,
+            penalty: UnsignedFixedPoint<T>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::set_liquidation_penalty(currency_id, penalty);
+            Ok(())
+        }
     }
 
     #[pallet::event]
@@ -438,8 +470,18 @@ pub mod pallet {
             VaultStatus,  // status
             BalanceOf<T>, // replace_collateral
         ),
+        /// vault_id, liquidated issued tokens, slashed collateral
+        PartiallyLiquidateVault(T::AccountId, BalanceOf<T>, BalanceOf<T>),
         /// vault_id, banned_until
         BanVault(T::AccountId, T::BlockNumber),
+        /// vault_id
+        VaultBelowSecureThreshold(T::AccountId),
+        /// vault_id
+        VaultBelowPremiumThreshold(T::AccountId),
+        /// vault_id
+        VaultBelowLiquidationThreshold(T::AccountId),
+        /// vault_id, new collateralization level
+        VaultRecoveredAboveThreshold(T::AccountId, CollateralizationLevel),
     }
 
     #[pallet::error]
@@ -529,6 +571,13 @@ pub mod pallet {
     #[pallet::getter(fn liquidation_collateral_threshold)]
     pub(super) type LiquidationCollateralThreshold<T: Config> =
         StorageMap<_, Blake2_128Concat, CurrencyId<T>, UnsignedFixedPoint<T>>;
+
+    /// Determines the share of the liquidated tokens' value that is slashed from a Vault's
+    /// collateral, on top of the collateral backing these tokens, when it is partially liquidated.
+    #[pallet::storage]
+    #[pallet::getter(fn liquidation_penalty)]
+    pub(super) type LiquidationPenalty<T: Config> =
+        StorageMap<_, Blake2_128Concat, CurrencyId<T>, UnsignedFixedPoint<T>, ValueQuery>;
     /// Account identifier of an artificial Vault maintained by the VaultRegistry to handle issued balances
     /// and collateral of liquidated Vaults. That is, when a Vault is liquidated, its balances are
     /// transferred to LiquidationVault and claims are later handled via the LiquidationVault.
@@ -549,6 +598,16 @@ pub mod pallet {
     pub(super) type ReservedAddresses<T: Config> =
         StorageMap<_, Blake2_128Concat, BtcAddress, T::AccountId, ValueQuery>;
 
+    /// The collateralization level of each active vault, as of the last threshold check
+    #[pallet::storage]
+    pub(super) type VaultCollateralizationLevels<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, CollateralizationLevel, ValueQuery>;
+
+    /// Raw storage key of the last vault whose thresholds were checked, from where the next
+    /// check continues. The check restarts from the first vault if unset.
+    #[pallet::storage]
+    pub(super) type ThresholdCheckCursor<T: Config> = StorageValue<_, Vec<u8>, OptionQuery>;
+
     /// Total collateral used for collateral tokens issued by active vaults, excluding the liquidation vault
     #[pallet::storage]
     pub(super) type TotalUserVaultCollateral<T: Config> =
@@ -624,6 +683,112 @@ impl<T: Config> Pallet<T> {
         }
     }
 
+    /// Checks the collateralization of at most `max_checked` vaults, continuing where the previous
+    /// check stopped, and emits an event for every vault that crossed a collateral threshold.
+    /// Vaults below the liquidation threshold are liquidated.
+    fn check_vault_thresholds(max_checked: u32) -> Weight {
+        let mut reads: Weight = 1;
+        let mut writes: Weight = 1;
+        let mut liquidation_weight: Weight = 0;
+
+        let mut vaults = match ThresholdCheckCursor::<T>::get() {
+            Some(last_key) => Vaults::<T>::iter_from(last_key),
+            None => Vaults::<T>::iter(),
+        };
+        let mut cursor = None;
+        for _ in 0..max_checked {
+            reads += 1;
+            let (vault_id, vault) = match vaults.next() {
+                Some(next) => next,
+                None => {
+                    // restart from the first vault in the next block
+                    cursor = None;
+                    break;
+                }
+            };
+            cursor = Some(Vaults::<T>::hashed_key_for(&vault_id));
+
+            if !matches!(vault.status, VaultStatus::Active(_)) {
+                writes += 1;
+                VaultCollateralizationLevels::<T>::remove(&vault_id);
+                continue;
+            }
+
+            // vault, stake, exchange rate and thresholds
+            reads += 8;
+            let level = match Self::get_collateralization_level(&vault) {
+                Ok(level) => level,
+                Err(_) => continue,
+            };
+            let previous_level = VaultCollateralizationLevels::<T>::get(&vault_id);
+            if level != previous_level {
+                writes += 1;
+                VaultCollateralizationLevels::<T>::insert(&vault_id, level);
+
+                let event = match level {
+                    _ if level < previous_level => {
+                        Some(Event::<T>::VaultRecoveredAboveThreshold(vault_id.clone(), level))
+                    }
+                    CollateralizationLevel::BelowSecureThreshold => {
+                        Some(Event::<T>::VaultBelowSecureThreshold(vault_id.clone()))
+                    }
+                    CollateralizationLevel::BelowPremiumThreshold => {
+                        Some(Event::<T>::VaultBelowPremiumThreshold(vault_id.clone()))
+                    }
+                    CollateralizationLevel::BelowLiquidationThreshold => {
+                        Some(Event::<T>::VaultBelowLiquidationThreshold(vault_id.clone()))
+                    }
+                    CollateralizationLevel::AboveSecureThreshold => None,
+                };
+                if let Some(event) = event {
+                    Self::deposit_event(event);
+                }
+            }
+
+            // liquidate the vault without waiting for it to be reported
+            reads += 1;
+            if level == CollateralizationLevel::BelowLiquidationThreshold
+                && ext::security::ensure_parachain_status_not_shutdown::<T>().is_ok()
+            {
+                liquidation_weight =
+                    liquidation_weight.saturating_add(<T as Config>::WeightInfo::report_undercollateralized_vault());
+                if Self::liquidate_undercollateralized_vault(&vault_id).is_ok() {
+                    VaultCollateralizationLevels::<T>::remove(&vault_id);
+                } else {
+                    log::warn!("Failed to liquidate vault {:?}", vault_id);
+                }
+            }
+        }
+        ThresholdCheckCursor::<T>::set(cursor);
+
+        T::DbWeight::get()
+            .reads_writes(reads, writes)
+            .saturating_add(liquidation_weight)
+    }
+
+    /// Liquidates a vault that was found below the liquidation threshold, reverting all
+    /// storage changes if the liquidation fails
+    #[transactional]
+    fn liquidate_undercollateralized_vault(vault_id: &T::AccountId) -> Result<Amount<T>, DispatchError> {
+        Self::partially_liquidate_vault(vault_id)
+    }
+
+    /// Get the lowest collateral threshold the vault is below
+    fn get_collateralization_level(vault: &DefaultVault<T>) -> Result<CollateralizationLevel, DispatchError> {
+        let liquidation_threshold =
+            Self::liquidation_collateral_threshold(vault.currency_id).ok_or(Error::<T>::ThresholdNotSet)?;
+        let level = if Self::is_vault_below_liquidation_threshold(vault, liquidation_threshold)? {
+            CollateralizationLevel::BelowLiquidationThreshold
+        } else if Self::is_vault_below_premium_threshold(&vault.id)? {
+            CollateralizationLevel::BelowPremiumThreshold
+        } else if Self::is_vault_below_secure_threshold(&vault.id)? {
+            CollateralizationLevel::BelowSecureThreshold
+        } else {
+            CollateralizationLevel::AboveSecureThreshold
+        };
+        Ok(level)
+    }
+
     /// Public functions
 
     pub fn _register_vault(
@@ -1277,6 +1442,72 @@ impl<T: Config> Pallet<T> {
         Ok(to_slash)
     }
 
+    /// Liquidates only as many of the vault's issued tokens as needed to restore it above the
+    /// `SecureCollateralThreshold`. The collateral backing these tokens, plus the
+    /// `LiquidationPenalty`, is moved to the `LiquidationVault` together with the tokens.
+    /// Falls back to a full liquidation if the vault cannot be restored this way.
+    ///
+    /// # Arguments
+    /// * `vault_id` - the id of the vault to liquidate
+    pub fn partially_liquidate_vault(vault_id: &T::AccountId) -> Result<Amount<T>, DispatchError> {
+        let mut vault = Self::get_active_rich_vault_from_id(&vault_id)?;
+
+        let (tokens, collateral) = match Self::calculate_partial_liquidation(&vault)? {
+            Some(amounts) => amounts,
+            None => return Self::liquidate_vault(vault_id),
+        };
+        vault.liquidate_partially(&tokens, &collateral)?;
+
+        Self::deposit_event(Event::<T>::PartiallyLiquidateVault(
+            vault_id.clone(),
+            tokens.amount(),
+            collateral.amount(),
+        ));
+        Ok(collateral)
+    }
+
+    /// Calculates the issued tokens and the collateral to move to the `LiquidationVault` such
+    /// that the vault ends up at the `SecureCollateralThreshold`. Moving `x` tokens slashes
+    /// `x * (1 + penalty)` worth of collateral, so we need
+    /// `x >= (secure_threshold * issued - collateral) / (secure_threshold - 1 - penalty)`.
+    /// Returns `None` if no partial liquidation can restore the vault.
+    fn calculate_partial_liquidation(vault: &RichVault<T>) -> Result<Option<(Amount<T>, Amount<T>)>, DispatchError> {
+        let currency_id = vault.data.currency_id;
+        let secure_threshold = Self::secure_collateral_threshold(currency_id).ok_or(Error::<T>::ThresholdNotSet)?;
+        let slash_rate = UnsignedFixedPoint::<T>::one()
+            .checked_add(&Self::liquidation_penalty(currency_id))
+            .ok_or(Error::<T>::ArithmeticOverflow)?;
+
+        // the collateralization only improves if less than the secure threshold is slashed per token
+        let restore_rate = match secure_threshold.checked_sub(&slash_rate) {
+            Some(rate) if !rate.is_zero() => rate,
+            _ => return Ok(None),
+        };
+
+        let collateral = Self::get_backing_collateral(&vault.id())?;
+        let collateral_in_wrapped = collateral.convert_to(T::GetWrappedCurrencyId::get())?;
+        let deficit = vault
+            .issued_tokens()
+            .checked_fixed_point_mul(&secure_threshold)?
+            .saturating_sub(&collateral_in_wrapped)?;
+
+        // round up so that the vault ends up at or above the secure threshold
+        let mut tokens = deficit.checked_div(&restore_rate)?;
+        if tokens.checked_fixed_point_mul(&restore_rate)?.lt(&deficit)? {
+            tokens = tokens.checked_add(&Amount::new(1u32.into(), T::GetWrappedCurrencyId::get()))?;
+        }
+        if tokens.is_zero() || tokens.gt(&vault.redeemable_tokens()?)? {
+            return Ok(None);
+        }
+
+        let to_slash = tokens.checked_fixed_point_mul(&slash_rate)?.convert_to(currency_id)?;
+        if to_slash.gt(&collateral)? {
+            return Ok(None);
+        }
+
+        Ok(Some((tokens, to_slash)))
+    }
+
     pub fn try_increase_total_backing_collateral(amount: &Amount<T>) -> DispatchResult {
         let new = Self::get_total_user_vault_collateral(amount.currency())?.checked_add(&amount)?;
 
@@ -1363,6 +1594,10 @@ impl<T: Config> Pallet<T> {
         PremiumRedeemThreshold::<T>::insert(currency_id, threshold);
     }
 
+    pub fn set_liquidation_penalty(currency_id: CurrencyId<T>, penalty: UnsignedFixedPoint<T>) {
+        LiquidationPenalty::<T>::insert(currency_id, penalty);
+    }
+
     pub fn set_liquidation_collateral_threshold(currency_id: CurrencyId<T>, threshold: UnsignedFixedPoint<T>) {
         LiquidationCollateralThreshold::<T>::insert(currency_id, threshold);
     }
@@ -1451,6 +1686,36 @@ impl<T: Config> Pallet<T> {
         Ok(vaults_with_issuable_tokens)
     }
 
+    /// Get the vaults that an issue group can be split across, ordered in descending order of
+    /// their issuable tokens. Only vaults that accept new issues, are not banned and use
+    /// `currency_id` as collateral are returned. At most `MAX_VAULT_SELECTION_CANDIDATES` vaults
+    /// are scanned, starting from a position derived from `random`, so that the cost of the
+    /// lookup is bounded without favouring any vault.
+    pub fn get_vaults_for_issue_group(
+        currency_id: CurrencyId<T>,
+        random: H256,
+    ) -> Result<Vec<(T::AccountId, Amount<T>)>, DispatchError> {
+        let mut candidates = Self::vault_selection_candidates(random)
+            .filter(|(_, vault)| vault.currency_id == currency_id)
+            .filter_map(|(account_id, vault)| {
+                let rich_vault: RichVault<T> = vault.into();
+                if rich_vault.is_banned() {
+                    return None;
+                }
+                // returns zero for vaults that do not accept new issues
+                let issuable_tokens = Self::get_issuable_tokens_from_vault(account_id.clone()).ok()?;
+                if !issuable_tokens.is_zero() {
+                    Some((account_id, issuable_tokens))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<(_, _)>>();
+
+        candidates.sort_by(|a, b| b.1.amount().cmp(&a.1.amount()));
+        Ok(candidates)
+    }
+
     /// Get all vaults with non-zero issued (thus redeemable) tokens, ordered in descending order of this amount
     pub fn get_vaults_with_redeemable_tokens() -> Result<Vec<(T::AccountId, Amount<T>)>, DispatchError> {
         // find all vault accounts with sufficient collateral
@@ -1470,6 +1735,105 @@ impl<T: Config> Pallet<T> {
         Ok(vaults_with_redeemable_tokens)
     }
 
+    /// Select a vault to issue `amount` tokens, using `random` as the source of randomness.
+    /// Only vaults that accept new issues, are not banned, use `currency_id` as collateral and
+    /// have at least `amount` issuable tokens are considered. The probability of a vault being
+    /// selected is proportional to its issuable tokens. At most `MAX_VAULT_SELECTION_CANDIDATES`
+    /// vaults are scanned, see `vault_selection_candidates`.
+    pub fn select_vault_for_issue(
+        amount: &Amount<T>,
+        currency_id: CurrencyId<T>,
+        random: H256,
+    ) -> Result<T::AccountId, DispatchError> {
+        let candidates = Self::vault_selection_candidates(random)
+            .filter(|(_, vault)| vault.currency_id == currency_id)
+            .filter_map(|(account_id, vault)| {
+                let rich_vault: RichVault<T> = vault.into();
+                if rich_vault.is_banned() {
+                    return None;
+                }
+                // returns zero for vaults that do not accept new issues
+                let issuable_tokens = Self::get_issuable_tokens_from_vault(account_id.clone()).ok()?;
+                if !issuable_tokens.is_zero() && issuable_tokens.ge(amount).ok()? {
+                    Some((account_id, issuable_tokens))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<(_, _)>>();
+
+        Self::select_weighted_vault(candidates, random)
+    }
+
+    /// Select a vault to redeem `amount` tokens, using `random` as the source of randomness.
+    /// Only active vaults that are not banned, use `currency_id` as collateral and have at least
+    /// `amount` redeemable tokens are considered. The probability of a vault being selected is
+    /// proportional to its redeemable tokens. At most `MAX_VAULT_SELECTION_CANDIDATES` vaults are
+    /// scanned, see `vault_selection_candidates`.
+    pub fn select_vault_for_redeem(
+        amount: &Amount<T>,
+        currency_id: CurrencyId<T>,
+        random: H256,
+    ) -> Result<T::AccountId, DispatchError> {
+        let candidates = Self::vault_selection_candidates(random)
+            .filter(|(_, vault)| vault.currency_id == currency_id && matches!(vault.status, VaultStatus::Active(_)))
+            .filter_map(|(account_id, vault)| {
+                let rich_vault: RichVault<T> = vault.into();
+                if rich_vault.is_banned() {
+                    return None;
+                }
+                let redeemable_tokens = rich_vault.redeemable_tokens().ok()?;
+                if !redeemable_tokens.is_zero() && redeemable_tokens.ge(amount).ok()? {
+                    Some((account_id, redeemable_tokens))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<(_, _)>>();
+
+        Self::select_weighted_vault(candidates, random)
+    }
+
+    /// Iterate over at most `MAX_VAULT_SELECTION_CANDIDATES` vaults, so that the cost of a
+    /// selection is bounded. The scan starts at a key derived from `random` and wraps around at
+    /// the end of `Vaults`, so every vault is equally likely to be scanned.
+    fn vault_selection_candidates(random: H256) -> impl Iterator<Item = (T::AccountId, DefaultVault<T>)> {
+        // the keys of `Vaults` are hashed, so a random key is a uniformly distributed starting
+        // point; `random` is hashed again so that the start is independent of the weighted pick
+        let mut start = Vaults::<T>::final_prefix().to_vec();
+        start.extend_from_slice(&sp_io::hashing::blake2_256(random.as_bytes()));
+        let end = start.clone();
+        Vaults::<T>::iter_from(start)
+            .chain(
+                Vaults::<T>::iter().take_while(move |(account_id, _)| Vaults::<T>::hashed_key_for(account_id) <= end),
+            )
+            .take(MAX_VAULT_SELECTION_CANDIDATES as usize)
+    }
+
+    /// Pick one of the `candidates` with a probability proportional to its capacity
+    fn select_weighted_vault(
+        candidates: Vec<(T::AccountId, Amount<T>)>,
+        random: H256,
+    ) -> Result<T::AccountId, DispatchError> {
+        let total_capacity = candidates
+            .iter()
+            .try_fold(U256::zero(), |total, (_, capacity)| {
+                total.checked_add(capacity.amount().into())
+            })
+            .ok_or(Error::<T>::ArithmeticOverflow)?;
+        ensure!(!total_capacity.is_zero(), Error::<T>::NoVaultWithSufficientTokens);
+
+        let mut target = U256::from_big_endian(random.as_bytes()) % total_capacity;
+        for (account_id, capacity) in candidates {
+            let capacity: U256 = capacity.amount().into();
+            if target < capacity {
+                return Ok(account_id);
+            }
+            target -= capacity;
+        }
+        Err(Error::<T>::NoVaultWithSufficientTokens.into())
+    }
+
     /// Get the amount of tokens a vault can issue
     pub fn get_issuable_tokens_from_vault(vault_id: T::AccountId) -> Result<Amount<T>, DispatchError> {
         let vault = Self::get_active_rich_vault_from_id(&vault_id)?;
@@ -3,6 +3,7 @@ use crate::{
     mock::*,
     types::{BalanceOf, BtcAddress},
     BtcPublicKey, CurrencySource, DispatchError, Error, UpdatableVault, Vault, VaultStatus, Wallet,
+    MAX_VAULT_SELECTION_CANDIDATES,
 };
 use codec::Decode;
 use currency::Amount;
@@ -10,7 +11,7 @@ use frame_support::{assert_err, assert_noop, assert_ok};
 use mocktopus::mocking::*;
 use security::Pallet as Security;
 use sp_arithmetic::{traits::One, FixedPointNumber, FixedU128};
-use sp_core::U256;
+use sp_core::{H256, U256};
 use sp_runtime::offchain::{testing::TestTransactionPoolExt, TransactionPoolExt};
 use sp_std::convert::TryInto;
 
@@ -716,6 +717,67 @@ fn liquidate_at_most_secure_threshold() {
     });
 }
 
+fn setup_partial_liquidation(penalty: FixedU128) -> <Test as frame_system::Config>::AccountId {
+    let vault_id = create_vault_with_collateral(DEFAULT_ID, 1700);
+    convert_to.mock_safe(convert_with_exchange_rate(1));
+    assert_ok!(VaultRegistry::try_increase_to_be_issued_tokens(
+        &vault_id,
+        &wrapped(100)
+    ));
+    assert_ok!(VaultRegistry::issue_tokens(&vault_id, &wrapped(100)));
+
+    assert_ok!(VaultRegistry::adjust_liquidation_penalty(
+        Origin::root(),
+        DEFAULT_TESTING_CURRENCY,
+        penalty
+    ));
+    // 1700 collateral is now worth 170 tokens, below the 200% secure threshold
+    convert_to.mock_safe(convert_with_exchange_rate(10));
+    vault_id
+}
+
+#[test]
+fn partially_liquidate_vault_restores_secure_threshold() {
+    run_test(|| {
+        let vault_id = setup_partial_liquidation(FixedU128::checked_from_rational(25, 100).unwrap()); // 25%
+        let liquidation_vault_before = VaultRegistry::get_rich_liquidation_vault(DEFAULT_TESTING_CURRENCY);
+
+        // (200 - 170) / (2 - 1.25) = 40 tokens, slashing 40 * 1.25 * 10 = 500 collateral
+        assert_eq!(VaultRegistry::partially_liquidate_vault(&vault_id), Ok(amount(500)));
+        assert_emitted!(Event::PartiallyLiquidateVault(vault_id, 40, 500));
+
+        let vault = VaultRegistry::get_active_vault_from_id(&vault_id).unwrap();
+        assert_eq!(vault.issued_tokens, 60);
+        assert_eq!(VaultRegistry::get_backing_collateral(&vault_id).unwrap(), amount(1200));
+        assert_eq!(VaultRegistry::is_vault_below_secure_threshold(&vault_id), Ok(false));
+
+        let liquidation_vault_after = VaultRegistry::get_rich_liquidation_vault(DEFAULT_TESTING_CURRENCY);
+        assert_eq!(
+            liquidation_vault_after.data.issued_tokens,
+            liquidation_vault_before.data.issued_tokens + 40
+        );
+        assert_eq!(
+            ext::currency::get_reserved_balance::<Test>(CurrencyId::DOT, &liquidation_vault_before.id()),
+            amount(500)
+        );
+    });
+}
+
+#[test]
+fn partially_liquidate_vault_falls_back_to_full_liquidation() {
+    run_test(|| {
+        // slashing 200% per token can never restore the 200% secure threshold
+        let vault_id = setup_partial_liquidation(FixedU128::one());
+
+        assert_ok!(VaultRegistry::partially_liquidate_vault(&vault_id));
+        assert_not_emitted!(Event::PartiallyLiquidateVault(vault_id, 40, 500));
+
+        let vault = <crate::Vaults<Test>>::get(&vault_id).unwrap();
+        assert!(matches!(vault.status, VaultStatus::Liquidated));
+        assert_eq!(vault.issued_tokens, 0);
+    });
+}
+
 #[test]
 fn is_collateral_below_threshold_true_succeeds() {
     run_test(|| {
@@ -1319,6 +1381,239 @@ mod get_vaults_with_redeemable_tokens_test {
     }
 }
 
+mod select_vault_tests {
+    use super::*;
+
+    #[test]
+    fn select_vault_for_issue_succeeds() {
+        run_test(|| {
+            let id1 = 3;
+            create_vault_with_collateral(id1, 100);
+            let id2 = 4;
+            create_vault_with_collateral(id2, 50);
+
+            for random in vec![H256::zero(), H256::repeat_byte(0x42), H256::repeat_byte(0xff)] {
+                let vault_id = VaultRegistry::select_vault_for_issue(&wrapped(1), DOT, random).unwrap();
+                assert!(vault_id == id1 || vault_id == id2);
+            }
+        })
+    }
+
+    #[test]
+    fn select_vault_for_issue_filters_out_unsuitable_vaults() {
+        run_test(|| {
+            let id1 = 3;
+            create_vault_with_collateral(id1, 100);
+            let issuable_tokens1 = VaultRegistry::get_issuable_tokens_from_vault(id1).unwrap();
+
+            // does not accept new issues
+            let id2 = 4;
+            create_vault_with_collateral(id2, 100);
+            assert_ok!(VaultRegistry::accept_new_issues(Origin::signed(id2), false));
+
+            // banned
+            let id3 = 5;
+            create_vault_with_collateral(id3, 100);
+            VaultRegistry::get_rich_vault_from_id(&id3).unwrap().ban_until(1000);
+
+            for random in vec![H256::zero(), H256::repeat_byte(0x42), H256::repeat_byte(0xff)] {
+                assert_eq!(VaultRegistry::select_vault_for_issue(&wrapped(1), DOT, random), Ok(id1));
+            }
+
+            // insufficient capacity
+            let amount = issuable_tokens1.checked_add(&wrapped(1)).unwrap();
+            assert_err!(
+                VaultRegistry::select_vault_for_issue(&amount, DOT, H256::zero()),
+                TestError::NoVaultWithSufficientTokens
+            );
+            // wrong collateral currency
+            assert_err!(
+                VaultRegistry::select_vault_for_issue(&wrapped(1), INTERBTC, H256::zero()),
+                TestError::NoVaultWithSufficientTokens
+            );
+        })
+    }
+
+    /// Registers more vaults than are scanned in one selection, returning the vault that comes
+    /// last in storage order.
+    fn insert_vaults_beyond_selection_bound() -> AccountId {
+        for id in 1000..(1000 + MAX_VAULT_SELECTION_CANDIDATES as u64 + 50) {
+            VaultRegistry::insert_vault(&id, Vault::new(id, Default::default(), CurrencyId::DOT));
+        }
+        crate::Vaults::<Test>::iter_keys().last().unwrap()
+    }
+
+    #[test]
+    fn select_vault_for_issue_scans_beyond_selection_bound() {
+        run_test(|| {
+            let last_id = insert_vaults_beyond_selection_bound();
+            // only the vault that comes last in storage order can issue
+            VaultRegistry::get_issuable_tokens_from_vault.mock_safe(move |id| {
+                let amount = if id == last_id { 100 } else { 0 };
+                MockResult::Return(Ok(wrapped(amount)))
+            });
+
+            let selected = (0..=255u8)
+                .filter(|&byte| {
+                    VaultRegistry::select_vault_for_issue(&wrapped(1), DOT, H256::repeat_byte(byte)) == Ok(last_id)
+                })
+                .count();
+            // the vault is scanned for some, but not all, of the random values
+            assert!(selected > 0 && selected < 256);
+        })
+    }
+
+    #[test]
+    fn get_vaults_for_issue_group_scans_beyond_selection_bound() {
+        run_test(|| {
+            let last_id = insert_vaults_beyond_selection_bound();
+            VaultRegistry::get_issuable_tokens_from_vault.mock_safe(|_| MockResult::Return(Ok(wrapped(100))));
+
+            let included = (0..=255u8)
+                .filter(|&byte| {
+                    let vaults = VaultRegistry::get_vaults_for_issue_group(DOT, H256::repeat_byte(byte)).unwrap();
+                    assert_eq!(vaults.len(), MAX_VAULT_SELECTION_CANDIDATES as usize);
+                    vaults.iter().any(|(id, _)| *id == last_id)
+                })
+                .count();
+            assert!(included > 0 && included < 256);
+        })
+    }
+
+    #[test]
+    fn select_vault_for_redeem_succeeds() {
+        run_test(|| {
+            let id1 = 3;
+            create_vault_and_issue_tokens(10, DEFAULT_COLLATERAL, id1);
+
+            let id2 = 4;
+            create_vault_and_issue_tokens(20, DEFAULT_COLLATERAL, id2);
+
+            // only the second vault can redeem this amount
+            for random in vec![H256::zero(), H256::repeat_byte(0x42), H256::repeat_byte(0xff)] {
+                assert_eq!(
+                    VaultRegistry::select_vault_for_redeem(&wrapped(15), DOT, random),
+                    Ok(id2)
+                );
+            }
+
+            // liquidated vaults are never selected
+            assert_ok!(VaultRegistry::liquidate_vault(&id2));
+            assert_err!(
+                VaultRegistry::select_vault_for_redeem(&wrapped(15), DOT, H256::zero()),
+                TestError::NoVaultWithSufficientTokens
+            );
+            assert_eq!(
+                VaultRegistry::select_vault_for_redeem(&wrapped(5), DOT, H256::zero()),
+                Ok(id1)
+            );
+        })
+    }
+}
+
+mod threshold_check_tests {
+    use super::*;
+    use crate::{CollateralizationLevel, MAX_THRESHOLD_CHECKS_PER_BLOCK};
+
+    fn mock_collateralization(below_secure: bool, below_premium: bool, below_liquidation: bool) {
+        VaultRegistry::is_vault_below_secure_threshold.mock_safe(move |_| MockResult::Return(Ok(below_secure)));
+        VaultRegistry::is_vault_below_premium_threshold.mock_safe(move |_| MockResult::Return(Ok(below_premium)));
+        VaultRegistry::is_vault_below_liquidation_threshold
+            .mock_safe(move |_, _| MockResult::Return(Ok(below_liquidation)));
+    }
+
+    #[test]
+    fn check_vault_thresholds_emits_events_when_crossing_thresholds() {
+        run_test(|| {
+            let id = create_sample_vault();
+
+            mock_collateralization(true, false, false);
+            VaultRegistry::check_vault_thresholds(MAX_THRESHOLD_CHECKS_PER_BLOCK);
+            assert_emitted!(Event::VaultBelowSecureThreshold(id), 1);
+
+            // no event is emitted while the collateralization stays at the same level
+            VaultRegistry::check_vault_thresholds(MAX_THRESHOLD_CHECKS_PER_BLOCK);
+            assert_emitted!(Event::VaultBelowSecureThreshold(id), 1);
+
+            mock_collateralization(true, true, false);
+            VaultRegistry::check_vault_thresholds(MAX_THRESHOLD_CHECKS_PER_BLOCK);
+            assert_emitted!(Event::VaultBelowPremiumThreshold(id));
+
+            mock_collateralization(false, false, false);
+            VaultRegistry::check_vault_thresholds(MAX_THRESHOLD_CHECKS_PER_BLOCK);
+            assert_emitted!(Event::VaultRecoveredAboveThreshold(
+                id,
+                CollateralizationLevel::AboveSecureThreshold
+            ));
+        })
+    }
+
+    #[test]
+    fn check_vault_thresholds_checks_a_bounded_number_of_vaults() {
+        run_test(|| {
+            let ids = vec![3, 4, 5];
+            for id in ids.iter() {
+                create_vault(*id);
+            }
+            mock_collateralization(true, false, false);
+            let emitted = |ids: &Vec<u64>| {
+                ids.iter()
+                    .filter(|id| {
+                        System::events()
+                            .iter()
+                            .any(|a| a.event == TestEvent::VaultRegistry(Event::VaultBelowSecureThreshold(**id)))
+                    })
+                    .count()
+            };
+
+            VaultRegistry::check_vault_thresholds(2);
+            assert_eq!(emitted(&ids), 2);
+
+            // continues with the remaining vault
+            VaultRegistry::check_vault_thresholds(2);
+            assert_eq!(emitted(&ids), 3);
+        })
+    }
+
+    #[test]
+    fn check_vault_thresholds_liquidates_vaults_below_liquidation_threshold() {
+        run_test(|| {
+            let id = create_sample_vault();
+
+            mock_collateralization(true, true, true);
+            VaultRegistry::check_vault_thresholds(MAX_THRESHOLD_CHECKS_PER_BLOCK);
+            assert_emitted!(Event::VaultBelowLiquidationThreshold(id));
+            assert!(VaultRegistry::is_vault_liquidated(&id).unwrap());
+        })
+    }
+
+    #[test]
+    fn check_vault_thresholds_does_not_liquidate_during_shutdown() {
+        run_test(|| {
+            let id = create_sample_vault();
+            ext::security::ensure_parachain_status_not_shutdown::<Test>
+                .mock_safe(|| MockResult::Return(Err(SecurityError::ParachainShutdown.into())));
+
+            mock_collateralization(true, true, true);
+            VaultRegistry::check_vault_thresholds(MAX_THRESHOLD_CHECKS_PER_BLOCK);
+            assert_emitted!(Event::VaultBelowLiquidationThreshold(id));
+            assert!(!VaultRegistry::is_vault_liquidated(&id).unwrap());
+        })
+    }
+
+    #[test]
+    fn check_vault_thresholds_ignores_liquidated_vaults() {
+        run_test(|| {
+            let id = create_sample_vault();
+            assert_ok!(VaultRegistry::liquidate_vault(&id));
+
+            mock_collateralization(true, true, true);
+            VaultRegistry::check_vault_thresholds(MAX_THRESHOLD_CHECKS_PER_BLOCK);
+            assert_not_emitted!(Event::VaultBelowLiquidationThreshold(id));
+        })
+    }
+}
+
 // #[test]
 // fn wallet_add_btc_address_succeeds() {
 //     run_test(|| {
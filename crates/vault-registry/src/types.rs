@@ -137,6 +137,22 @@ impl Default for VaultStatus {
     }
 }
 
+/// The lowest collateral threshold a vault was last found to be below, ordered from the
+/// healthiest to the least healthy level
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum CollateralizationLevel {
+    AboveSecureThreshold,
+    BelowSecureThreshold,
+    BelowPremiumThreshold,
+    BelowLiquidationThreshold,
+}
+
+impl Default for CollateralizationLevel {
+    fn default() -> Self {
+        CollateralizationLevel::AboveSecureThreshold
+    }
+}
+
 #[derive(Encode, Decode, Clone, PartialEq)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub struct Vault<AccountId, BlockNumber, Balance, CurrencyId> {
@@ -566,6 +582,18 @@ impl<T: Config> RichVault<T> {
         Ok(liquidated_collateral_excluding_to_be_redeemed)
     }
 
+    /// Moves `tokens` of the issued tokens to the liquidation vault and slashes `collateral`
+    /// to it, keeping the vault active with the remaining tokens and collateral.
+    pub(crate) fn liquidate_partially(&mut self, tokens: &Amount<T>, collateral: &Amount<T>) -> DispatchResult {
+        self.slash_to_liquidation_vault(collateral)?;
+
+        let mut liquidation_vault = Pallet::<T>::get_rich_liquidation_vault(self.data.currency_id);
+        liquidation_vault.increase_issued(tokens)?;
+
+        // also withdraws the stake from the reward pool
+        self.decrease_issued(tokens)
+    }
+
     pub fn ensure_not_banned(&self) -> DispatchResult {
         if self.is_banned() {
             Err(Error::<T>::VaultBanned.into())
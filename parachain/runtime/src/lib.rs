@@ -8,7 +8,7 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
-use bitcoin::types::H256Le;
+use bitcoin::types::{BlockChain, H256Le};
 use frame_support::{
     dispatch::{DispatchError, DispatchResult},
     traits::EnsureOrigin,
@@ -52,7 +52,7 @@ pub use sp_runtime::BuildStorage;
 pub use sp_runtime::{Perbill, Permill};
 
 // interBTC exports
-pub use btc_relay::{bitcoin, Call as RelayCall, TARGET_SPACING};
+pub use btc_relay::{bitcoin, Call as RelayCall, PaymentVerification, RichBlockHeader, TARGET_SPACING};
 pub use module_oracle_rpc_runtime_api::BalanceWrapper;
 pub use security::StatusCode;
 
@@ -767,6 +767,7 @@ impl btc_relay::Config for Runtime {
     type Event = Event;
     type WeightInfo = ();
     type ParachainBlocksPerBitcoinBlock = ParachainBlocksPerBitcoinBlock;
+    type ChainParams = btc_relay::BitcoinChainParams;
 }
 
 parameter_types! {
@@ -901,14 +902,14 @@ impl refund::Config for Runtime {
     type WeightInfo = ();
 }
 
-pub use issue::{Event as IssueEvent, IssueRequest};
+pub use issue::{Event as IssueEvent, IssueGroupStatus, IssueRequest};
 
 impl issue::Config for Runtime {
     type Event = Event;
     type WeightInfo = ();
 }
 
-pub use redeem::{Event as RedeemEvent, RedeemRequest};
+pub use redeem::{Event as RedeemEvent, RedeemPaymentVerification, RedeemRequest};
 
 impl redeem::Config for Runtime {
     type Event = Event;
@@ -1194,10 +1195,37 @@ impl_runtime_apis! {
     impl module_btc_relay_rpc_runtime_api::BtcRelayApi<
         Block,
         H256Le,
+        BlockChain,
+        RichBlockHeader<BlockNumber>,
     > for Runtime {
         fn verify_block_header_inclusion(block_hash: H256Le) -> Result<(), DispatchError> {
             BTCRelay::verify_block_header_inclusion(block_hash, None).map(|_| ())
         }
+
+        fn get_best_block() -> H256Le {
+            BTCRelay::get_best_block()
+        }
+
+        fn get_best_block_height() -> u32 {
+            BTCRelay::get_best_block_height()
+        }
+
+        fn get_block_header(block_hash: H256Le) -> Option<RichBlockHeader<BlockNumber>> {
+            BTCRelay::get_block_header_from_hash(block_hash).ok()
+        }
+
+        fn get_block_header_at_height(block_height: u32) -> Option<RichBlockHeader<BlockNumber>> {
+            BTCRelay::get_main_chain_block_header(block_height).ok()
+        }
+
+        fn get_forks() -> Vec<(BlockChain, H256Le)> {
+            BTCRelay::get_forks()
+        }
+
+        fn verify_transaction_inclusion(tx_id: H256Le, raw_merkle_proof: Vec<u8>, confirmations: u32) -> Result<(), DispatchError> {
+            let merkle_proof = BTCRelay::parse_merkle_proof(&raw_merkle_proof)?;
+            BTCRelay::_verify_transaction_inclusion(tx_id, merkle_proof, Some(confirmations))
+        }
     }
 
     impl module_oracle_rpc_runtime_api::OracleApi<
@@ -1288,7 +1316,9 @@ impl_runtime_apis! {
         Block,
         AccountId,
         H256,
-        IssueRequest<AccountId, BlockNumber, Balance>
+        IssueRequest<AccountId, BlockNumber, Balance>,
+        PaymentVerification<Balance>,
+        IssueGroupStatus<Balance>
     > for Runtime {
         fn get_issue_requests(account_id: AccountId) -> Vec<(H256, IssueRequest<AccountId, BlockNumber, Balance>)> {
             Issue::get_issue_requests_for_account(account_id)
@@ -1297,13 +1327,22 @@ impl_runtime_apis! {
         fn get_vault_issue_requests(account_id: AccountId) -> Vec<(H256, IssueRequest<AccountId, BlockNumber, Balance>)> {
             Issue::get_issue_requests_for_vault(account_id)
         }
+
+        fn verify_issue_payment(issue_id: H256, raw_merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> Result<PaymentVerification<Balance>, DispatchError> {
+            Issue::verify_issue_payment(issue_id, raw_merkle_proof, raw_tx)
+        }
+
+        fn get_issue_group_status(group_id: H256) -> Result<IssueGroupStatus<Balance>, DispatchError> {
+            Issue::get_issue_group_status(group_id)
+        }
     }
 
     impl module_redeem_rpc_runtime_api::RedeemApi<
         Block,
         AccountId,
         H256,
-        RedeemRequest<AccountId, BlockNumber, Balance>
+        RedeemRequest<AccountId, BlockNumber, Balance>,
+        RedeemPaymentVerification<Balance>
     > for Runtime {
         fn get_redeem_requests(account_id: AccountId) -> Vec<(H256, RedeemRequest<AccountId, BlockNumber, Balance>)> {
             Redeem::get_redeem_requests_for_account(account_id)
@@ -1312,6 +1351,10 @@ impl_runtime_apis! {
         fn get_vault_redeem_requests(account_id: AccountId) -> Vec<(H256, RedeemRequest<AccountId, BlockNumber, Balance>)> {
             Redeem::get_redeem_requests_for_vault(account_id)
         }
+
+        fn verify_redeem_payment(redeem_id: H256, raw_merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> Result<RedeemPaymentVerification<Balance>, DispatchError> {
+            Redeem::verify_redeem_payment(redeem_id, raw_merkle_proof, raw_tx)
+        }
     }
 
     impl module_refund_rpc_runtime_api::RefundApi<
@@ -1337,7 +1380,8 @@ impl_runtime_apis! {
         Block,
         AccountId,
         H256,
-        ReplaceRequest<AccountId, BlockNumber, Balance>
+        ReplaceRequest<AccountId, BlockNumber, Balance>,
+        PaymentVerification<Balance>
     > for Runtime {
         fn get_old_vault_replace_requests(account_id: AccountId) -> Vec<(H256, ReplaceRequest<AccountId, BlockNumber, Balance>)> {
             Replace::get_replace_requests_for_old_vault(account_id)
@@ -1346,6 +1390,10 @@ impl_runtime_apis! {
         fn get_new_vault_replace_requests(account_id: AccountId) -> Vec<(H256, ReplaceRequest<AccountId, BlockNumber, Balance>)> {
             Replace::get_replace_requests_for_new_vault(account_id)
         }
+
+        fn verify_replace_payment(replace_id: H256, raw_merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> Result<PaymentVerification<Balance>, DispatchError> {
+            Replace::verify_replace_payment(replace_id, raw_merkle_proof, raw_tx)
+        }
     }
 }
 
@@ -1,4 +1,7 @@
-use bitcoin::utils::{virtual_transaction_size, InputType, TransactionInputMetadata, TransactionOutputMetadata};
+use bitcoin::{
+    utils::{virtual_transaction_size, InputType, TransactionInputMetadata, TransactionOutputMetadata},
+    Network as BitcoinNetwork,
+};
 use cumulus_primitives_core::ParaId;
 use hex_literal::hex;
 use interbtc_runtime::{
@@ -68,6 +71,7 @@ const DEFAULT_MAX_DELAY_MS: u32 = 60 * 60 * 1000; // one hour
 const DEFAULT_DUST_VALUE: Balance = 1000;
 const DEFAULT_BITCOIN_CONFIRMATIONS: u32 = 1;
 const SECURE_BITCOIN_CONFIRMATIONS: u32 = 6;
+const DEFAULT_PRUNING_DEPTH: u32 = 2 * 2016; // two difficulty adjustment periods
 
 fn get_properties() -> Map<String, Value> {
     let mut properties = Map::new();
@@ -293,8 +297,13 @@ fn testnet_genesis(
         btc_relay: BTCRelayConfig {
             bitcoin_confirmations,
             parachain_confirmations: bitcoin_confirmations.saturating_mul(BITCOIN_BLOCK_SPACING),
-            disable_difficulty_check: true,
+            disable_difficulty_check: false,
             disable_inclusion_check: false,
+            pruning_depth: DEFAULT_PRUNING_DEPTH,
+            bitcoin_network: BitcoinNetwork::Testnet,
+            checkpoints: vec![],
+            confirmation_tiers: vec![],
+            block_header_snapshot: None,
         },
         issue: IssueConfig {
             issue_period: DAYS,
@@ -505,6 +514,11 @@ fn mainnet_genesis(
             parachain_confirmations: bitcoin_confirmations.saturating_mul(BITCOIN_BLOCK_SPACING),
             disable_difficulty_check: false,
             disable_inclusion_check: false,
+            pruning_depth: DEFAULT_PRUNING_DEPTH,
+            bitcoin_network: BitcoinNetwork::Mainnet,
+            checkpoints: vec![],
+            confirmation_tiers: vec![],
+            block_header_snapshot: None,
         },
         issue: IssueConfig {
             issue_period: DAYS,
@@ -97,6 +97,31 @@ pub mod issue {
         /// the status of this issue request
         pub status: IssueRequestStatus,
     }
+
+    #[derive(Encode, Decode, Default, Clone, PartialEq)]
+    #[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+    pub struct IssueGroupStatus<Balance> {
+        /// the issue requests in this group
+        pub issue_ids: Vec<H256>,
+        #[cfg_attr(feature = "std", serde(bound(deserialize = "Balance: std::str::FromStr")))]
+        #[cfg_attr(feature = "std", serde(deserialize_with = "deserialize_from_string"))]
+        #[cfg_attr(feature = "std", serde(bound(serialize = "Balance: std::fmt::Display")))]
+        #[cfg_attr(feature = "std", serde(serialize_with = "serialize_as_string"))]
+        /// the number of tokens that will be transferred to the user by all requests
+        pub amount: Balance,
+        #[cfg_attr(feature = "std", serde(bound(deserialize = "Balance: std::str::FromStr")))]
+        #[cfg_attr(feature = "std", serde(deserialize_with = "deserialize_from_string"))]
+        #[cfg_attr(feature = "std", serde(bound(serialize = "Balance: std::fmt::Display")))]
+        #[cfg_attr(feature = "std", serde(serialize_with = "serialize_as_string"))]
+        /// the number of tokens that were transferred to the user by completed requests
+        pub amount_issued: Balance,
+        /// the number of requests that are neither executed nor cancelled
+        pub pending: u32,
+        /// the number of requests that were executed
+        pub completed: u32,
+        /// the number of requests that were cancelled
+        pub cancelled: u32,
+    }
 }
 
 #[cfg(feature = "std")]
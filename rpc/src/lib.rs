@@ -5,10 +5,15 @@
 
 #![warn(missing_docs)]
 
+use btc_relay::{bitcoin::types::BlockChain, PaymentVerification, RichBlockHeader};
 use primitives::{
-    issue::IssueRequest, redeem::RedeemRequest, refund::RefundRequest, replace::ReplaceRequest, AccountId, Balance,
-    Block, BlockNumber, CurrencyId, H256Le, Nonce,
+    issue::{IssueGroupStatus, IssueRequest},
+    redeem::RedeemRequest,
+    refund::RefundRequest,
+    replace::ReplaceRequest,
+    AccountId, Balance, Block, BlockNumber, CurrencyId, H256Le, Nonce,
 };
+use redeem::RedeemPaymentVerification;
 pub use sc_rpc_api::DenyUnsafe;
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
@@ -38,15 +43,33 @@ where
     C: Send + Sync + 'static,
     C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
-    C::Api: module_btc_relay_rpc::BtcRelayRuntimeApi<Block, H256Le>,
+    C::Api: module_btc_relay_rpc::BtcRelayRuntimeApi<Block, H256Le, BlockChain, RichBlockHeader<BlockNumber>>,
     C::Api: module_oracle_rpc::OracleRuntimeApi<Block, Balance, CurrencyId>,
     C::Api: module_relay_rpc::RelayRuntimeApi<Block, AccountId>,
     C::Api: module_vault_registry_rpc::VaultRegistryRuntimeApi<Block, AccountId, Balance, FixedU128, CurrencyId>,
-    C::Api: module_issue_rpc::IssueRuntimeApi<Block, AccountId, H256, IssueRequest<AccountId, BlockNumber, Balance>>,
-    C::Api: module_redeem_rpc::RedeemRuntimeApi<Block, AccountId, H256, RedeemRequest<AccountId, BlockNumber, Balance>>,
+    C::Api: module_issue_rpc::IssueRuntimeApi<
+        Block,
+        AccountId,
+        H256,
+        IssueRequest<AccountId, BlockNumber, Balance>,
+        PaymentVerification<Balance>,
+        IssueGroupStatus<Balance>,
+    >,
+    C::Api: module_redeem_rpc::RedeemRuntimeApi<
+        Block,
+        AccountId,
+        H256,
+        RedeemRequest<AccountId, BlockNumber, Balance>,
+        RedeemPaymentVerification<Balance>,
+    >,
     C::Api: module_refund_rpc::RefundRuntimeApi<Block, AccountId, H256, RefundRequest<AccountId, Balance>>,
-    C::Api:
-        module_replace_rpc::ReplaceRuntimeApi<Block, AccountId, H256, ReplaceRequest<AccountId, BlockNumber, Balance>>,
+    C::Api: module_replace_rpc::ReplaceRuntimeApi<
+        Block,
+        AccountId,
+        H256,
+        ReplaceRequest<AccountId, BlockNumber, Balance>,
+        PaymentVerification<Balance>,
+    >,
     C::Api: BlockBuilder<Block>,
     P: TransactionPool + 'static,
 {
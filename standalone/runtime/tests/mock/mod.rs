@@ -933,7 +933,7 @@ impl TransactionGenerator {
             .with_previous_hash(prev_hash)
             .with_version(4)
             .with_coinbase(&self.address, 50, 3)
-            .with_timestamp(1588814835)
+            .with_timestamp(1588813835 + (height - 1) * 1000)
             .add_transaction(transaction.clone())
             .mine(U256::from(2).pow(254.into()))
             .unwrap();
@@ -951,7 +951,7 @@ impl TransactionGenerator {
 
         // Mine six new blocks to get over required confirmations
         let mut prev_block_hash = block.header.hash;
-        let mut timestamp = 1588814835;
+        let mut timestamp = block.header.timestamp;
         for _ in 0..extra_confirmations {
             height += 1;
             timestamp += 1000;
@@ -1050,6 +1050,11 @@ impl ExtBuilder {
             parachain_confirmations: CONFIRMATIONS,
             disable_difficulty_check: false,
             disable_inclusion_check: false,
+            pruning_depth: 0,
+            bitcoin_network: bitcoin::Network::Mainnet,
+            checkpoints: vec![],
+            confirmation_tiers: vec![],
+            block_header_snapshot: None,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
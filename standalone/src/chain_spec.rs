@@ -1,4 +1,7 @@
-use bitcoin::utils::{virtual_transaction_size, InputType, TransactionInputMetadata, TransactionOutputMetadata};
+use bitcoin::{
+    utils::{virtual_transaction_size, InputType, TransactionInputMetadata, TransactionOutputMetadata},
+    Network as BitcoinNetwork,
+};
 use hex_literal::hex;
 use interbtc_runtime::{
     AccountId, AuraConfig, BTCRelayConfig, CurrencyId, FeeConfig, GenesisConfig, GrandpaConfig, IssueConfig,
@@ -257,8 +260,13 @@ fn testnet_genesis(
         btc_relay: BTCRelayConfig {
             bitcoin_confirmations,
             parachain_confirmations: bitcoin_confirmations.saturating_mul(BITCOIN_BLOCK_SPACING),
-            disable_difficulty_check: true,
+            disable_difficulty_check: false,
             disable_inclusion_check: false,
+            pruning_depth: 2 * 2016, // two difficulty adjustment periods
+            bitcoin_network: BitcoinNetwork::Testnet,
+            checkpoints: vec![],
+            confirmation_tiers: vec![],
+            block_header_snapshot: None,
         },
         issue: IssueConfig {
             issue_period: DAYS,